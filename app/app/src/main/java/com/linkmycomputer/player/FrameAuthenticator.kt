@@ -0,0 +1,91 @@
+package com.linkmycomputer.player
+
+import java.nio.ByteBuffer
+import java.security.MessageDigest
+import javax.crypto.Mac
+import javax.crypto.spec.SecretKeySpec
+
+enum class Direction(val context: String) {
+    HOST_TO_PHONE("host->phone"),
+    PHONE_TO_HOST("phone->host")
+}
+
+class FrameAuthenticator(private val key: ByteArray) {
+    private var nextCounter = 0L
+    private var highestCounter: Long? = null
+    private var replayBitmap = 0L
+
+    @Synchronized
+    fun seal(body: ByteArray): ByteArray {
+        val signed = ByteBuffer.allocate(body.size + COUNTER_LEN)
+            .put(body)
+            .putLong(nextCounter)
+            .array()
+        nextCounter += 1
+        return signed + hmac(key, signed)
+    }
+
+    @Synchronized
+    fun open(datagram: ByteArray): ByteArray? {
+        if (datagram.size <= COUNTER_LEN + TAG_LEN) {
+            return null
+        }
+
+        val signed = datagram.copyOfRange(0, datagram.size - TAG_LEN)
+        val tag = datagram.copyOfRange(datagram.size - TAG_LEN, datagram.size)
+        if (!MessageDigest.isEqual(hmac(key, signed), tag)) {
+            return null
+        }
+
+        val counter = ByteBuffer.wrap(signed, signed.size - COUNTER_LEN, COUNTER_LEN).long
+        if (!acceptCounter(counter)) {
+            return null
+        }
+        return signed.copyOfRange(0, signed.size - COUNTER_LEN)
+    }
+
+    private fun acceptCounter(counter: Long): Boolean {
+        val highest = highestCounter
+        if (highest == null || counter > highest) {
+            val shift = if (highest == null) REPLAY_WINDOW else counter - highest
+            replayBitmap = if (shift >= REPLAY_WINDOW) 1L else (replayBitmap shl shift.toInt()) or 1L
+            highestCounter = counter
+            return true
+        }
+
+        val age = highest - counter
+        if (age >= REPLAY_WINDOW || replayBitmap and (1L shl age.toInt()) != 0L) {
+            return false
+        }
+        replayBitmap = replayBitmap or (1L shl age.toInt())
+        return true
+    }
+
+    companion object {
+        const val KEY_LEN = 32
+        private const val COUNTER_LEN = 8
+        private const val TAG_LEN = 32
+        private const val REPLAY_WINDOW = 64L
+        private const val SESSION_KEY_CONTEXT = "linkmycomputer/session-key/v1"
+        private const val DIRECTION_KEY_CONTEXT = "linkmycomputer/direction-key/v1"
+
+        fun forSession(
+            pairingKey: ByteArray,
+            sessionNonce: String,
+            direction: Direction
+        ): FrameAuthenticator {
+            val sessionKey = hmac(pairingKey, (SESSION_KEY_CONTEXT + sessionNonce).toByteArray())
+            val directionKey = hmac(
+                sessionKey,
+                (DIRECTION_KEY_CONTEXT + direction.context).toByteArray()
+            )
+            return FrameAuthenticator(directionKey)
+        }
+
+        private fun hmac(key: ByteArray, payload: ByteArray): ByteArray {
+            val mac = Mac.getInstance("HmacSHA256")
+            mac.init(SecretKeySpec(key, "HmacSHA256"))
+            return mac.doFinal(payload)
+        }
+    }
+}
@@ -0,0 +1,110 @@
+package com.linkmycomputer.player
+
+import android.media.MediaCodecInfo
+import android.media.MediaCodecList
+import org.json.JSONArray
+import org.json.JSONObject
+
+data class DecoderCapability(
+    val codecs: List<String>,
+    val maxWidth: Int,
+    val maxHeight: Int,
+    val maxRefreshHz: Int
+)
+
+data class SessionWelcome(
+    val codec: String,
+    val width: Int,
+    val height: Int,
+    val fps: Int,
+    val sessionNonce: String?
+)
+
+object Handshake {
+    private const val PROTOCOL_MAJOR = 1
+    private const val PROTOCOL_MINOR = 0
+    private const val MAX_POINTERS = 10
+    private const val UINT16_MAX = 0xFFFF
+
+    private val CODEC_MIME_TYPES = linkedMapOf(
+        "av1" to "video/av01",
+        "hevc" to "video/hevc",
+        "h264" to "video/avc"
+    )
+
+    fun detectDecoders(maxRefreshHz: Int): DecoderCapability {
+        val decoders = MediaCodecList(MediaCodecList.REGULAR_CODECS).codecInfos
+            .filterNot(MediaCodecInfo::isEncoder)
+        val codecs = mutableListOf<String>()
+        var maxWidth = 0
+        var maxHeight = 0
+
+        CODEC_MIME_TYPES.forEach { (codec, mime) ->
+            val capabilities = decoders.mapNotNull { info ->
+                val type = info.supportedTypes.firstOrNull { it.equals(mime, ignoreCase = true) }
+                type?.let { info.getCapabilitiesForType(it).videoCapabilities }
+            }
+            if (capabilities.isNotEmpty()) {
+                codecs += codec
+                capabilities.forEach { video ->
+                    maxWidth = maxOf(maxWidth, video.supportedWidths.upper)
+                    maxHeight = maxOf(maxHeight, video.supportedHeights.upper)
+                }
+            }
+        }
+
+        return DecoderCapability(
+            codecs = codecs,
+            maxWidth = maxWidth.coerceAtMost(UINT16_MAX),
+            maxHeight = maxHeight.coerceAtMost(UINT16_MAX),
+            maxRefreshHz = maxRefreshHz.coerceIn(1, UINT16_MAX)
+        )
+    }
+
+    fun helloMessage(decoder: DecoderCapability, appVersion: String): String {
+        val payload = JSONObject()
+            .put("protocol_version", protocolVersion())
+            .put("app_version", appVersion)
+            .put("codecs", JSONArray(decoder.codecs))
+            .put("max_decode_width", decoder.maxWidth)
+            .put("max_decode_height", decoder.maxHeight)
+            .put("max_refresh_hz", decoder.maxRefreshHz)
+            .put(
+                "input",
+                JSONObject()
+                    .put("touch", true)
+                    .put("max_pointers", MAX_POINTERS)
+                    .put("keyboard", false)
+                    .put("gamepad", false)
+                    .put("mouse", false)
+            )
+            .put("features", JSONArray())
+
+        return JSONObject()
+            .put("kind", "hello")
+            .put("payload", payload)
+            .toString()
+    }
+
+    fun parseWelcome(message: String): SessionWelcome? {
+        val frame = runCatching { JSONObject(message) }.getOrNull() ?: return null
+        if (frame.optString("kind") != "welcome") {
+            return null
+        }
+
+        val payload = frame.optJSONObject("payload") ?: return null
+        return SessionWelcome(
+            codec = payload.optString("codec"),
+            width = payload.optInt("width"),
+            height = payload.optInt("height"),
+            fps = payload.optInt("fps"),
+            sessionNonce = payload.optString("session_nonce").ifBlank { null }
+        )
+    }
+
+    private fun protocolVersion(): JSONObject {
+        return JSONObject()
+            .put("major", PROTOCOL_MAJOR)
+            .put("minor", PROTOCOL_MINOR)
+    }
+}
@@ -8,7 +8,10 @@ import java.net.InetSocketAddress
 import java.net.SocketException
 import java.util.Locale
 import java.util.concurrent.atomic.AtomicBoolean
+import java.util.concurrent.atomic.AtomicLong
 import kotlin.concurrent.thread
+import org.json.JSONArray
+import org.json.JSONObject
 
 data class ConnectRequest(
     val desktopName: String,
@@ -24,6 +27,8 @@ data class LanConnectionState(
 )
 
 class LanControlServer(
+    private val decoder: DecoderCapability,
+    private val pairingKey: ByteArray?,
     private val onStatus: (String) -> Unit,
     private val onConnectRequest: (ConnectRequest, (Boolean) -> Unit) -> Unit,
     private val onConnectionChanged: (LanConnectionState) -> Unit
@@ -33,6 +38,8 @@ class LanControlServer(
     private var controlSocket: DatagramSocket? = null
     private var discoveryThread: Thread? = null
     private var controlThread: Thread? = null
+    private var heartbeatThread: Thread? = null
+    private val touchSequence = AtomicLong(0)
 
     @Volatile
     private var connectedEndpoint: InetSocketAddress? = null
@@ -43,6 +50,15 @@ class LanControlServer(
     @Volatile
     private var connectedDesktopName: String? = null
 
+    @Volatile
+    private var sessionWelcome: SessionWelcome? = null
+
+    @Volatile
+    private var outboundAuthenticator: FrameAuthenticator? = null
+
+    @Volatile
+    private var inboundAuthenticator: FrameAuthenticator? = null
+
     fun start() {
         if (!running.compareAndSet(false, true)) {
             return
@@ -50,6 +66,7 @@ class LanControlServer(
 
         startDiscoveryLoop()
         startControlLoop()
+        startHeartbeatLoop()
         onStatus("已启动局域网监听，等待电脑扫描。")
     }
 
@@ -68,9 +85,11 @@ class LanControlServer(
 
         discoveryThread = null
         controlThread = null
+        heartbeatThread = null
         connectedEndpoint = null
         connectedTouchEndpoint = null
         connectedDesktopName = null
+        resetSession()
         onConnectionChanged(LanConnectionState(connected = false))
     }
 
@@ -79,29 +98,60 @@ class LanControlServer(
         connectedEndpoint = null
         connectedTouchEndpoint = null
         connectedDesktopName = null
+        resetSession()
         onConnectionChanged(LanConnectionState(connected = false))
         onStatus("已从手机端主动断开连接。")
     }
 
     fun sendTouchFrame(frame: TouchFrame) {
         val endpoint = connectedTouchEndpoint ?: return
+        val authenticator = outboundAuthenticator
 
         frame.events.forEach { event ->
-            val packet = String.format(
-                Locale.US,
-                "LMC_TOUCH|%d|%d|%s|%.5f|%.5f|%.4f|%d",
-                frame.frameId,
-                event.pointerId,
-                event.action.name,
-                event.x,
-                event.y,
-                event.pressure,
-                event.timestampMs
-            )
-            sendPacket(packet, endpoint.address, endpoint.port)
+            val frameId = touchSequence.incrementAndGet()
+            if (authenticator != null) {
+                val sealed = authenticator.seal(sealedTouchMessage(frameId, event).toByteArray())
+                sendBytes(sealed, endpoint.address, endpoint.port)
+            } else {
+                val packet = String.format(
+                    Locale.US,
+                    "LMC_TOUCH|%d|%d|%s|%.5f|%.5f|%.4f|%d",
+                    frameId,
+                    event.pointerId,
+                    event.action.name,
+                    event.x,
+                    event.y,
+                    event.pressure,
+                    event.timestampMs
+                )
+                sendPacket(packet, endpoint.address, endpoint.port)
+            }
         }
     }
 
+    private fun sealedTouchMessage(frameId: Long, event: PointerSnapshot): String {
+        val pointer = JSONObject()
+            .put("pointer_id", event.pointerId)
+            .put("action", event.action.name.lowercase(Locale.US))
+            .put("x", event.x.toDouble())
+            .put("y", event.y.toDouble())
+            .put("pressure", event.pressure.toDouble())
+            .put("timestamp_ms", event.timestampMs)
+        val payload = JSONObject()
+            .put("frame_id", frameId)
+            .put("events", JSONArray().put(pointer))
+        return JSONObject()
+            .put("kind", "touch")
+            .put("payload", payload)
+            .toString()
+    }
+
+    private fun resetSession() {
+        sessionWelcome = null
+        outboundAuthenticator = null
+        inboundAuthenticator = null
+    }
+
     private fun startDiscoveryLoop() {
         discoveryThread = Thread {
             try {
@@ -140,6 +190,27 @@ class LanControlServer(
         }
     }
 
+    private fun startHeartbeatLoop() {
+        heartbeatThread = thread(name = "LanHeartbeatThread") {
+            while (running.get()) {
+                connectedTouchEndpoint?.let { endpoint ->
+                    val authenticator = outboundAuthenticator
+                    if (authenticator != null) {
+                        val sealed = authenticator.seal(HEARTBEAT.toByteArray())
+                        sendBytes(sealed, endpoint.address, endpoint.port)
+                    } else {
+                        sendPacket(HEARTBEAT, endpoint.address, endpoint.port)
+                    }
+                }
+                try {
+                    Thread.sleep(HEARTBEAT_INTERVAL_MS)
+                } catch (_: InterruptedException) {
+                    break
+                }
+            }
+        }
+    }
+
     private fun startControlLoop() {
         controlThread = Thread {
             try {
@@ -153,7 +224,8 @@ class LanControlServer(
                         val packet = DatagramPacket(buffer, buffer.size)
                         socket.receive(packet)
 
-                        val message = String(packet.data, 0, packet.length)
+                        val datagram = packet.data.copyOf(packet.length)
+                        val message = openControlDatagram(datagram, packet.address) ?: continue
                         handleControlMessage(message, packet.address, packet.port)
                     } catch (_: java.net.SocketTimeoutException) {
                     } catch (err: SocketException) {
@@ -172,6 +244,20 @@ class LanControlServer(
         }
     }
 
+    private fun openControlDatagram(datagram: ByteArray, address: InetAddress): String? {
+        val authenticator = inboundAuthenticator
+        if (authenticator == null || connectedEndpoint?.address != address) {
+            return String(datagram, Charsets.UTF_8)
+        }
+
+        val verified = authenticator.open(datagram)
+        if (verified != null) {
+            return String(verified, Charsets.UTF_8)
+        }
+        val message = String(datagram, Charsets.UTF_8)
+        return message.takeIf { it.startsWith("LMC_") }
+    }
+
     private fun handleControlMessage(message: String, address: InetAddress, port: Int) {
         when {
             message.startsWith("LMC_CONNECT_REQUEST|") -> {
@@ -190,9 +276,12 @@ class LanControlServer(
                         thread(name = "LanConnectAcceptReply") {
                             val sent = sendPacket("LMC_CONNECT_ACCEPT|${Build.MODEL}", address, port)
                             if (sent) {
+                                touchSequence.set(0)
+                                resetSession()
                                 connectedEndpoint = InetSocketAddress(address, port)
                                 connectedTouchEndpoint = InetSocketAddress(address, hostTouchPort)
                                 connectedDesktopName = desktopName
+                                sendPacket(Handshake.helloMessage(decoder, APP_VERSION), address, port)
                                 onConnectionChanged(
                                     LanConnectionState(
                                         connected = true,
@@ -218,12 +307,42 @@ class LanControlServer(
                 connectedEndpoint = null
                 connectedTouchEndpoint = null
                 connectedDesktopName = null
+                resetSession()
                 onConnectionChanged(LanConnectionState(connected = false))
                 onStatus("电脑端已断开连接。")
             }
 
-            message == "LMC_PING" -> {
-                sendPacket("LMC_PONG", address, port)
+            message == "LMC_PING" || message.startsWith("LMC_PING|") -> {
+                val receivedAt = System.currentTimeMillis()
+                val origin = message.split("|").getOrNull(1)?.trim()?.toLongOrNull()
+                val reply = if (origin == null) {
+                    "LMC_PONG"
+                } else {
+                    "LMC_PONG|$origin|$receivedAt|${System.currentTimeMillis()}"
+                }
+                sendPacket(reply, address, port)
+            }
+
+            message.startsWith("{") -> {
+                if (connectedEndpoint?.address != address) {
+                    return
+                }
+                if (sessionWelcome != null) {
+                    return
+                }
+                val welcome = Handshake.parseWelcome(message) ?: return
+                sessionWelcome = welcome
+                val nonce = welcome.sessionNonce
+                if (pairingKey != null && nonce != null) {
+                    outboundAuthenticator =
+                        FrameAuthenticator.forSession(pairingKey, nonce, Direction.PHONE_TO_HOST)
+                    inboundAuthenticator =
+                        FrameAuthenticator.forSession(pairingKey, nonce, Direction.HOST_TO_PHONE)
+                }
+                onStatus(
+                    "已与电脑协商：${welcome.codec.uppercase(Locale.US)} " +
+                        "${welcome.width}x${welcome.height}@${welcome.fps}"
+                )
             }
         }
     }
@@ -234,9 +353,12 @@ class LanControlServer(
     }
 
     private fun sendPacket(message: String, address: InetAddress, port: Int): Boolean {
+        return sendBytes(message.toByteArray(Charsets.UTF_8), address, port)
+    }
+
+    private fun sendBytes(payload: ByteArray, address: InetAddress, port: Int): Boolean {
         return try {
             DatagramSocket().use { socket ->
-                val payload = message.toByteArray(Charsets.UTF_8)
                 val packet = DatagramPacket(payload, payload.size, address, port)
                 socket.send(packet)
             }
@@ -257,6 +379,8 @@ class LanControlServer(
         const val CONTROL_PORT = 42043
         private const val DEFAULT_HOST_TOUCH_PORT = 42044
         private const val SOCKET_TIMEOUT_MS = 700
+        private const val HEARTBEAT_INTERVAL_MS = 1_000L
+        private const val HEARTBEAT = "LMC_PING"
         private const val APP_VERSION = "0.1.0"
     }
 }
@@ -2,6 +2,7 @@ package com.linkmycomputer.player
 
 import android.graphics.Color
 import android.os.Bundle
+import android.util.Base64
 import android.util.TypedValue
 import android.view.Gravity
 import android.view.View
@@ -11,6 +12,7 @@ import android.widget.LinearLayout
 import android.widget.TextView
 import androidx.appcompat.app.AlertDialog
 import androidx.appcompat.app.AppCompatActivity
+import kotlin.math.roundToInt
 
 class PlayerActivity : AppCompatActivity() {
     private lateinit var statusText: TextView
@@ -25,6 +27,8 @@ class PlayerActivity : AppCompatActivity() {
         setContentView(createContentView())
 
         lanServer = LanControlServer(
+            decoder = Handshake.detectDecoders(displayRefreshHz()),
+            pairingKey = loadPairingKey(),
             onStatus = { message ->
                 runOnUiThread {
                     statusText.text = "状态：$message"
@@ -172,7 +176,28 @@ class PlayerActivity : AppCompatActivity() {
         }
     }
 
+    private fun loadPairingKey(): ByteArray? {
+        val preferences = getSharedPreferences(PREFERENCES_NAME, MODE_PRIVATE)
+        intent.getStringExtra(EXTRA_PAIRING_KEY)?.let { encoded ->
+            preferences.edit().putString(EXTRA_PAIRING_KEY, encoded.trim()).apply()
+        }
+
+        val encoded = preferences.getString(EXTRA_PAIRING_KEY, null) ?: return null
+        val key = runCatching { Base64.decode(encoded, Base64.DEFAULT) }.getOrNull()
+        return key?.takeIf { it.size == FrameAuthenticator.KEY_LEN }
+    }
+
+    @Suppress("DEPRECATION")
+    private fun displayRefreshHz(): Int {
+        return windowManager.defaultDisplay.refreshRate.roundToInt()
+    }
+
     private fun dp(value: Int): Int {
         return (value * resources.displayMetrics.density).toInt()
     }
+
+    companion object {
+        private const val PREFERENCES_NAME = "link_my_computer"
+        private const val EXTRA_PAIRING_KEY = "pairing_key"
+    }
 }
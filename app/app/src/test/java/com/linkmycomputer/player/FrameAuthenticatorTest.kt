@@ -0,0 +1,37 @@
+package com.linkmycomputer.player
+
+import org.junit.Assert.assertArrayEquals
+import org.junit.Assert.assertEquals
+import org.junit.Assert.assertNull
+import org.junit.Test
+
+class FrameAuthenticatorTest {
+
+    private val pairingKey = ByteArray(FrameAuthenticator.KEY_LEN) { it.toByte() }
+    private val nonce = "0123456789abcdef"
+
+    @Test
+    fun sealedHeartbeat_matchesHostDerivation() {
+        val sender = FrameAuthenticator.forSession(pairingKey, nonce, Direction.PHONE_TO_HOST)
+
+        val sealed = sender.seal("LMC_PING".toByteArray())
+        val tag = sealed.copyOfRange(sealed.size - 32, sealed.size)
+            .joinToString("") { "%02x".format(it) }
+
+        assertEquals("f6f889bd8a6d1cbde029f084241c400c70e7541a2ebb15f0df6c08d48a283a14", tag)
+    }
+
+    @Test
+    fun reflectedAndReplayedFrames_areRejected() {
+        val phone = FrameAuthenticator.forSession(pairingKey, nonce, Direction.PHONE_TO_HOST)
+        val host = FrameAuthenticator.forSession(pairingKey, nonce, Direction.HOST_TO_PHONE)
+        val receiver = FrameAuthenticator.forSession(pairingKey, nonce, Direction.HOST_TO_PHONE)
+
+        val reflected = phone.seal("LMC_PING".toByteArray())
+        assertNull(receiver.open(reflected))
+
+        val sealed = host.seal("{\"kind\":\"display_info\"}".toByteArray())
+        assertArrayEquals("{\"kind\":\"display_info\"}".toByteArray(), receiver.open(sealed))
+        assertNull(receiver.open(sealed))
+    }
+}
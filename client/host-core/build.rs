@@ -0,0 +1,14 @@
+use std::path::PathBuf;
+
+fn main() {
+    let proto_root = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../../shared/proto");
+    let control_proto = proto_root.join("control.proto");
+    println!("cargo:rerun-if-changed={}", control_proto.display());
+
+    if std::env::var_os("PROTOC").is_none() {
+        let protoc = protoc_bin_vendored::protoc_bin_path().expect("vendored protoc is available");
+        std::env::set_var("PROTOC", protoc);
+    }
+
+    prost_build::compile_protos(&[control_proto], &[proto_root]).expect("compile control.proto");
+}
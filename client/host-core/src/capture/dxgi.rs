@@ -1,10 +1,12 @@
-use crate::config::profile::RuntimeProfile;
+use crate::config::profile::{Orientation, RuntimeProfile, StreamLevel};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct CaptureDescriptor {
     pub width: u16,
     pub height: u16,
     pub fps: u16,
+    pub orientation: Orientation,
+    pub pixel_format: String,
     pub source_hint: String,
 }
 
@@ -14,7 +16,22 @@ impl CaptureDescriptor {
             width: profile.width,
             height: profile.height,
             fps: profile.fps,
+            orientation: profile.orientation,
+            pixel_format: profile.color.pixel_format().to_string(),
             source_hint: "mumu-window".to_string(),
         }
     }
+
+    pub fn apply_level(&mut self, level: StreamLevel) {
+        self.width = level.width;
+        self.height = level.height;
+        self.fps = level.fps;
+    }
+
+    pub fn frame_size(&self) -> (u16, u16) {
+        match self.orientation {
+            Orientation::Landscape => (self.width, self.height),
+            Orientation::Portrait => (self.height, self.width),
+        }
+    }
 }
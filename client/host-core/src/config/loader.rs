@@ -0,0 +1,448 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::config::profile::{
+    recommended_bitrate_kbps, AdaptiveConfig, BitDepth, ChromaSubsampling, Codec, ColorFormat,
+    ColorRange, ColorSpace, LockPolicy, Orientation, ProfileError, ResolutionMode, RuntimeProfile,
+    DEFAULT_KEYFRAME_INTERVAL_MS,
+};
+use crate::pipeline::HostCapability;
+
+pub const ENV_CONFIG_PATH: &str = "LMC_CONFIG";
+pub const ENV_PROFILE_NAME: &str = "LMC_PROFILE";
+
+const ENV_PREFIX: &str = "LMC_";
+const LAYER_KEYS: [&str; 13] = [
+    "fps",
+    "resolution",
+    "resolution_mode",
+    "orientation",
+    "color_space",
+    "color_range",
+    "chroma",
+    "bit_depth",
+    "keyframe_interval_ms",
+    "bitrate",
+    "bitrate_kbps",
+    "codec",
+    "lock_policy",
+];
+const DEFAULT_WIDTH: u16 = 2460;
+const DEFAULT_HEIGHT: u16 = 1080;
+const DEFAULT_FPS: u16 = 144;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ProfileLayer {
+    pub fps: Option<u16>,
+    pub resolution: Option<String>,
+    pub resolution_mode: Option<ResolutionMode>,
+    pub orientation: Option<Orientation>,
+    pub color_space: Option<ColorSpace>,
+    pub color_range: Option<ColorRange>,
+    pub chroma: Option<ChromaSubsampling>,
+    pub bit_depth: Option<BitDepth>,
+    pub keyframe_interval_ms: Option<u32>,
+    pub bitrate_kbps: Option<u32>,
+    pub codec: Option<Codec>,
+    pub lock_policy: Option<LockPolicy>,
+    pub adaptive: Option<AdaptiveConfig>,
+}
+
+impl ProfileLayer {
+    pub fn set(&mut self, key: &str, value: &str) -> Result<(), ConfigError> {
+        match key {
+            "fps" => self.fps = Some(parse_number(key, value)?),
+            "resolution" => {
+                parse_resolution(value)?;
+                self.resolution = Some(value.to_string());
+            }
+            "resolution_mode" => self.resolution_mode = Some(parse_resolution_mode(value)?),
+            "orientation" => self.orientation = Some(parse_orientation(value)?),
+            "color_space" => self.color_space = Some(parse_color_space(value)?),
+            "color_range" => self.color_range = Some(parse_color_range(value)?),
+            "chroma" => self.chroma = Some(parse_chroma(value)?),
+            "bit_depth" => self.bit_depth = Some(parse_bit_depth(value)?),
+            "keyframe_interval_ms" => self.keyframe_interval_ms = Some(parse_number(key, value)?),
+            "bitrate" | "bitrate_kbps" => self.bitrate_kbps = Some(parse_number(key, value)?),
+            "codec" => self.codec = Some(parse_codec(value)?),
+            "lock_policy" => self.lock_policy = Some(parse_lock_policy(value)?),
+            other => return Err(ConfigError::UnknownKey(other.to_string())),
+        }
+
+        Ok(())
+    }
+
+    pub fn from_env_vars<I, K, V>(vars: I) -> Result<Self, ConfigError>
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: AsRef<str>,
+        V: AsRef<str>,
+    {
+        let mut layer = Self::default();
+        for (key, value) in vars {
+            let Some(field) = key.as_ref().strip_prefix(ENV_PREFIX) else {
+                continue;
+            };
+
+            let field = field.to_ascii_lowercase();
+            if LAYER_KEYS.contains(&field.as_str()) {
+                layer.set(&field, value.as_ref())?;
+            }
+        }
+
+        Ok(layer)
+    }
+
+    pub fn merge(&mut self, other: &ProfileLayer) {
+        if other.fps.is_some() {
+            self.fps = other.fps;
+        }
+        if other.resolution.is_some() {
+            self.resolution = other.resolution.clone();
+        }
+        if other.resolution_mode.is_some() {
+            self.resolution_mode = other.resolution_mode;
+        }
+        if other.orientation.is_some() {
+            self.orientation = other.orientation;
+        }
+        if other.color_space.is_some() {
+            self.color_space = other.color_space;
+        }
+        if other.color_range.is_some() {
+            self.color_range = other.color_range;
+        }
+        if other.chroma.is_some() {
+            self.chroma = other.chroma;
+        }
+        if other.bit_depth.is_some() {
+            self.bit_depth = other.bit_depth;
+        }
+        if other.keyframe_interval_ms.is_some() {
+            self.keyframe_interval_ms = other.keyframe_interval_ms;
+        }
+        if other.bitrate_kbps.is_some() {
+            self.bitrate_kbps = other.bitrate_kbps;
+        }
+        if other.codec.is_some() {
+            self.codec = other.codec;
+        }
+        if other.lock_policy.is_some() {
+            self.lock_policy = other.lock_policy;
+        }
+        if other.adaptive.is_some() {
+            self.adaptive = other.adaptive;
+        }
+    }
+
+    pub fn build(
+        &self,
+        capability: Option<&HostCapability>,
+    ) -> Result<RuntimeProfile, ConfigError> {
+        let (width, height) = match self.resolution.as_deref() {
+            Some(value) => parse_resolution(value)?,
+            None => (DEFAULT_WIDTH, DEFAULT_HEIGHT),
+        };
+        let fps = self.fps.unwrap_or(DEFAULT_FPS);
+        let codec = self.codec.unwrap_or(Codec::Hevc);
+        let bitrate_kbps = self
+            .bitrate_kbps
+            .unwrap_or_else(|| recommended_bitrate_kbps(width, height, fps, codec));
+        let lock_policy = self.lock_policy.unwrap_or(LockPolicy::TurboLock);
+
+        let profile = match self.resolution_mode.unwrap_or_default() {
+            ResolutionMode::Preset => match capability {
+                Some(capability) => RuntimeProfile::negotiate(
+                    width,
+                    height,
+                    fps,
+                    bitrate_kbps,
+                    codec,
+                    lock_policy,
+                    capability,
+                ),
+                None => RuntimeProfile::new(width, height, fps, bitrate_kbps, codec, lock_policy),
+            },
+            ResolutionMode::Custom => {
+                RuntimeProfile::new_custom(width, height, fps, bitrate_kbps, codec, lock_policy)
+                    .and_then(|profile| match capability {
+                        Some(capability) => profile.check_capability(capability).map(|_| profile),
+                        None => Ok(profile),
+                    })
+            }
+        }
+        .map_err(ConfigError::Profile)?
+        .with_orientation(self.orientation.unwrap_or_default())
+        .with_color(ColorFormat {
+            color_space: self.color_space.unwrap_or_default(),
+            range: self.color_range.unwrap_or_default(),
+            chroma: self.chroma.unwrap_or_default(),
+            bit_depth: self.bit_depth.unwrap_or_default(),
+        })
+        .and_then(|profile| {
+            profile.with_keyframe_interval_ms(
+                self.keyframe_interval_ms
+                    .unwrap_or(DEFAULT_KEYFRAME_INTERVAL_MS),
+            )
+        })
+        .map_err(ConfigError::Profile)?;
+
+        match self.adaptive {
+            Some(config) if lock_policy == LockPolicy::Adaptive => {
+                profile.with_adaptive(config).map_err(ConfigError::Profile)
+            }
+            Some(_) => Err(ConfigError::AdaptiveWithoutPolicy),
+            None => Ok(profile),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ConfigFile {
+    pub default_profile: Option<String>,
+    pub profiles: BTreeMap<String, ProfileLayer>,
+    pub packages: BTreeMap<String, String>,
+}
+
+impl ConfigFile {
+    pub fn load(path: &Path) -> Result<Self, ConfigError> {
+        let raw = std::fs::read_to_string(path)
+            .map_err(|err| ConfigError::Io(path.to_path_buf(), err))?;
+
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("toml") => Self::from_toml_str(&raw),
+            Some("json") => Self::from_json_str(&raw),
+            _ => Err(ConfigError::UnsupportedFormat(path.to_path_buf())),
+        }
+    }
+
+    pub fn from_toml_str(raw: &str) -> Result<Self, ConfigError> {
+        toml::from_str(raw).map_err(ConfigError::Toml)
+    }
+
+    pub fn from_json_str(raw: &str) -> Result<Self, ConfigError> {
+        serde_json::from_str(raw).map_err(ConfigError::Json)
+    }
+
+    pub fn profile(&self, name: Option<&str>) -> Result<Option<&ProfileLayer>, ConfigError> {
+        let Some(name) = name.or(self.default_profile.as_deref()) else {
+            return Ok(None);
+        };
+
+        self.profiles
+            .get(name)
+            .map(Some)
+            .ok_or_else(|| ConfigError::UnknownProfile(name.to_string()))
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ProfileLoader {
+    file: Option<ConfigFile>,
+    profile_name: Option<String>,
+    env: ProfileLayer,
+    cli: ProfileLayer,
+    capability: Option<HostCapability>,
+}
+
+impl ProfileLoader {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_file(mut self, file: ConfigFile) -> Self {
+        self.file = Some(file);
+        self
+    }
+
+    pub fn with_profile_name(mut self, name: impl Into<String>) -> Self {
+        self.profile_name = Some(name.into());
+        self
+    }
+
+    pub fn with_env(mut self, layer: ProfileLayer) -> Self {
+        self.env = layer;
+        self
+    }
+
+    pub fn with_cli(mut self, layer: ProfileLayer) -> Self {
+        self.cli = layer;
+        self
+    }
+
+    pub fn with_capability(mut self, capability: HostCapability) -> Self {
+        self.capability = Some(capability);
+        self
+    }
+
+    pub fn file(&self) -> Option<&ConfigFile> {
+        self.file.as_ref()
+    }
+
+    pub fn resolve(&self) -> Result<RuntimeProfile, ConfigError> {
+        self.resolve_layers(self.profile_name.as_deref())
+    }
+
+    pub fn resolve_named(&self, name: &str) -> Result<RuntimeProfile, ConfigError> {
+        self.resolve_layers(Some(name))
+    }
+
+    fn resolve_layers(&self, name: Option<&str>) -> Result<RuntimeProfile, ConfigError> {
+        let mut merged = ProfileLayer::default();
+
+        match &self.file {
+            Some(file) => {
+                if let Some(layer) = file.profile(name)? {
+                    merged.merge(layer);
+                }
+            }
+            None => {
+                if let Some(name) = name {
+                    return Err(ConfigError::UnknownProfile(name.to_string()));
+                }
+            }
+        }
+
+        merged.merge(&self.env);
+        merged.merge(&self.cli);
+        merged.build(self.capability.as_ref())
+    }
+}
+
+pub fn parse_resolution(value: &str) -> Result<(u16, u16), ConfigError> {
+    let invalid = || ConfigError::InvalidValue {
+        key: "resolution".to_string(),
+        value: value.to_string(),
+    };
+
+    let (w, h) = value.trim().split_once('x').ok_or_else(invalid)?;
+    let width = w.parse::<u16>().map_err(|_| invalid())?;
+    let height = h.parse::<u16>().map_err(|_| invalid())?;
+
+    Ok((width, height))
+}
+
+pub fn parse_codec(value: &str) -> Result<Codec, ConfigError> {
+    match value.trim().to_ascii_lowercase().as_str() {
+        "h264" => Ok(Codec::H264),
+        "hevc" | "h265" => Ok(Codec::Hevc),
+        "av1" => Ok(Codec::Av1),
+        _ => Err(ConfigError::InvalidValue {
+            key: "codec".to_string(),
+            value: value.to_string(),
+        }),
+    }
+}
+
+pub fn parse_lock_policy(value: &str) -> Result<LockPolicy, ConfigError> {
+    match value.trim().to_ascii_lowercase().as_str() {
+        "turbo_lock" => Ok(LockPolicy::TurboLock),
+        "adaptive" | "balanced" => Ok(LockPolicy::Adaptive),
+        _ => Err(ConfigError::InvalidValue {
+            key: "lock_policy".to_string(),
+            value: value.to_string(),
+        }),
+    }
+}
+
+pub fn parse_resolution_mode(value: &str) -> Result<ResolutionMode, ConfigError> {
+    match value.trim().to_ascii_lowercase().as_str() {
+        "preset" => Ok(ResolutionMode::Preset),
+        "custom" => Ok(ResolutionMode::Custom),
+        _ => Err(ConfigError::InvalidValue {
+            key: "resolution_mode".to_string(),
+            value: value.to_string(),
+        }),
+    }
+}
+
+pub fn parse_orientation(value: &str) -> Result<Orientation, ConfigError> {
+    match value.trim().to_ascii_lowercase().as_str() {
+        "landscape" => Ok(Orientation::Landscape),
+        "portrait" => Ok(Orientation::Portrait),
+        _ => Err(ConfigError::InvalidValue {
+            key: "orientation".to_string(),
+            value: value.to_string(),
+        }),
+    }
+}
+
+pub fn parse_color_space(value: &str) -> Result<ColorSpace, ConfigError> {
+    match value.trim().to_ascii_lowercase().as_str() {
+        "bt601" | "bt.601" => Ok(ColorSpace::Bt601),
+        "bt709" | "bt.709" => Ok(ColorSpace::Bt709),
+        _ => Err(ConfigError::InvalidValue {
+            key: "color_space".to_string(),
+            value: value.to_string(),
+        }),
+    }
+}
+
+pub fn parse_color_range(value: &str) -> Result<ColorRange, ConfigError> {
+    match value.trim().to_ascii_lowercase().as_str() {
+        "limited" | "tv" => Ok(ColorRange::Limited),
+        "full" | "pc" => Ok(ColorRange::Full),
+        _ => Err(ConfigError::InvalidValue {
+            key: "color_range".to_string(),
+            value: value.to_string(),
+        }),
+    }
+}
+
+pub fn parse_chroma(value: &str) -> Result<ChromaSubsampling, ConfigError> {
+    match value.trim().to_ascii_lowercase().as_str() {
+        "yuv420" | "420" | "4:2:0" => Ok(ChromaSubsampling::Yuv420),
+        "yuv444" | "444" | "4:4:4" => Ok(ChromaSubsampling::Yuv444),
+        _ => Err(ConfigError::InvalidValue {
+            key: "chroma".to_string(),
+            value: value.to_string(),
+        }),
+    }
+}
+
+pub fn parse_bit_depth(value: &str) -> Result<BitDepth, ConfigError> {
+    let invalid = || ConfigError::InvalidValue {
+        key: "bit_depth".to_string(),
+        value: value.to_string(),
+    };
+
+    let bits = value.trim().parse::<u8>().map_err(|_| invalid())?;
+    BitDepth::try_from(bits).map_err(|_| invalid())
+}
+
+fn parse_number<T: std::str::FromStr>(key: &str, value: &str) -> Result<T, ConfigError> {
+    value
+        .trim()
+        .parse::<T>()
+        .map_err(|_| ConfigError::InvalidValue {
+            key: key.to_string(),
+            value: value.to_string(),
+        })
+}
+
+#[derive(Debug, Error)]
+pub enum ConfigError {
+    #[error("failed to read config file {}: {1}", .0.display())]
+    Io(PathBuf, std::io::Error),
+    #[error("config file {} must use a .toml or .json extension", .0.display())]
+    UnsupportedFormat(PathBuf),
+    #[error("failed to parse TOML config: {0}")]
+    Toml(toml::de::Error),
+    #[error("failed to parse JSON config: {0}")]
+    Json(serde_json::Error),
+    #[error("profile `{0}` is not defined in the config file")]
+    UnknownProfile(String),
+    #[error("unknown config key `{0}`")]
+    UnknownKey(String),
+    #[error("invalid value `{value}` for `{key}`")]
+    InvalidValue { key: String, value: String },
+    #[error("`[adaptive]` settings require lock_policy = \"adaptive\"")]
+    AdaptiveWithoutPolicy,
+    #[error("profile validation failed: {0}")]
+    Profile(ProfileError),
+}
@@ -1 +1,3 @@
+pub mod loader;
 pub mod profile;
+pub mod registry;
@@ -1,21 +1,270 @@
+use std::fmt;
+
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
+use crate::geometry::Rotation;
+use crate::pipeline::HostCapability;
+
 pub const SUPPORTED_FPS: [u16; 4] = [60, 90, 120, 144];
 pub const SUPPORTED_RESOLUTIONS: [(u16, u16); 4] =
     [(1280, 720), (1600, 900), (1920, 1080), (2460, 1080)];
+pub const MAX_PRESET_CANDIDATES: usize = 3;
+pub const MIN_CUSTOM_PIXELS: u32 = 640 * 360;
+pub const MAX_CUSTOM_PIXELS: u32 = 3840 * 2160;
+pub const DEFAULT_KEYFRAME_INTERVAL_MS: u32 = 2_000;
+pub const MIN_KEYFRAME_INTERVAL_MS: u32 = 250;
+pub const MAX_KEYFRAME_INTERVAL_MS: u32 = 10_000;
+
+const REFERENCE_BITS_PER_PIXEL: f64 = 80_000_000.0 / (2460.0 * 1080.0 * 144.0);
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum Codec {
     H264,
     Hevc,
+    Av1,
+}
+
+impl Codec {
+    pub const PREFERENCE: [Codec; 3] = [Codec::Av1, Codec::Hevc, Codec::H264];
+
+    pub fn block_size(self) -> u16 {
+        match self {
+            Codec::H264 => 16,
+            Codec::Hevc => 32,
+            Codec::Av1 => 64,
+        }
+    }
+
+    pub fn bitrate_bounds(self) -> BitrateBounds {
+        match self {
+            Codec::H264 => BitrateBounds {
+                hard_min_bpp: 0.01,
+                warn_min_bpp: 0.06,
+                recommended_bpp: 0.25,
+                warn_max_bpp: 0.45,
+                hard_max_bpp: 1.2,
+            },
+            Codec::Hevc => BitrateBounds {
+                hard_min_bpp: 0.008,
+                warn_min_bpp: 0.045,
+                recommended_bpp: 0.2,
+                warn_max_bpp: 0.36,
+                hard_max_bpp: 1.0,
+            },
+            Codec::Av1 => BitrateBounds {
+                hard_min_bpp: 0.006,
+                warn_min_bpp: 0.035,
+                recommended_bpp: 0.16,
+                warn_max_bpp: 0.3,
+                hard_max_bpp: 0.8,
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BitrateBounds {
+    pub hard_min_bpp: f64,
+    pub warn_min_bpp: f64,
+    pub recommended_bpp: f64,
+    pub warn_max_bpp: f64,
+    pub hard_max_bpp: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum BitrateWarning {
+    BelowRecommended {
+        bitrate_kbps: u32,
+        threshold_kbps: u32,
+        recommended_kbps: u32,
+    },
+    AboveRecommended {
+        bitrate_kbps: u32,
+        threshold_kbps: u32,
+        recommended_kbps: u32,
+    },
+}
+
+impl fmt::Display for BitrateWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BitrateWarning::BelowRecommended {
+                bitrate_kbps,
+                threshold_kbps,
+                recommended_kbps,
+            } => write!(
+                f,
+                "bitrate {bitrate_kbps}kbps is below {threshold_kbps}kbps and may look blocky; recommended {recommended_kbps}kbps"
+            ),
+            BitrateWarning::AboveRecommended {
+                bitrate_kbps,
+                threshold_kbps,
+                recommended_kbps,
+            } => write!(
+                f,
+                "bitrate {bitrate_kbps}kbps is above {threshold_kbps}kbps and may congest the LAN; recommended {recommended_kbps}kbps"
+            ),
+        }
+    }
+}
+
+pub fn recommended_bitrate_kbps(width: u16, height: u16, fps: u16, codec: Codec) -> u32 {
+    let bounds = codec.bitrate_bounds();
+    let kbps = kbps_for_bpp(bounds.recommended_bpp, width, height, fps);
+    (kbps.div_ceil(1000) * 1000).max(1000)
+}
+
+pub fn negotiate_codec(host: &[Codec], peer: &[Codec], preferred: Option<Codec>) -> Option<Codec> {
+    let shared = |codec: &Codec| host.contains(codec) && peer.contains(codec);
+
+    preferred
+        .filter(shared)
+        .or_else(|| Codec::PREFERENCE.into_iter().find(shared))
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ColorSpace {
+    Bt601,
+    #[default]
+    Bt709,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ColorRange {
+    #[default]
+    Limited,
+    Full,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChromaSubsampling {
+    #[default]
+    Yuv420,
+    Yuv444,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "u8", into = "u8")]
+pub enum BitDepth {
+    #[default]
+    Eight,
+    Ten,
+}
+
+impl TryFrom<u8> for BitDepth {
+    type Error = String;
+
+    fn try_from(bits: u8) -> Result<Self, Self::Error> {
+        match bits {
+            8 => Ok(BitDepth::Eight),
+            10 => Ok(BitDepth::Ten),
+            other => Err(format!("unsupported bit depth {other}, expected 8 or 10")),
+        }
+    }
+}
+
+impl From<BitDepth> for u8 {
+    fn from(depth: BitDepth) -> Self {
+        match depth {
+            BitDepth::Eight => 8,
+            BitDepth::Ten => 10,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ColorFormat {
+    pub color_space: ColorSpace,
+    pub range: ColorRange,
+    pub chroma: ChromaSubsampling,
+    pub bit_depth: BitDepth,
+}
+
+impl ColorFormat {
+    pub fn pixel_format(&self) -> &'static str {
+        match (self.chroma, self.bit_depth) {
+            (ChromaSubsampling::Yuv420, BitDepth::Eight) => "nv12",
+            (ChromaSubsampling::Yuv420, BitDepth::Ten) => "p010",
+            (ChromaSubsampling::Yuv444, BitDepth::Eight) => "yuv444",
+            (ChromaSubsampling::Yuv444, BitDepth::Ten) => "yuv444_16bit",
+        }
+    }
+
+    pub fn check_codec(&self, codec: Codec) -> Result<(), ProfileError> {
+        let reason = match (codec, self.chroma, self.bit_depth) {
+            (Codec::H264, _, BitDepth::Ten) => "H.264 hardware encoding is limited to 8-bit",
+            (Codec::Av1, ChromaSubsampling::Yuv444, _) => {
+                "AV1 hardware encoding is limited to 4:2:0 chroma"
+            }
+            _ => return Ok(()),
+        };
+
+        Err(ProfileError::UnsupportedColorFormat { codec, reason })
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Orientation {
+    #[default]
+    Landscape,
+    Portrait,
+}
+
+impl Orientation {
+    pub fn from_rotation(rotation: Rotation) -> Self {
+        if rotation.swaps_axes() {
+            Orientation::Portrait
+        } else {
+            Orientation::Landscape
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ResolutionMode {
+    #[default]
+    Preset,
+    Custom,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum LockPolicy {
     TurboLock,
+    Adaptive,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AdaptiveConfig {
+    pub floor_width: u16,
+    pub floor_height: u16,
+    pub floor_fps: u16,
+    pub floor_bitrate_kbps: u32,
+    pub bitrate_step_percent: u8,
+    pub recovery_samples: u8,
+}
+
+impl Default for AdaptiveConfig {
+    fn default() -> Self {
+        Self {
+            floor_width: 1280,
+            floor_height: 720,
+            floor_fps: 60,
+            floor_bitrate_kbps: 10_000,
+            bitrate_step_percent: 25,
+            recovery_samples: 5,
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -26,6 +275,20 @@ pub struct RuntimeProfile {
     pub target_bitrate_kbps: u32,
     pub codec: Codec,
     pub lock_policy: LockPolicy,
+    #[serde(default)]
+    pub adaptive: Option<AdaptiveConfig>,
+    #[serde(default)]
+    pub resolution_mode: ResolutionMode,
+    #[serde(default)]
+    pub orientation: Orientation,
+    #[serde(default)]
+    pub color: ColorFormat,
+    #[serde(default = "default_keyframe_interval_ms")]
+    pub keyframe_interval_ms: u32,
+}
+
+fn default_keyframe_interval_ms() -> u32 {
+    DEFAULT_KEYFRAME_INTERVAL_MS
 }
 
 impl RuntimeProfile {
@@ -37,18 +300,123 @@ impl RuntimeProfile {
         codec: Codec,
         lock_policy: LockPolicy,
     ) -> Result<Self, ProfileError> {
-        if !SUPPORTED_FPS.contains(&fps) {
-            return Err(ProfileError::UnsupportedFps(fps));
-        }
+        check_fps(width, height, fps, target_bitrate_kbps, codec)?;
 
         if !SUPPORTED_RESOLUTIONS.contains(&(width, height)) {
-            return Err(ProfileError::UnsupportedResolution(width, height));
+            return Err(ProfileError::UnsupportedResolution {
+                width,
+                height,
+                candidates: nearest_presets(width, height, fps, target_bitrate_kbps, codec, None),
+            });
+        }
+
+        Self::validated(
+            width,
+            height,
+            fps,
+            target_bitrate_kbps,
+            codec,
+            lock_policy,
+            ResolutionMode::Preset,
+        )
+    }
+
+    pub fn with_orientation(mut self, orientation: Orientation) -> Self {
+        self.orientation = orientation;
+        self
+    }
+
+    pub fn with_color(mut self, color: ColorFormat) -> Result<Self, ProfileError> {
+        color.check_codec(self.codec)?;
+        self.color = color;
+        Ok(self)
+    }
+
+    pub fn with_keyframe_interval_ms(mut self, interval_ms: u32) -> Result<Self, ProfileError> {
+        if !(MIN_KEYFRAME_INTERVAL_MS..=MAX_KEYFRAME_INTERVAL_MS).contains(&interval_ms) {
+            return Err(ProfileError::KeyframeIntervalOutOfRange {
+                interval_ms,
+                min: MIN_KEYFRAME_INTERVAL_MS,
+                max: MAX_KEYFRAME_INTERVAL_MS,
+            });
+        }
+
+        self.keyframe_interval_ms = interval_ms;
+        Ok(self)
+    }
+
+    pub fn stream_dimensions(&self) -> (u16, u16) {
+        match self.orientation {
+            Orientation::Landscape => (self.width, self.height),
+            Orientation::Portrait => (self.height, self.width),
         }
+    }
+
+    pub fn new_custom(
+        width: u16,
+        height: u16,
+        fps: u16,
+        target_bitrate_kbps: u32,
+        codec: Codec,
+        lock_policy: LockPolicy,
+    ) -> Result<Self, ProfileError> {
+        check_fps(width, height, fps, target_bitrate_kbps, codec)?;
+
+        if !width.is_multiple_of(2) || !height.is_multiple_of(2) {
+            return Err(ProfileError::OddResolution(width, height));
+        }
+
+        let pixels = u32::from(width) * u32::from(height);
+        if !(MIN_CUSTOM_PIXELS..=MAX_CUSTOM_PIXELS).contains(&pixels) {
+            return Err(ProfileError::PixelCountOutOfRange {
+                width,
+                height,
+                min: MIN_CUSTOM_PIXELS,
+                max: MAX_CUSTOM_PIXELS,
+            });
+        }
+
+        Self::validated(
+            width,
+            height,
+            fps,
+            target_bitrate_kbps,
+            codec,
+            lock_policy,
+            ResolutionMode::Custom,
+        )
+    }
 
+    fn validated(
+        width: u16,
+        height: u16,
+        fps: u16,
+        target_bitrate_kbps: u32,
+        codec: Codec,
+        lock_policy: LockPolicy,
+        resolution_mode: ResolutionMode,
+    ) -> Result<Self, ProfileError> {
         if target_bitrate_kbps == 0 {
             return Err(ProfileError::InvalidBitrate(target_bitrate_kbps));
         }
 
+        let bounds = codec.bitrate_bounds();
+        let min_kbps = kbps_for_bpp(bounds.hard_min_bpp, width, height, fps);
+        if target_bitrate_kbps < min_kbps {
+            return Err(ProfileError::BitrateTooLow {
+                bitrate_kbps: target_bitrate_kbps,
+                min_kbps,
+            });
+        }
+
+        let max_kbps = kbps_for_bpp(bounds.hard_max_bpp, width, height, fps);
+        if target_bitrate_kbps > max_kbps {
+            return Err(ProfileError::BitrateTooHigh {
+                bitrate_kbps: target_bitrate_kbps,
+                max_kbps,
+            });
+        }
+
         Ok(Self {
             width,
             height,
@@ -56,18 +424,481 @@ impl RuntimeProfile {
             target_bitrate_kbps,
             codec,
             lock_policy,
+            adaptive: (lock_policy == LockPolicy::Adaptive).then(AdaptiveConfig::default),
+            resolution_mode,
+            orientation: Orientation::Landscape,
+            color: ColorFormat::default(),
+            keyframe_interval_ms: DEFAULT_KEYFRAME_INTERVAL_MS,
         })
     }
+
+    pub fn bitrate_warnings(&self) -> Vec<BitrateWarning> {
+        let bounds = self.codec.bitrate_bounds();
+        let low_kbps = kbps_for_bpp(bounds.warn_min_bpp, self.width, self.height, self.fps);
+        let high_kbps = kbps_for_bpp(bounds.warn_max_bpp, self.width, self.height, self.fps);
+        let recommended_kbps =
+            recommended_bitrate_kbps(self.width, self.height, self.fps, self.codec);
+
+        if self.target_bitrate_kbps < low_kbps {
+            vec![BitrateWarning::BelowRecommended {
+                bitrate_kbps: self.target_bitrate_kbps,
+                threshold_kbps: low_kbps,
+                recommended_kbps,
+            }]
+        } else if self.target_bitrate_kbps > high_kbps {
+            vec![BitrateWarning::AboveRecommended {
+                bitrate_kbps: self.target_bitrate_kbps,
+                threshold_kbps: high_kbps,
+                recommended_kbps,
+            }]
+        } else {
+            Vec::new()
+        }
+    }
+
+    pub fn coded_dimensions(&self) -> (u16, u16) {
+        match self.resolution_mode {
+            ResolutionMode::Preset => (self.width, self.height),
+            ResolutionMode::Custom => {
+                let block = self.codec.block_size();
+                (
+                    self.width.div_ceil(block).saturating_mul(block),
+                    self.height.div_ceil(block).saturating_mul(block),
+                )
+            }
+        }
+    }
+
+    pub fn check_capability(&self, capability: &HostCapability) -> Result<(), ProfileError> {
+        let (coded_width, coded_height) = self.coded_dimensions();
+        if coded_width > capability.max_width
+            || coded_height > capability.max_height
+            || self.fps > capability.max_fps
+        {
+            return Err(ProfileError::ExceedsCapability {
+                width: self.width,
+                height: self.height,
+                fps: self.fps,
+                candidates: nearest_presets(
+                    self.width,
+                    self.height,
+                    self.fps,
+                    self.target_bitrate_kbps,
+                    self.codec,
+                    Some(capability),
+                ),
+            });
+        }
+
+        Ok(())
+    }
+
+    pub fn with_adaptive(mut self, config: AdaptiveConfig) -> Result<Self, ProfileError> {
+        if self.lock_policy != LockPolicy::Adaptive {
+            return Err(ProfileError::InvalidAdaptiveConfig(
+                "adaptive floors require the adaptive lock policy",
+            ));
+        }
+
+        if !SUPPORTED_FPS.contains(&config.floor_fps) {
+            return Err(ProfileError::InvalidAdaptiveConfig(
+                "floor fps must be one of the supported fps presets",
+            ));
+        }
+
+        if config.floor_fps > self.fps
+            || config.floor_width > self.width
+            || config.floor_height > self.height
+            || config.floor_bitrate_kbps == 0
+            || config.floor_bitrate_kbps > self.target_bitrate_kbps
+        {
+            return Err(ProfileError::InvalidAdaptiveConfig(
+                "floors must be non-zero and must not exceed the requested profile",
+            ));
+        }
+
+        if !(1..=90).contains(&config.bitrate_step_percent) || config.recovery_samples == 0 {
+            return Err(ProfileError::InvalidAdaptiveConfig(
+                "bitrate step must be within 1..=90 percent and recovery samples must be > 0",
+            ));
+        }
+
+        self.adaptive = Some(config);
+        Ok(self)
+    }
+
+    pub fn requires_rebuild(&self, next: &RuntimeProfile) -> bool {
+        (self.width, self.height, self.fps, self.codec)
+            != (next.width, next.height, next.fps, next.codec)
+            || self.resolution_mode != next.resolution_mode
+            || self.orientation != next.orientation
+            || self.color != next.color
+    }
+
+    pub fn stream_level(&self) -> StreamLevel {
+        StreamLevel {
+            width: self.width,
+            height: self.height,
+            fps: self.fps,
+            target_bitrate_kbps: self.target_bitrate_kbps,
+        }
+    }
+
+    pub fn negotiate(
+        width: u16,
+        height: u16,
+        fps: u16,
+        target_bitrate_kbps: u32,
+        codec: Codec,
+        lock_policy: LockPolicy,
+        capability: &HostCapability,
+    ) -> Result<Self, ProfileError> {
+        let candidates = || {
+            nearest_presets(
+                width,
+                height,
+                fps,
+                target_bitrate_kbps,
+                codec,
+                Some(capability),
+            )
+        };
+
+        let profile = match Self::new(width, height, fps, target_bitrate_kbps, codec, lock_policy) {
+            Ok(profile) => profile,
+            Err(ProfileError::UnsupportedFps { fps, .. }) => {
+                return Err(ProfileError::UnsupportedFps {
+                    fps,
+                    candidates: candidates(),
+                })
+            }
+            Err(ProfileError::UnsupportedResolution { width, height, .. }) => {
+                return Err(ProfileError::UnsupportedResolution {
+                    width,
+                    height,
+                    candidates: candidates(),
+                })
+            }
+            Err(err) => return Err(err),
+        };
+
+        profile.check_capability(capability)?;
+        Ok(profile)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ProfileUpdate {
+    pub resolution: Option<(u16, u16)>,
+    pub fps: Option<u16>,
+    pub codec: Option<Codec>,
+    pub target_bitrate_kbps: Option<u32>,
+    pub keyframe_interval_ms: Option<u32>,
+}
+
+impl ProfileUpdate {
+    pub fn apply_to(&self, current: &RuntimeProfile) -> Result<RuntimeProfile, ProfileError> {
+        let (width, height) = self.resolution.unwrap_or((current.width, current.height));
+        let fps = self.fps.unwrap_or(current.fps);
+        let codec = self.codec.unwrap_or(current.codec);
+        let bitrate = self
+            .target_bitrate_kbps
+            .unwrap_or(current.target_bitrate_kbps);
+
+        let profile = match current.resolution_mode {
+            ResolutionMode::Preset => {
+                RuntimeProfile::new(width, height, fps, bitrate, codec, current.lock_policy)?
+            }
+            ResolutionMode::Custom => {
+                RuntimeProfile::new_custom(width, height, fps, bitrate, codec, current.lock_policy)?
+            }
+        };
+        let mut profile = profile
+            .with_orientation(current.orientation)
+            .with_color(current.color)?
+            .with_keyframe_interval_ms(
+                self.keyframe_interval_ms
+                    .unwrap_or(current.keyframe_interval_ms),
+            )?;
+        if let Some(adaptive) = current.adaptive {
+            profile = profile.with_adaptive(adaptive)?;
+        }
+
+        Ok(profile)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StreamLevel {
+    pub width: u16,
+    pub height: u16,
+    pub fps: u16,
+    pub target_bitrate_kbps: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DegradationLadder {
+    levels: Vec<StreamLevel>,
+    recovery_samples: u8,
+}
+
+impl DegradationLadder {
+    pub fn for_profile(profile: &RuntimeProfile) -> Option<Self> {
+        let config = profile.adaptive?;
+        let bounded = |level: StreamLevel| StreamLevel {
+            target_bitrate_kbps: bounded_bitrate_kbps(
+                level.target_bitrate_kbps,
+                level.width,
+                level.height,
+                level.fps,
+                profile.codec,
+            ),
+            ..level
+        };
+        let mut current = bounded(profile.stream_level());
+        let mut levels = vec![current];
+
+        while current.target_bitrate_kbps > config.floor_bitrate_kbps {
+            let step = current.target_bitrate_kbps / 100 * u32::from(config.bitrate_step_percent);
+            let next = bounded(StreamLevel {
+                target_bitrate_kbps: current
+                    .target_bitrate_kbps
+                    .saturating_sub(step.max(1))
+                    .max(config.floor_bitrate_kbps),
+                ..current
+            });
+            if next.target_bitrate_kbps >= current.target_bitrate_kbps {
+                break;
+            }
+            current = next;
+            levels.push(current);
+        }
+
+        for &fps in SUPPORTED_FPS.iter().rev() {
+            if fps < current.fps && fps >= config.floor_fps {
+                current = bounded(StreamLevel { fps, ..current });
+                levels.push(current);
+            }
+        }
+
+        for &(width, height) in SUPPORTED_RESOLUTIONS.iter().rev() {
+            if width < current.width
+                && height <= current.height
+                && width >= config.floor_width
+                && height >= config.floor_height
+            {
+                current = bounded(StreamLevel {
+                    width,
+                    height,
+                    ..current
+                });
+                levels.push(current);
+            }
+        }
+
+        Some(Self {
+            levels,
+            recovery_samples: config.recovery_samples,
+        })
+    }
+
+    pub fn levels(&self) -> &[StreamLevel] {
+        &self.levels
+    }
+
+    pub fn level(&self, index: usize) -> Option<StreamLevel> {
+        self.levels.get(index).copied()
+    }
+
+    pub fn recovery_samples(&self) -> u8 {
+        self.recovery_samples
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PresetCandidate {
+    pub width: u16,
+    pub height: u16,
+    pub fps: u16,
+    pub target_bitrate_kbps: u32,
+}
+
+impl fmt::Display for PresetCandidate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}x{}@{}", self.width, self.height, self.fps)
+    }
+}
+
+pub fn nearest_presets(
+    width: u16,
+    height: u16,
+    fps: u16,
+    target_bitrate_kbps: u32,
+    codec: Codec,
+    capability: Option<&HostCapability>,
+) -> Vec<PresetCandidate> {
+    let requested_pixels = f64::from(width.max(1)) * f64::from(height.max(1));
+    let requested_aspect = f64::from(width.max(1)) / f64::from(height.max(1));
+    let requested_fps = f64::from(fps.max(1));
+    let bits_per_pixel = if target_bitrate_kbps == 0 {
+        REFERENCE_BITS_PER_PIXEL
+    } else {
+        f64::from(target_bitrate_kbps) * 1000.0 / (requested_pixels * requested_fps)
+    };
+
+    let mut ranked = Vec::new();
+    for &(w, h) in &SUPPORTED_RESOLUTIONS {
+        for &f in &SUPPORTED_FPS {
+            if let Some(capability) = capability {
+                if w > capability.max_width || h > capability.max_height || f > capability.max_fps {
+                    continue;
+                }
+            }
+
+            let pixels = f64::from(w) * f64::from(h);
+            let aspect = f64::from(w) / f64::from(h);
+            let score = (pixels / requested_pixels).ln().abs()
+                + (aspect / requested_aspect).ln().abs()
+                + (f64::from(f) / requested_fps).ln().abs();
+            let bitrate_kbps =
+                (bits_per_pixel * pixels * f64::from(f) / 1000.0 / 1000.0).round() as u32 * 1000;
+
+            ranked.push((
+                score,
+                PresetCandidate {
+                    width: w,
+                    height: h,
+                    fps: f,
+                    target_bitrate_kbps: bounded_bitrate_kbps(
+                        bitrate_kbps.max(1000),
+                        w,
+                        h,
+                        f,
+                        codec,
+                    ),
+                },
+            ));
+        }
+    }
+
+    ranked.sort_by(|a, b| {
+        a.0.total_cmp(&b.0)
+            .then_with(|| b.1.fps.cmp(&a.1.fps))
+            .then_with(|| b.1.width.cmp(&a.1.width))
+    });
+    ranked
+        .into_iter()
+        .take(MAX_PRESET_CANDIDATES)
+        .map(|(_, candidate)| candidate)
+        .collect()
+}
+
+fn kbps_for_bpp(bits_per_pixel: f64, width: u16, height: u16, fps: u16) -> u32 {
+    let pixel_rate = f64::from(width) * f64::from(height) * f64::from(fps);
+    (bits_per_pixel * pixel_rate / 1000.0).round() as u32
+}
+
+fn bounded_bitrate_kbps(kbps: u32, width: u16, height: u16, fps: u16, codec: Codec) -> u32 {
+    let bounds = codec.bitrate_bounds();
+    kbps.clamp(
+        kbps_for_bpp(bounds.hard_min_bpp, width, height, fps),
+        kbps_for_bpp(bounds.hard_max_bpp, width, height, fps),
+    )
+}
+
+fn check_fps(
+    width: u16,
+    height: u16,
+    fps: u16,
+    target_bitrate_kbps: u32,
+    codec: Codec,
+) -> Result<(), ProfileError> {
+    if SUPPORTED_FPS.contains(&fps) {
+        return Ok(());
+    }
+
+    Err(ProfileError::UnsupportedFps {
+        fps,
+        candidates: nearest_presets(width, height, fps, target_bitrate_kbps, codec, None),
+    })
+}
+
+fn did_you_mean(candidates: &[PresetCandidate]) -> String {
+    if candidates.is_empty() {
+        return String::new();
+    }
+
+    let list = candidates
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(", ");
+    format!("; did you mean {list}?")
 }
 
 #[derive(Debug, Error, PartialEq, Eq)]
 pub enum ProfileError {
-    #[error("unsupported fps preset {0}; supported fps presets: 60, 90, 120, 144")]
-    UnsupportedFps(u16),
     #[error(
-        "unsupported resolution {0}x{1}; supported resolutions: 1280x720, 1600x900, 1920x1080, 2460x1080"
+        "unsupported fps preset {fps}; supported fps presets: 60, 90, 120, 144{}",
+        did_you_mean(.candidates)
+    )]
+    UnsupportedFps {
+        fps: u16,
+        candidates: Vec<PresetCandidate>,
+    },
+    #[error(
+        "unsupported resolution {width}x{height}; supported resolutions: 1280x720, 1600x900, 1920x1080, 2460x1080{}",
+        did_you_mean(.candidates)
+    )]
+    UnsupportedResolution {
+        width: u16,
+        height: u16,
+        candidates: Vec<PresetCandidate>,
+    },
+    #[error(
+        "{width}x{height}@{fps} exceeds host capability{}",
+        did_you_mean(.candidates)
     )]
-    UnsupportedResolution(u16, u16),
+    ExceedsCapability {
+        width: u16,
+        height: u16,
+        fps: u16,
+        candidates: Vec<PresetCandidate>,
+    },
+    #[error("custom resolution {0}x{1} must use even dimensions")]
+    OddResolution(u16, u16),
+    #[error("custom resolution {width}x{height} must cover {min}..={max} pixels")]
+    PixelCountOutOfRange {
+        width: u16,
+        height: u16,
+        min: u32,
+        max: u32,
+    },
     #[error("target bitrate must be > 0 kbps")]
     InvalidBitrate(u32),
+    #[error("target bitrate {bitrate_kbps}kbps is below the {min_kbps}kbps minimum for this resolution and fps")]
+    BitrateTooLow { bitrate_kbps: u32, min_kbps: u32 },
+    #[error("target bitrate {bitrate_kbps}kbps exceeds the {max_kbps}kbps maximum for this resolution and fps")]
+    BitrateTooHigh { bitrate_kbps: u32, max_kbps: u32 },
+    #[error("color format is unsupported by {codec:?}: {reason}")]
+    UnsupportedColorFormat { codec: Codec, reason: &'static str },
+    #[error("keyframe interval {interval_ms}ms is outside the supported range {min}..={max}ms")]
+    KeyframeIntervalOutOfRange {
+        interval_ms: u32,
+        min: u32,
+        max: u32,
+    },
+    #[error("invalid adaptive config: {0}")]
+    InvalidAdaptiveConfig(&'static str),
+}
+
+impl ProfileError {
+    pub fn candidates(&self) -> &[PresetCandidate] {
+        match self {
+            ProfileError::UnsupportedFps { candidates, .. }
+            | ProfileError::UnsupportedResolution { candidates, .. }
+            | ProfileError::ExceedsCapability { candidates, .. } => candidates,
+            _ => &[],
+        }
+    }
 }
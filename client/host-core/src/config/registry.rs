@@ -0,0 +1,74 @@
+use std::collections::BTreeMap;
+
+use crate::config::loader::{ConfigError, ConfigFile, ProfileLoader};
+use crate::config::profile::RuntimeProfile;
+use crate::pipeline::HostCapability;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProfileRegistry {
+    default: RuntimeProfile,
+    packages: BTreeMap<String, RuntimeProfile>,
+    foreground: Option<String>,
+}
+
+impl ProfileRegistry {
+    pub fn new(default: RuntimeProfile) -> Self {
+        Self {
+            default,
+            packages: BTreeMap::new(),
+            foreground: None,
+        }
+    }
+
+    pub fn from_config(
+        file: &ConfigFile,
+        default: RuntimeProfile,
+        capability: Option<&HostCapability>,
+    ) -> Result<Self, ConfigError> {
+        let mut loader = ProfileLoader::new().with_file(file.clone());
+        if let Some(capability) = capability {
+            loader = loader.with_capability(capability.clone());
+        }
+        Self::from_loader(&loader, default)
+    }
+
+    pub fn from_loader(
+        loader: &ProfileLoader,
+        default: RuntimeProfile,
+    ) -> Result<Self, ConfigError> {
+        let mut registry = Self::new(default);
+        if let Some(file) = loader.file() {
+            for (package, profile_name) in &file.packages {
+                registry.insert(package.clone(), loader.resolve_named(profile_name)?);
+            }
+        }
+
+        Ok(registry)
+    }
+
+    pub fn insert(&mut self, package: impl Into<String>, profile: RuntimeProfile) {
+        self.packages.insert(package.into(), profile);
+    }
+
+    pub fn profile_for(&self, package: Option<&str>) -> &RuntimeProfile {
+        package
+            .and_then(|package| self.packages.get(package))
+            .unwrap_or(&self.default)
+    }
+
+    pub fn foreground(&self) -> Option<&str> {
+        self.foreground.as_deref()
+    }
+
+    pub fn on_foreground_changed(&mut self, package: Option<&str>) -> Option<&RuntimeProfile> {
+        if self.foreground.as_deref() == package {
+            return None;
+        }
+
+        let previous = self.profile_for(self.foreground.as_deref()).clone();
+        self.foreground = package.map(str::to_string);
+
+        let next = self.profile_for(package);
+        (*next != previous).then_some(next)
+    }
+}
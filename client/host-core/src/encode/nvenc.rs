@@ -1,4 +1,9 @@
-use crate::config::profile::{Codec, RuntimeProfile};
+use std::process::Command;
+
+use crate::config::profile::{Codec, ColorFormat, RuntimeProfile, StreamLevel};
+
+pub const NVIDIA_SMI: &str = "nvidia-smi";
+pub const AV1_MIN_COMPUTE_CAPABILITY: (u8, u8) = (8, 9);
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct EncoderDescriptor {
@@ -6,20 +11,74 @@ pub struct EncoderDescriptor {
     pub target_bitrate_kbps: u32,
     pub low_latency: bool,
     pub preset: String,
+    pub color: ColorFormat,
+    pub keyframe_interval_ms: u32,
+    pub gop_frames: u32,
 }
 
 impl EncoderDescriptor {
     pub fn from_profile(profile: &RuntimeProfile) -> Self {
-        let preset = match profile.fps {
-            120 | 144 => "p1_low_latency_hq",
-            _ => "p3_low_latency",
-        };
-
         Self {
             codec: profile.codec,
             target_bitrate_kbps: profile.target_bitrate_kbps,
             low_latency: true,
-            preset: preset.to_string(),
+            preset: preset_for(profile.codec, profile.fps).to_string(),
+            color: profile.color,
+            keyframe_interval_ms: profile.keyframe_interval_ms,
+            gop_frames: gop_frames_for(profile.keyframe_interval_ms, profile.fps),
         }
     }
+
+    pub fn apply_level(&mut self, level: StreamLevel) {
+        self.target_bitrate_kbps = level.target_bitrate_kbps;
+        self.preset = preset_for(self.codec, level.fps).to_string();
+        self.gop_frames = gop_frames_for(self.keyframe_interval_ms, level.fps);
+    }
+
+    pub fn set_keyframe_interval_ms(&mut self, interval_ms: u32, fps: u16) {
+        self.keyframe_interval_ms = interval_ms;
+        self.gop_frames = gop_frames_for(interval_ms, fps);
+    }
+}
+
+fn gop_frames_for(interval_ms: u32, fps: u16) -> u32 {
+    (interval_ms * u32::from(fps) / 1000).max(1)
+}
+
+fn preset_for(codec: Codec, fps: u16) -> &'static str {
+    match (codec, fps) {
+        (Codec::Av1, 120 | 144) => "p2_low_latency_av1",
+        (Codec::Av1, _) => "p4_low_latency_av1",
+        (_, 120 | 144) => "p1_low_latency_hq",
+        _ => "p3_low_latency",
+    }
+}
+
+pub fn detect_codecs(nvidia_smi: &str) -> Vec<Codec> {
+    let compute_capability = Command::new(nvidia_smi)
+        .args(["--query-gpu=compute_cap", "--format=csv,noheader"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| parse_compute_capability(&String::from_utf8_lossy(&output.stdout)));
+
+    codecs_for_compute_capability(compute_capability)
+}
+
+pub fn parse_compute_capability(output: &str) -> Option<(u8, u8)> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (major, minor) = line.trim().split_once('.')?;
+            Some((major.parse().ok()?, minor.parse().ok()?))
+        })
+        .max()
+}
+
+pub fn codecs_for_compute_capability(compute_capability: Option<(u8, u8)>) -> Vec<Codec> {
+    if compute_capability.is_some_and(|capability| capability >= AV1_MIN_COMPUTE_CAPABILITY) {
+        vec![Codec::Av1, Codec::Hevc, Codec::H264]
+    } else {
+        vec![Codec::Hevc, Codec::H264]
+    }
 }
@@ -0,0 +1,44 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Rotation {
+    #[default]
+    Deg0,
+    Deg90,
+    Deg180,
+    Deg270,
+}
+
+impl Rotation {
+    pub fn from_quarter_turns(turns: u8) -> Self {
+        match turns % 4 {
+            0 => Rotation::Deg0,
+            1 => Rotation::Deg90,
+            2 => Rotation::Deg180,
+            _ => Rotation::Deg270,
+        }
+    }
+
+    pub fn quarter_turns(self) -> u8 {
+        match self {
+            Rotation::Deg0 => 0,
+            Rotation::Deg90 => 1,
+            Rotation::Deg180 => 2,
+            Rotation::Deg270 => 3,
+        }
+    }
+
+    pub fn swaps_axes(self) -> bool {
+        matches!(self, Rotation::Deg90 | Rotation::Deg270)
+    }
+
+    pub fn to_natural(self, x: f32, y: f32) -> (f32, f32) {
+        match self {
+            Rotation::Deg0 => (x, y),
+            Rotation::Deg90 => (1.0 - y, x),
+            Rotation::Deg180 => (1.0 - x, 1.0 - y),
+            Rotation::Deg270 => (y, 1.0 - x),
+        }
+    }
+}
@@ -1,5 +1,7 @@
 use thiserror::Error;
 
+use crate::geometry::Rotation;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct EmulatorPoint {
     pub x: u32,
@@ -12,6 +14,7 @@ pub struct ViewportMapping {
     window_height: u32,
     emulator_width: u32,
     emulator_height: u32,
+    rotation: Rotation,
     scale: f32,
     offset_x: f32,
     offset_y: f32,
@@ -28,24 +31,48 @@ impl ViewportMapping {
             return Err(MappingError::InvalidDimensions);
         }
 
-        let sx = window_width as f32 / emulator_width as f32;
-        let sy = window_height as f32 / emulator_height as f32;
-        let scale = sx.min(sy);
-
-        let content_width = emulator_width as f32 * scale;
-        let content_height = emulator_height as f32 * scale;
-        let offset_x = (window_width as f32 - content_width) * 0.5;
-        let offset_y = (window_height as f32 - content_height) * 0.5;
-
-        Ok(Self {
+        let mut mapping = Self {
             window_width,
             window_height,
             emulator_width,
             emulator_height,
-            scale,
-            offset_x,
-            offset_y,
-        })
+            rotation: Rotation::Deg0,
+            scale: 1.0,
+            offset_x: 0.0,
+            offset_y: 0.0,
+        };
+        mapping.layout();
+        Ok(mapping)
+    }
+
+    pub fn with_rotation(mut self, rotation: Rotation) -> Self {
+        self.rotation = rotation;
+        self.layout();
+        self
+    }
+
+    pub fn rotation(&self) -> Rotation {
+        self.rotation
+    }
+
+    fn displayed_size(&self) -> (u32, u32) {
+        if self.rotation.swaps_axes() {
+            (self.emulator_height, self.emulator_width)
+        } else {
+            (self.emulator_width, self.emulator_height)
+        }
+    }
+
+    fn layout(&mut self) {
+        let (displayed_width, displayed_height) = self.displayed_size();
+        let sx = self.window_width as f32 / displayed_width as f32;
+        let sy = self.window_height as f32 / displayed_height as f32;
+        self.scale = sx.min(sy);
+
+        let content_width = displayed_width as f32 * self.scale;
+        let content_height = displayed_height as f32 * self.scale;
+        self.offset_x = (self.window_width as f32 - content_width) * 0.5;
+        self.offset_y = (self.window_height as f32 - content_height) * 0.5;
     }
 
     pub fn normalized_to_emulator(&self, x: f32, y: f32) -> Result<EmulatorPoint, MappingError> {
@@ -53,6 +80,7 @@ impl ViewportMapping {
             return Err(MappingError::OutOfRangeNormalized);
         }
 
+        let (x, y) = self.rotation.to_natural(x, y);
         let max_x = self.emulator_width.saturating_sub(1) as f32;
         let max_y = self.emulator_height.saturating_sub(1) as f32;
 
@@ -69,8 +97,9 @@ impl ViewportMapping {
 
         let xf = x as f32;
         let yf = y as f32;
-        let active_width = self.emulator_width as f32 * self.scale;
-        let active_height = self.emulator_height as f32 * self.scale;
+        let (displayed_width, displayed_height) = self.displayed_size();
+        let active_width = displayed_width as f32 * self.scale;
+        let active_height = displayed_height as f32 * self.scale;
 
         if xf < self.offset_x
             || xf > self.offset_x + active_width
@@ -80,14 +109,20 @@ impl ViewportMapping {
             return Err(MappingError::OutsideActiveViewport);
         }
 
-        let local_x = ((xf - self.offset_x) / self.scale)
-            .clamp(0.0, self.emulator_width.saturating_sub(1) as f32);
-        let local_y = ((yf - self.offset_y) / self.scale)
-            .clamp(0.0, self.emulator_height.saturating_sub(1) as f32);
+        let max_x = displayed_width.saturating_sub(1) as f32;
+        let max_y = displayed_height.saturating_sub(1) as f32;
+        let local_x = ((xf - self.offset_x) / self.scale).clamp(0.0, max_x);
+        let local_y = ((yf - self.offset_y) / self.scale).clamp(0.0, max_y);
+        let (natural_x, natural_y) = match self.rotation {
+            Rotation::Deg0 => (local_x, local_y),
+            Rotation::Deg90 => (max_y - local_y, local_x),
+            Rotation::Deg180 => (max_x - local_x, max_y - local_y),
+            Rotation::Deg270 => (local_y, max_x - local_x),
+        };
 
         Ok(EmulatorPoint {
-            x: local_x.round() as u32,
-            y: local_y.round() as u32,
+            x: natural_x.round() as u32,
+            y: natural_y.round() as u32,
         })
     }
 }
@@ -1,3 +1,5 @@
+use crate::geometry::Rotation;
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum AdbDeviceState {
     Device,
@@ -41,6 +43,48 @@ pub fn find_mumu_candidate(devices: &[AdbDevice]) -> Option<AdbDevice> {
         .cloned()
 }
 
+pub fn parse_foreground_package(raw: &str) -> Option<String> {
+    ["mCurrentFocus=", "mFocusedApp="].iter().find_map(|key| {
+        raw.lines()
+            .filter_map(|line| line.trim().strip_prefix(key))
+            .find_map(|rest| {
+                rest.split_whitespace()
+                    .find_map(|token| token.split_once('/'))
+                    .map(|(package, _)| package.to_string())
+                    .filter(|package| !package.is_empty())
+            })
+    })
+}
+
+pub fn parse_display_rotation(raw: &str) -> Option<Rotation> {
+    raw.lines().find_map(|line| {
+        let line = line.trim();
+        let value = line
+            .strip_prefix("SurfaceOrientation:")
+            .or_else(|| line.strip_prefix("mCurrentRotation="))?
+            .trim();
+        let value = value.strip_prefix("ROTATION_").unwrap_or(value);
+
+        match value {
+            "0" => Some(Rotation::Deg0),
+            "1" | "90" => Some(Rotation::Deg90),
+            "2" | "180" => Some(Rotation::Deg180),
+            "3" | "270" => Some(Rotation::Deg270),
+            _ => None,
+        }
+    })
+}
+
+pub fn parse_display_size(raw: &str) -> Option<(u32, u32)> {
+    let size = |key: &str| {
+        raw.lines().find_map(|line| {
+            let (width, height) = line.trim().strip_prefix(key)?.trim().split_once('x')?;
+            Some((width.trim().parse().ok()?, height.trim().parse().ok()?))
+        })
+    };
+    size("Override size:").or_else(|| size("Physical size:"))
+}
+
 fn parse_state(raw: &str) -> AdbDeviceState {
     match raw {
         "device" => AdbDeviceState::Device,
@@ -1,20 +1,43 @@
-use std::process::Command;
+use std::io::Write;
+use std::process::{Child, ChildStdin, Command, Stdio};
 
 use thiserror::Error;
 
-use crate::input::mumu::adb::{find_mumu_candidate, parse_adb_devices, AdbDevice};
+use crate::geometry::Rotation;
+use crate::input::mumu::adb::{
+    find_mumu_candidate, parse_adb_devices, parse_display_rotation, parse_display_size,
+    parse_foreground_package, AdbDevice,
+};
+use crate::input::mumu::keyboard::ShellInput;
 use crate::input::mumu::minitouch::{MinitouchBuilder, TouchPoint};
+use crate::input::mumu::slots::{SlotAllocator, SlotOwner};
 use crate::protocol::control::{PointerAction, PointerEvent};
 
+pub const MINITOUCH_PATH: &str = "/data/local/tmp/minitouch";
+
 #[derive(Debug, Clone, Copy)]
 pub struct MumuBridge {
     width: u32,
     height: u32,
+    rotation: Rotation,
 }
 
 impl MumuBridge {
     pub fn new(width: u32, height: u32) -> Self {
-        Self { width, height }
+        Self {
+            width,
+            height,
+            rotation: Rotation::Deg0,
+        }
+    }
+
+    pub fn with_rotation(mut self, rotation: Rotation) -> Self {
+        self.rotation = rotation;
+        self
+    }
+
+    pub fn rotation(&self) -> Rotation {
+        self.rotation
     }
 
     pub fn discover_serial_from_adb_output(&self, raw: &str) -> Result<String, MumuBridgeError> {
@@ -44,52 +67,228 @@ impl MumuBridge {
         Ok(parse_adb_devices(&stdout))
     }
 
+    pub fn query_foreground_package(
+        &self,
+        adb_path: &str,
+        serial: &str,
+    ) -> Result<Option<String>, MumuBridgeError> {
+        let output = Command::new(adb_path)
+            .args(["-s", serial, "shell", "dumpsys", "window", "windows"])
+            .output()
+            .map_err(MumuBridgeError::AdbExecution)?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+            return Err(MumuBridgeError::AdbFailed(stderr));
+        }
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        Ok(parse_foreground_package(&stdout))
+    }
+
+    pub fn query_rotation(
+        &self,
+        adb_path: &str,
+        serial: &str,
+    ) -> Result<Rotation, MumuBridgeError> {
+        let output = Command::new(adb_path)
+            .args(["-s", serial, "shell", "dumpsys", "input"])
+            .output()
+            .map_err(MumuBridgeError::AdbExecution)?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+            return Err(MumuBridgeError::AdbFailed(stderr));
+        }
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        parse_display_rotation(&stdout).ok_or(MumuBridgeError::RotationUnavailable)
+    }
+
+    pub fn query_display_size(
+        &self,
+        adb_path: &str,
+        serial: &str,
+    ) -> Result<(u32, u32), MumuBridgeError> {
+        let output = Command::new(adb_path)
+            .args(["-s", serial, "shell", "wm", "size"])
+            .output()
+            .map_err(MumuBridgeError::AdbExecution)?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+            return Err(MumuBridgeError::AdbFailed(stderr));
+        }
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        parse_display_size(&stdout).ok_or(MumuBridgeError::DisplaySizeUnavailable)
+    }
+
+    pub fn spawn_minitouch(
+        &self,
+        adb_path: &str,
+        serial: &str,
+    ) -> Result<MinitouchProcess, MumuBridgeError> {
+        let mut child = Command::new(adb_path)
+            .args(["-s", serial, "shell", MINITOUCH_PATH, "-i"])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(MumuBridgeError::AdbExecution)?;
+        let stdin = child.stdin.take().ok_or(MumuBridgeError::MinitouchClosed)?;
+        Ok(MinitouchProcess { child, stdin })
+    }
+
+    pub fn inject_shell_input(
+        &self,
+        adb_path: &str,
+        serial: &str,
+        input: &ShellInput,
+    ) -> Result<(), MumuBridgeError> {
+        let output = Command::new(adb_path)
+            .args(["-s", serial, "shell"])
+            .args(input.shell_args())
+            .output()
+            .map_err(MumuBridgeError::AdbExecution)?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+            return Err(MumuBridgeError::AdbFailed(stderr));
+        }
+
+        Ok(())
+    }
+
     pub fn build_minitouch_payload(
         &self,
         events: &[PointerEvent],
+    ) -> Result<String, MumuBridgeError> {
+        self.build_minitouch_payload_with_slots(&mut SlotAllocator::default(), events)
+    }
+
+    pub fn build_minitouch_payload_with_slots(
+        &self,
+        slots: &mut SlotAllocator,
+        events: &[PointerEvent],
     ) -> Result<String, MumuBridgeError> {
         if events.is_empty() {
             return Err(MumuBridgeError::EmptyEventBatch);
         }
 
         let mut builder = MinitouchBuilder::default();
+        let mut dropped_down = false;
+        let mut unknown_pointer = None;
         for event in events {
-            let point = TouchPoint::new(
-                event.pointer_id,
-                self.to_pixel(event.x, self.width),
-                self.to_pixel(event.y, self.height),
-                self.to_pressure(event.pressure),
-            );
+            let owner = SlotOwner::Touch(event.pointer_id);
+            let slot = match event.action {
+                PointerAction::Down => slots.acquire(owner),
+                PointerAction::Move => slots.slot(owner),
+                PointerAction::Up | PointerAction::Cancel => slots.release(owner),
+            };
+            let Some(slot) = slot else {
+                match event.action {
+                    PointerAction::Down => dropped_down = true,
+                    _ => unknown_pointer = unknown_pointer.or(Some(event.pointer_id)),
+                }
+                continue;
+            };
 
+            let point = self.touch_point(slot, event.x, event.y, event.pressure);
             builder = match event.action {
                 PointerAction::Down => builder.down(point),
                 PointerAction::Move => builder.move_to(point),
-                PointerAction::Up | PointerAction::Cancel => builder.up(event.pointer_id),
+                PointerAction::Up | PointerAction::Cancel => builder.up(slot),
             };
         }
 
+        if builder.is_empty() {
+            return Err(match unknown_pointer {
+                Some(pointer_id) if !dropped_down => MumuBridgeError::UnknownPointer(pointer_id),
+                _ => MumuBridgeError::NoFreeSlot,
+            });
+        }
+
         Ok(builder.commit().to_string())
     }
 
-    fn to_pixel(&self, normalized: f32, max: u32) -> u32 {
+    pub(crate) fn displayed_size(&self) -> (u32, u32) {
+        if self.rotation.swaps_axes() {
+            (self.height, self.width)
+        } else {
+            (self.width, self.height)
+        }
+    }
+
+    pub(crate) fn display_point(&self, x: f32, y: f32) -> (u32, u32) {
+        let (width, height) = self.displayed_size();
+        (self.scale_pixel(x, width), self.scale_pixel(y, height))
+    }
+
+    pub(crate) fn touch_point(&self, slot: u8, x: f32, y: f32, pressure: f32) -> TouchPoint {
+        let (x, y) = self.rotation.to_natural(x, y);
+        TouchPoint::new(
+            slot,
+            self.scale_pixel(x, self.width),
+            self.scale_pixel(y, self.height),
+            self.scale_pressure(pressure),
+        )
+    }
+
+    fn scale_pixel(&self, normalized: f32, max: u32) -> u32 {
         let safe = normalized.clamp(0.0, 1.0);
         (safe * max as f32).round() as u32
     }
 
-    fn to_pressure(&self, normalized: f32) -> u32 {
+    fn scale_pressure(&self, normalized: f32) -> u32 {
         let safe = normalized.clamp(0.0, 1.0);
         (safe * 100.0).round() as u32
     }
 }
 
+#[derive(Debug)]
+pub struct MinitouchProcess {
+    child: Child,
+    stdin: ChildStdin,
+}
+
+impl MinitouchProcess {
+    pub fn send(&mut self, payload: &str) -> Result<(), MumuBridgeError> {
+        if let Ok(Some(_)) = self.child.try_wait() {
+            return Err(MumuBridgeError::MinitouchClosed);
+        }
+        self.stdin
+            .write_all(payload.as_bytes())
+            .and_then(|()| self.stdin.flush())
+            .map_err(MumuBridgeError::AdbExecution)
+    }
+}
+
+impl Drop for MinitouchProcess {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
 #[derive(Debug, Error)]
 pub enum MumuBridgeError {
     #[error("no MuMu-compatible ADB device found")]
     NoDeviceFound,
     #[error("touch event batch is empty")]
     EmptyEventBatch,
+    #[error("no touch slot is free for the event batch")]
+    NoFreeSlot,
+    #[error("pointer {0} does not hold a touch slot")]
+    UnknownPointer(u8),
     #[error("failed to execute adb: {0}")]
     AdbExecution(std::io::Error),
     #[error("adb command failed: {0}")]
     AdbFailed(String),
+    #[error("adb output did not report a display rotation")]
+    RotationUnavailable,
+    #[error("adb output did not report a display size")]
+    DisplaySizeUnavailable,
+    #[error("minitouch process has exited")]
+    MinitouchClosed,
 }
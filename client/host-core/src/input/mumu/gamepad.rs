@@ -0,0 +1,314 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::input::mumu::bridge::MumuBridge;
+use crate::input::mumu::minitouch::{MinitouchBuilder, TouchPoint};
+use crate::input::mumu::slots::{SlotAllocator, SlotOwner};
+use crate::protocol::control::{AnalogStick, GamepadButton, GamepadState};
+
+pub const DEFAULT_DEAD_ZONE: f32 = 0.15;
+pub const DEFAULT_TRIGGER_THRESHOLD: f32 = 0.5;
+
+const GAMEPAD_TOUCH_PRESSURE: f32 = 0.5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Stick {
+    Left,
+    Right,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Trigger {
+    Left,
+    Right,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum GamepadControl {
+    Button(GamepadButton),
+    Trigger(Trigger),
+    Stick(Stick),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ButtonBinding {
+    pub button: GamepadButton,
+    pub x: f32,
+    pub y: f32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TriggerBinding {
+    pub trigger: Trigger,
+    pub x: f32,
+    pub y: f32,
+    #[serde(default = "default_trigger_threshold")]
+    pub threshold: f32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StickBinding {
+    pub stick: Stick,
+    pub x: f32,
+    pub y: f32,
+    pub radius: f32,
+    #[serde(default = "default_dead_zone")]
+    pub dead_zone: f32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GamepadLayout {
+    pub package: String,
+    #[serde(default)]
+    pub buttons: Vec<ButtonBinding>,
+    #[serde(default)]
+    pub triggers: Vec<TriggerBinding>,
+    #[serde(default)]
+    pub sticks: Vec<StickBinding>,
+}
+
+fn default_trigger_threshold() -> f32 {
+    DEFAULT_TRIGGER_THRESHOLD
+}
+
+fn default_dead_zone() -> f32 {
+    DEFAULT_DEAD_ZONE
+}
+
+impl GamepadLayout {
+    pub fn load(path: &Path) -> Result<Self, GamepadError> {
+        let raw = std::fs::read_to_string(path)
+            .map_err(|err| GamepadError::Io(path.to_path_buf(), err))?;
+        Self::from_toml_str(&raw)
+    }
+
+    pub fn from_toml_str(raw: &str) -> Result<Self, GamepadError> {
+        let layout: GamepadLayout = toml::from_str(raw).map_err(GamepadError::Toml)?;
+        layout.validate()?;
+        Ok(layout)
+    }
+
+    pub fn validate(&self) -> Result<(), GamepadError> {
+        let mut seen = Vec::new();
+        let anchors = self
+            .buttons
+            .iter()
+            .map(|b| (GamepadControl::Button(b.button), b.x, b.y))
+            .chain(
+                self.triggers
+                    .iter()
+                    .map(|t| (GamepadControl::Trigger(t.trigger), t.x, t.y)),
+            )
+            .chain(
+                self.sticks
+                    .iter()
+                    .map(|s| (GamepadControl::Stick(s.stick), s.x, s.y)),
+            );
+
+        for (control, x, y) in anchors {
+            if seen.contains(&control) {
+                return Err(GamepadError::DuplicateBinding(control));
+            }
+            seen.push(control);
+
+            if !(0.0..=1.0).contains(&x) || !(0.0..=1.0).contains(&y) {
+                return Err(GamepadError::InvalidAnchor(control));
+            }
+        }
+
+        for trigger in &self.triggers {
+            if !(trigger.threshold > 0.0 && trigger.threshold <= 1.0) {
+                return Err(GamepadError::InvalidThreshold(trigger.trigger));
+            }
+        }
+
+        for stick in &self.sticks {
+            if !(stick.radius > 0.0 && stick.radius <= 0.5) {
+                return Err(GamepadError::InvalidRadius(stick.stick));
+            }
+            if !(0.0..1.0).contains(&stick.dead_zone) {
+                return Err(GamepadError::InvalidDeadZone(stick.stick));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct ActiveTouch {
+    slot: u8,
+    point: TouchPoint,
+}
+
+#[derive(Debug, Clone)]
+pub struct GamepadMapper {
+    layout: GamepadLayout,
+    bridge: MumuBridge,
+    active: BTreeMap<GamepadControl, ActiveTouch>,
+}
+
+impl GamepadMapper {
+    pub fn new(layout: GamepadLayout, bridge: MumuBridge) -> Self {
+        Self {
+            layout,
+            bridge,
+            active: BTreeMap::new(),
+        }
+    }
+
+    pub fn set_bridge(&mut self, bridge: MumuBridge) {
+        self.bridge = bridge;
+    }
+
+    pub fn layout(&self) -> &GamepadLayout {
+        &self.layout
+    }
+
+    pub fn slot(&self, control: GamepadControl) -> Option<u8> {
+        self.active.get(&control).map(|touch| touch.slot)
+    }
+
+    pub fn on_state(&mut self, slots: &mut SlotAllocator, state: &GamepadState) -> Option<String> {
+        let mut targets = Vec::new();
+        for binding in &self.layout.buttons {
+            let control = GamepadControl::Button(binding.button);
+            let target = state
+                .is_pressed(binding.button)
+                .then_some((binding.x, binding.y));
+            targets.push((control, (binding.x, binding.y), target));
+        }
+        for binding in &self.layout.triggers {
+            let control = GamepadControl::Trigger(binding.trigger);
+            let value = match binding.trigger {
+                Trigger::Left => state.left_trigger,
+                Trigger::Right => state.right_trigger,
+            };
+            let target = (value >= binding.threshold).then_some((binding.x, binding.y));
+            targets.push((control, (binding.x, binding.y), target));
+        }
+        for binding in &self.layout.sticks {
+            let control = GamepadControl::Stick(binding.stick);
+            let axes = match binding.stick {
+                Stick::Left => state.left_stick,
+                Stick::Right => state.right_stick,
+            };
+            let target = self.stick_target(binding, axes);
+            targets.push((control, (binding.x, binding.y), target));
+        }
+
+        let mut builder = MinitouchBuilder::default();
+        for (control, _, target) in &targets {
+            if target.is_none() {
+                if let Some(touch) = self.active.remove(control) {
+                    slots.release(SlotOwner::Gamepad(*control));
+                    builder = builder.up(touch.slot);
+                }
+            }
+        }
+
+        let mut drags = Vec::new();
+        for (control, (anchor_x, anchor_y), target) in targets {
+            let Some((x, y)) = target else {
+                continue;
+            };
+
+            match self.active.get_mut(&control) {
+                Some(touch) => {
+                    let point = self
+                        .bridge
+                        .touch_point(touch.slot, x, y, GAMEPAD_TOUCH_PRESSURE);
+                    if point != touch.point {
+                        touch.point = point;
+                        builder = builder.move_to(point);
+                    }
+                }
+                None => {
+                    let Some(slot) = slots.acquire(SlotOwner::Gamepad(control)) else {
+                        continue;
+                    };
+                    let point = self.bridge.touch_point(slot, x, y, GAMEPAD_TOUCH_PRESSURE);
+                    let anchor =
+                        self.bridge
+                            .touch_point(slot, anchor_x, anchor_y, GAMEPAD_TOUCH_PRESSURE);
+                    if anchor != point {
+                        drags.push(point);
+                    }
+                    self.active.insert(control, ActiveTouch { slot, point });
+                    builder = builder.down(anchor);
+                }
+            }
+        }
+
+        if builder.is_empty() {
+            return None;
+        }
+
+        builder = builder.commit();
+        if !drags.is_empty() {
+            for point in drags {
+                builder = builder.move_to(point);
+            }
+            builder = builder.commit();
+        }
+        Some(builder.to_string())
+    }
+
+    pub fn release_all(&mut self, slots: &mut SlotAllocator) -> Option<String> {
+        if self.active.is_empty() {
+            return None;
+        }
+
+        let mut released = std::mem::take(&mut self.active)
+            .into_iter()
+            .map(|(control, touch)| {
+                slots.release(SlotOwner::Gamepad(control));
+                touch.slot
+            })
+            .collect::<Vec<_>>();
+        released.sort_unstable();
+
+        let builder = released
+            .into_iter()
+            .fold(MinitouchBuilder::default(), MinitouchBuilder::up);
+        Some(builder.commit().to_string())
+    }
+
+    fn stick_target(&self, binding: &StickBinding, axes: AnalogStick) -> Option<(f32, f32)> {
+        let magnitude = axes.x.hypot(axes.y);
+        if magnitude < binding.dead_zone.max(f32::EPSILON) {
+            return None;
+        }
+
+        let scale = magnitude.max(1.0);
+        let (width, height) = self.bridge.displayed_size();
+        let (width, height) = (width.max(1) as f32, height.max(1) as f32);
+        let radius = binding.radius * width.min(height);
+        let x = binding.x + axes.x / scale * radius / width;
+        let y = binding.y + axes.y / scale * radius / height;
+        Some((x.clamp(0.0, 1.0), y.clamp(0.0, 1.0)))
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum GamepadError {
+    #[error("failed to read gamepad layout {0}: {1}")]
+    Io(PathBuf, std::io::Error),
+    #[error("failed to parse gamepad layout: {0}")]
+    Toml(toml::de::Error),
+    #[error("{0:?} is bound more than once")]
+    DuplicateBinding(GamepadControl),
+    #[error("{0:?} anchor must be within [0.0, 1.0]")]
+    InvalidAnchor(GamepadControl),
+    #[error("{0:?} trigger threshold must be within (0.0, 1.0]")]
+    InvalidThreshold(Trigger),
+    #[error("{0:?} stick radius must be within (0.0, 0.5]")]
+    InvalidRadius(Stick),
+    #[error("{0:?} stick dead zone must be within [0.0, 1.0)")]
+    InvalidDeadZone(Stick),
+}
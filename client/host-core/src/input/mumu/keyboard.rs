@@ -0,0 +1,200 @@
+use std::collections::HashMap;
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+
+use crate::protocol::control::{KeyAction, KeyEvent, SystemKey};
+
+pub const LONG_PRESS_MS: u64 = 500;
+
+const IME_BROADCAST_ACTION: &str = "ADB_INPUT_B64";
+const SHELL_SPECIAL_CHARS: &str = "\\\"'`$&|;<>()*?~!#[]{}";
+const MODIFIER_KEYCODES: [u32; 8] = [57, 58, 59, 60, 113, 114, 117, 118];
+const META_MODIFIERS: [(u32, u32); 4] = [
+    (0x0000_1000, 113),
+    (0x0000_0002, 57),
+    (0x0000_0001, 59),
+    (0x0001_0000, 117),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MotionAction {
+    Down,
+    Move,
+    Up,
+    HoverMove,
+}
+
+impl MotionAction {
+    fn as_str(self) -> &'static str {
+        match self {
+            MotionAction::Down => "DOWN",
+            MotionAction::Move => "MOVE",
+            MotionAction::Up => "UP",
+            MotionAction::HoverMove => "HOVER_MOVE",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ShellInput {
+    KeyEvent {
+        keycode: u32,
+        long_press: bool,
+    },
+    KeyCombination(Vec<u32>),
+    Text(String),
+    ImeBroadcast(String),
+    MouseMotion {
+        action: MotionAction,
+        x: u32,
+        y: u32,
+    },
+    MouseScroll {
+        x: u32,
+        y: u32,
+        vertical: f32,
+        horizontal: f32,
+    },
+}
+
+impl ShellInput {
+    pub fn text(text: &str) -> Self {
+        let plain = text
+            .chars()
+            .all(|c| (c.is_ascii_graphic() && c != '%') || c == ' ');
+        if plain {
+            ShellInput::Text(text.to_string())
+        } else {
+            ShellInput::ImeBroadcast(text.to_string())
+        }
+    }
+
+    pub fn system_key(key: SystemKey) -> Self {
+        ShellInput::KeyEvent {
+            keycode: key.keycode(),
+            long_press: false,
+        }
+    }
+
+    pub fn shell_args(&self) -> Vec<String> {
+        match self {
+            ShellInput::KeyEvent {
+                keycode,
+                long_press,
+            } => {
+                let mut args = vec!["input".to_string(), "keyevent".to_string()];
+                if *long_press {
+                    args.push("--longpress".to_string());
+                }
+                args.push(keycode.to_string());
+                args
+            }
+            ShellInput::KeyCombination(keycodes) => ["input", "keycombination"]
+                .into_iter()
+                .map(str::to_string)
+                .chain(keycodes.iter().map(u32::to_string))
+                .collect(),
+            ShellInput::Text(text) => vec![
+                "input".to_string(),
+                "text".to_string(),
+                escape_input_text(text),
+            ],
+            ShellInput::ImeBroadcast(text) => vec![
+                "am".to_string(),
+                "broadcast".to_string(),
+                "-a".to_string(),
+                IME_BROADCAST_ACTION.to_string(),
+                "--es".to_string(),
+                "msg".to_string(),
+                STANDARD.encode(text.as_bytes()),
+            ],
+            ShellInput::MouseMotion { action, x, y } => vec![
+                "input".to_string(),
+                "mouse".to_string(),
+                "motionevent".to_string(),
+                action.as_str().to_string(),
+                x.to_string(),
+                y.to_string(),
+            ],
+            ShellInput::MouseScroll {
+                x,
+                y,
+                vertical,
+                horizontal,
+            } => {
+                let mut args = ["input", "mouse", "scroll"]
+                    .into_iter()
+                    .map(str::to_string)
+                    .chain([x.to_string(), y.to_string()])
+                    .collect::<Vec<_>>();
+                for (axis, value) in [("VSCROLL", vertical), ("HSCROLL", horizontal)] {
+                    if *value != 0.0 {
+                        args.push("--axis".to_string());
+                        args.push(format!("{axis},{value}"));
+                    }
+                }
+                args
+            }
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct KeyInjector {
+    pressed: HashMap<u32, u64>,
+}
+
+impl KeyInjector {
+    pub fn on_key(&mut self, event: &KeyEvent) -> Option<ShellInput> {
+        match event.action {
+            KeyAction::Down => {
+                self.pressed
+                    .entry(event.keycode)
+                    .or_insert(event.timestamp_ms);
+                None
+            }
+            KeyAction::Up => {
+                let pressed_at = self.pressed.remove(&event.keycode)?;
+                if MODIFIER_KEYCODES.contains(&event.keycode) {
+                    return None;
+                }
+
+                let modifiers = META_MODIFIERS
+                    .iter()
+                    .filter(|(mask, _)| event.meta_state & mask != 0)
+                    .map(|&(_, keycode)| keycode)
+                    .collect::<Vec<_>>();
+                if !modifiers.is_empty() {
+                    let mut keycodes = modifiers;
+                    keycodes.push(event.keycode);
+                    return Some(ShellInput::KeyCombination(keycodes));
+                }
+
+                Some(ShellInput::KeyEvent {
+                    keycode: event.keycode,
+                    long_press: event.timestamp_ms.saturating_sub(pressed_at) >= LONG_PRESS_MS,
+                })
+            }
+        }
+    }
+
+    pub fn release_all(&mut self) {
+        self.pressed.clear();
+    }
+}
+
+fn escape_input_text(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if c == ' ' {
+            escaped.push_str("%s");
+            continue;
+        }
+        if SHELL_SPECIAL_CHARS.contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
@@ -1,3 +1,5 @@
+use std::fmt;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct TouchPoint {
     pub slot: u8,
@@ -44,18 +46,21 @@ impl MinitouchBuilder {
         self
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.lines.is_empty()
+    }
+
     pub fn commit(mut self) -> Self {
         self.lines.push("c".to_string());
         self
     }
+}
 
-    pub fn to_string(&self) -> String {
-        if self.lines.is_empty() {
-            return String::new();
+impl fmt::Display for MinitouchBuilder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for line in &self.lines {
+            writeln!(f, "{line}")?;
         }
-
-        let mut payload = self.lines.join("\n");
-        payload.push('\n');
-        payload
+        Ok(())
     }
 }
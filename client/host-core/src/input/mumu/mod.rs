@@ -1,3 +1,7 @@
 pub mod adb;
 pub mod bridge;
+pub mod gamepad;
+pub mod keyboard;
 pub mod minitouch;
+pub mod mouse;
+pub mod slots;
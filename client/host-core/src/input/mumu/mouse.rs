@@ -0,0 +1,190 @@
+use serde::{Deserialize, Serialize};
+
+use crate::input::mumu::bridge::MumuBridge;
+use crate::input::mumu::keyboard::{MotionAction, ShellInput};
+use crate::input::mumu::minitouch::MinitouchBuilder;
+use crate::input::mumu::slots::{SlotAllocator, SlotOwner};
+use crate::protocol::control::{MouseButton, MouseEvent, MouseMotion, SystemKey};
+
+pub const SCROLL_STEP: f32 = 0.08;
+
+const MAX_SCROLL_SWIPE: f32 = 0.4;
+const MOUSE_TOUCH_PRESSURE: f32 = 0.5;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MouseBackend {
+    #[default]
+    Touch,
+    Mouse,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum MouseOutput {
+    Minitouch(String),
+    Shell(ShellInput),
+}
+
+#[derive(Debug, Clone)]
+pub struct MouseTranslator {
+    bridge: MumuBridge,
+    backend: MouseBackend,
+    cursor: (f32, f32),
+    buttons: u32,
+}
+
+impl MouseTranslator {
+    pub fn new(bridge: MumuBridge, backend: MouseBackend) -> Self {
+        Self {
+            bridge,
+            backend,
+            cursor: (0.5, 0.5),
+            buttons: 0,
+        }
+    }
+
+    pub fn set_bridge(&mut self, bridge: MumuBridge) {
+        self.bridge = bridge;
+    }
+
+    pub fn backend(&self) -> MouseBackend {
+        self.backend
+    }
+
+    pub fn cursor(&self) -> (f32, f32) {
+        self.cursor
+    }
+
+    pub fn on_event(&mut self, slots: &mut SlotAllocator, event: &MouseEvent) -> Vec<MouseOutput> {
+        let previous = self.cursor;
+        self.cursor = match event.motion {
+            MouseMotion::Absolute { x, y } => (x, y),
+            MouseMotion::Relative { dx, dy } => (
+                (previous.0 + dx).clamp(0.0, 1.0),
+                (previous.1 + dy).clamp(0.0, 1.0),
+            ),
+        };
+        let moved = self.cursor != previous;
+
+        let was_down = self.buttons & MouseButton::Primary.bit() != 0;
+        let is_down = event.is_pressed(MouseButton::Primary);
+        let pressed = event.buttons & !self.buttons;
+        self.buttons = event.buttons;
+
+        let mut outputs = Vec::new();
+        let primary = match (was_down, is_down) {
+            (false, true) => Some(MotionAction::Down),
+            (true, true) if moved => Some(MotionAction::Move),
+            (true, false) => Some(MotionAction::Up),
+            _ => None,
+        };
+        if let Some(output) = primary.and_then(|action| self.primary(slots, action, moved)) {
+            outputs.push(output);
+        }
+        if event.hover && moved && self.backend == MouseBackend::Mouse {
+            let (x, y) = self.bridge.display_point(self.cursor.0, self.cursor.1);
+            outputs.push(MouseOutput::Shell(ShellInput::MouseMotion {
+                action: MotionAction::HoverMove,
+                x,
+                y,
+            }));
+        }
+
+        if !is_down && (event.scroll_x != 0.0 || event.scroll_y != 0.0) {
+            outputs.extend(self.scroll(slots, event.scroll_x, event.scroll_y));
+        }
+
+        let back = MouseButton::Secondary.bit() | MouseButton::Back.bit();
+        if pressed & back != 0 {
+            outputs.push(MouseOutput::Shell(ShellInput::system_key(SystemKey::Back)));
+        }
+
+        outputs
+    }
+
+    pub fn release_all(&mut self, slots: &mut SlotAllocator) -> Vec<MouseOutput> {
+        let was_down = self.buttons & MouseButton::Primary.bit() != 0;
+        self.buttons = 0;
+        if was_down {
+            self.primary(slots, MotionAction::Up, false)
+                .into_iter()
+                .collect()
+        } else {
+            Vec::new()
+        }
+    }
+
+    fn primary(
+        &self,
+        slots: &mut SlotAllocator,
+        action: MotionAction,
+        moved: bool,
+    ) -> Option<MouseOutput> {
+        let (x, y) = self.cursor;
+
+        match self.backend {
+            MouseBackend::Touch => {
+                let slot = match action {
+                    MotionAction::Down => slots.acquire(SlotOwner::Mouse)?,
+                    MotionAction::Move => slots.slot(SlotOwner::Mouse)?,
+                    MotionAction::Up => slots.release(SlotOwner::Mouse)?,
+                    MotionAction::HoverMove => return None,
+                };
+                let point = self.bridge.touch_point(slot, x, y, MOUSE_TOUCH_PRESSURE);
+                let builder = MinitouchBuilder::default();
+                let builder = match action {
+                    MotionAction::Down => builder.down(point),
+                    MotionAction::Move | MotionAction::HoverMove => builder.move_to(point),
+                    MotionAction::Up if moved => builder.move_to(point).up(slot),
+                    MotionAction::Up => builder.up(slot),
+                };
+                Some(MouseOutput::Minitouch(builder.commit().to_string()))
+            }
+            MouseBackend::Mouse => {
+                let (x, y) = self.bridge.display_point(x, y);
+                Some(MouseOutput::Shell(ShellInput::MouseMotion { action, x, y }))
+            }
+        }
+    }
+
+    fn scroll(
+        &self,
+        slots: &mut SlotAllocator,
+        scroll_x: f32,
+        scroll_y: f32,
+    ) -> Option<MouseOutput> {
+        let (x, y) = self.cursor;
+        match self.backend {
+            MouseBackend::Touch => {
+                let slot = slots.acquire(SlotOwner::Mouse)?;
+                slots.release(SlotOwner::Mouse);
+                let dx = (-scroll_x * SCROLL_STEP).clamp(-MAX_SCROLL_SWIPE, MAX_SCROLL_SWIPE);
+                let dy = (scroll_y * SCROLL_STEP).clamp(-MAX_SCROLL_SWIPE, MAX_SCROLL_SWIPE);
+                let start = self.bridge.touch_point(slot, x, y, MOUSE_TOUCH_PRESSURE);
+                let end = self.bridge.touch_point(
+                    slot,
+                    (x + dx).clamp(0.0, 1.0),
+                    (y + dy).clamp(0.0, 1.0),
+                    MOUSE_TOUCH_PRESSURE,
+                );
+                let payload = MinitouchBuilder::default()
+                    .down(start)
+                    .commit()
+                    .move_to(end)
+                    .commit()
+                    .up(slot)
+                    .commit();
+                Some(MouseOutput::Minitouch(payload.to_string()))
+            }
+            MouseBackend::Mouse => {
+                let (x, y) = self.bridge.display_point(x, y);
+                Some(MouseOutput::Shell(ShellInput::MouseScroll {
+                    x,
+                    y,
+                    vertical: scroll_y,
+                    horizontal: scroll_x,
+                }))
+            }
+        }
+    }
+}
@@ -0,0 +1,47 @@
+use crate::input::mumu::gamepad::GamepadControl;
+use crate::protocol::handshake::MAX_TOUCH_POINTERS;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SlotOwner {
+    Touch(u8),
+    Gamepad(GamepadControl),
+    Mouse,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SlotAllocator {
+    slots: [Option<SlotOwner>; MAX_TOUCH_POINTERS as usize],
+}
+
+impl SlotAllocator {
+    pub fn acquire(&mut self, owner: SlotOwner) -> Option<u8> {
+        if let Some(slot) = self.slot(owner) {
+            return Some(slot);
+        }
+
+        let free = self.slots.iter().position(Option::is_none)?;
+        self.slots[free] = Some(owner);
+        Some(free as u8)
+    }
+
+    pub fn slot(&self, owner: SlotOwner) -> Option<u8> {
+        self.slots
+            .iter()
+            .position(|held| *held == Some(owner))
+            .map(|slot| slot as u8)
+    }
+
+    pub fn release(&mut self, owner: SlotOwner) -> Option<u8> {
+        let slot = self.slot(owner)?;
+        self.slots[usize::from(slot)] = None;
+        Some(slot)
+    }
+
+    pub fn in_use(&self) -> usize {
+        self.slots.iter().filter(|held| held.is_some()).count()
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
@@ -1,6 +1,7 @@
 pub mod capture;
 pub mod config;
 pub mod encode;
+pub mod geometry;
 pub mod input;
 pub mod pipeline;
 pub mod protocol;
@@ -1,11 +1,23 @@
 use std::env;
+use std::path::PathBuf;
 
-use host_core::config::profile::{Codec, LockPolicy, RuntimeProfile};
+use host_core::config::loader::{
+    ConfigFile, ProfileLayer, ProfileLoader, ENV_CONFIG_PATH, ENV_PROFILE_NAME,
+};
+use host_core::config::profile::RuntimeProfile;
+use host_core::encode::nvenc;
 use host_core::pipeline::HostCapability;
 use host_core::session::SessionManager;
 
 fn main() {
-    let profile = match read_profile_from_args() {
+    let capability = HostCapability {
+        max_width: 2560,
+        max_height: 1440,
+        max_fps: 144,
+        codecs: nvenc::detect_codecs(nvenc::NVIDIA_SMI),
+    };
+
+    let profile = match read_profile_from_args(&capability) {
         Ok(profile) => profile,
         Err(err) => {
             eprintln!("配置无效: {err}");
@@ -13,12 +25,9 @@ fn main() {
         }
     };
 
-    let capability = HostCapability {
-        max_width: 2560,
-        max_height: 1440,
-        max_fps: 144,
-        codecs: vec![Codec::Hevc, Codec::H264],
-    };
+    for warning in profile.bitrate_warnings() {
+        eprintln!("码率提示: {warning}");
+    }
 
     let mut session = SessionManager::new();
     match session.start(profile, capability) {
@@ -39,78 +48,62 @@ fn main() {
     }
 }
 
-fn read_profile_from_args() -> Result<RuntimeProfile, String> {
-    let mut fps = 144_u16;
-    let mut width = 2460_u16;
-    let mut height = 1080_u16;
-    let mut bitrate = 80_000_u32;
-    let mut codec = Codec::Hevc;
+fn read_profile_from_args(capability: &HostCapability) -> Result<RuntimeProfile, String> {
+    let mut config_path = env::var(ENV_CONFIG_PATH).ok().map(PathBuf::from);
+    let mut profile_name = env::var(ENV_PROFILE_NAME).ok();
+    let mut cli = ProfileLayer::default();
 
     let args = env::args().skip(1).collect::<Vec<_>>();
     let mut i = 0_usize;
     while i < args.len() {
-        match args[i].as_str() {
-            "--fps" => {
-                i += 1;
-                fps = parse_u16(args.get(i), "--fps")?;
-            }
-            "--resolution" => {
-                i += 1;
-                let value = args.get(i).ok_or("--resolution 缺少参数")?;
-                let (w, h) = parse_resolution(value)?;
-                width = w;
-                height = h;
-            }
-            "--bitrate" => {
-                i += 1;
-                bitrate = parse_u32(args.get(i), "--bitrate")?;
-            }
-            "--codec" => {
-                i += 1;
-                codec = parse_codec(args.get(i).ok_or("--codec 缺少参数")?)?;
-            }
-            other => {
-                return Err(format!("未知参数: {other}"));
+        let key = args[i].as_str();
+        i += 1;
+        let is_field = matches!(
+            key,
+            "--fps"
+                | "--resolution"
+                | "--resolution-mode"
+                | "--bitrate"
+                | "--codec"
+                | "--orientation"
+                | "--lock-policy"
+                | "--color-space"
+                | "--color-range"
+                | "--chroma"
+                | "--bit-depth"
+                | "--keyframe-interval-ms"
+        );
+        if !is_field && key != "--config" && key != "--profile" {
+            return Err(format!("未知参数: {key}"));
+        }
+        let value = args
+            .get(i)
+            .ok_or_else(|| format!("{key} 缺少参数"))?
+            .as_str();
+
+        match key {
+            "--config" => config_path = Some(PathBuf::from(value)),
+            "--profile" => profile_name = Some(value.to_string()),
+            _ => {
+                let field = key.trim_start_matches("--").replace('-', "_");
+                cli.set(&field, value)
+                    .map_err(|err| format!("{key} 参数无效: {err}"))?;
             }
         }
         i += 1;
     }
 
-    RuntimeProfile::new(width, height, fps, bitrate, codec, LockPolicy::TurboLock)
-        .map_err(|err| err.to_string())
-}
-
-fn parse_u16(value: Option<&String>, key: &str) -> Result<u16, String> {
-    value
-        .ok_or_else(|| format!("{key} 缺少参数"))?
-        .parse::<u16>()
-        .map_err(|err| format!("{key} 参数无效: {err}"))
-}
-
-fn parse_u32(value: Option<&String>, key: &str) -> Result<u32, String> {
-    value
-        .ok_or_else(|| format!("{key} 缺少参数"))?
-        .parse::<u32>()
-        .map_err(|err| format!("{key} 参数无效: {err}"))
-}
-
-fn parse_resolution(value: &str) -> Result<(u16, u16), String> {
-    let (w, h) = value.split_once('x').ok_or("分辨率格式必须为 宽x高")?;
-
-    let width = w
-        .parse::<u16>()
-        .map_err(|err| format!("分辨率宽度无效: {err}"))?;
-    let height = h
-        .parse::<u16>()
-        .map_err(|err| format!("分辨率高度无效: {err}"))?;
-
-    Ok((width, height))
-}
-
-fn parse_codec(value: &str) -> Result<Codec, String> {
-    match value {
-        "h264" => Ok(Codec::H264),
-        "hevc" => Ok(Codec::Hevc),
-        _ => Err("编码格式必须是 h264 或 hevc".to_string()),
+    let env_layer = ProfileLayer::from_env_vars(env::vars()).map_err(|err| err.to_string())?;
+    let mut loader = ProfileLoader::new()
+        .with_env(env_layer)
+        .with_cli(cli)
+        .with_capability(capability.clone());
+    if let Some(path) = config_path {
+        loader = loader.with_file(ConfigFile::load(&path).map_err(|err| err.to_string())?);
+    }
+    if let Some(name) = profile_name {
+        loader = loader.with_profile_name(name);
     }
+
+    loader.resolve().map_err(|err| err.to_string())
 }
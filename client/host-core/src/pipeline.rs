@@ -1,7 +1,9 @@
 use thiserror::Error;
 
 use crate::capture::dxgi::CaptureDescriptor;
-use crate::config::profile::{Codec, RuntimeProfile};
+use crate::config::profile::{
+    negotiate_codec, Codec, DegradationLadder, ProfileError, RuntimeProfile, StreamLevel,
+};
 use crate::encode::nvenc::EncoderDescriptor;
 use crate::transport::webrtc::TransportDescriptor;
 
@@ -13,21 +15,52 @@ pub struct HostCapability {
     pub codecs: Vec<Codec>,
 }
 
+impl HostCapability {
+    pub fn negotiate_codec(
+        &self,
+        peer_codecs: &[Codec],
+        preferred: Option<Codec>,
+    ) -> Result<Codec, PipelineError> {
+        negotiate_codec(&self.codecs, peer_codecs, preferred).ok_or_else(|| {
+            PipelineError::NoCommonCodec {
+                host: self.codecs.clone(),
+                peer: peer_codecs.to_vec(),
+            }
+        })
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct PipelineDescriptor {
     pub capture: CaptureDescriptor,
     pub encoder: EncoderDescriptor,
     pub transport: TransportDescriptor,
+    pub ladder: Option<DegradationLadder>,
+}
+
+impl PipelineDescriptor {
+    pub fn apply_level(&mut self, level: StreamLevel) {
+        self.capture.apply_level(level);
+        self.encoder.apply_level(level);
+    }
+
+    pub fn retune(&mut self, profile: &RuntimeProfile) {
+        self.encoder
+            .set_keyframe_interval_ms(profile.keyframe_interval_ms, profile.fps);
+        self.apply_level(profile.stream_level());
+        self.ladder = DegradationLadder::for_profile(profile);
+    }
 }
 
 pub fn build_locked_pipeline(
     profile: &RuntimeProfile,
     capability: &HostCapability,
 ) -> Result<PipelineDescriptor, PipelineError> {
-    if profile.width > capability.max_width || profile.height > capability.max_height {
+    let (coded_width, coded_height) = profile.coded_dimensions();
+    if coded_width > capability.max_width || coded_height > capability.max_height {
         return Err(PipelineError::ResolutionUnsupported {
-            requested_width: profile.width,
-            requested_height: profile.height,
+            requested_width: coded_width,
+            requested_height: coded_height,
             max_width: capability.max_width,
             max_height: capability.max_height,
         });
@@ -44,10 +77,17 @@ pub fn build_locked_pipeline(
         return Err(PipelineError::CodecUnsupported(profile.codec));
     }
 
+    if let Err(ProfileError::UnsupportedColorFormat { codec, reason }) =
+        profile.color.check_codec(profile.codec)
+    {
+        return Err(PipelineError::ColorFormatUnsupported { codec, reason });
+    }
+
     Ok(PipelineDescriptor {
         capture: CaptureDescriptor::from_profile(profile),
         encoder: EncoderDescriptor::from_profile(profile),
         transport: TransportDescriptor::lan_low_latency(),
+        ladder: DegradationLadder::for_profile(profile),
     })
 }
 
@@ -55,6 +95,10 @@ pub fn build_locked_pipeline(
 pub enum PipelineError {
     #[error("requested codec is unsupported: {0:?}")]
     CodecUnsupported(Codec),
+    #[error("host codecs {host:?} and peer codecs {peer:?} have nothing in common")]
+    NoCommonCodec { host: Vec<Codec>, peer: Vec<Codec> },
+    #[error("requested color format is unsupported by {codec:?}: {reason}")]
+    ColorFormatUnsupported { codec: Codec, reason: &'static str },
     #[error("requested fps {requested} exceeds host maximum {max}")]
     FpsUnsupported { requested: u16, max: u16 },
     #[error(
@@ -0,0 +1,273 @@
+use std::fmt;
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use thiserror::Error;
+
+use crate::protocol::control::{
+    CompactTouchDecoder, ControlCodecError, ControlFrame, TouchEnvelope, WireCodec,
+};
+
+pub const SESSION_KEY_LEN: usize = 32;
+pub const AUTH_TAG_LEN: usize = 32;
+pub const AUTH_COUNTER_LEN: usize = 8;
+pub const AUTH_OVERHEAD: usize = AUTH_COUNTER_LEN + AUTH_TAG_LEN;
+pub const REPLAY_WINDOW_FRAMES: u64 = 64;
+pub const DEFAULT_MAX_TIMESTAMP_SKEW_MS: u64 = 2_000;
+
+const SESSION_KEY_CONTEXT: &[u8] = b"linkmycomputer/session-key/v1";
+const DIRECTION_KEY_CONTEXT: &[u8] = b"linkmycomputer/direction-key/v1";
+
+type HmacSha256 = Hmac<Sha256>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    HostToPhone,
+    PhoneToHost,
+}
+
+impl Direction {
+    fn context(self) -> &'static [u8] {
+        match self {
+            Direction::HostToPhone => b"host->phone",
+            Direction::PhoneToHost => b"phone->host",
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Eq)]
+pub struct SessionKey([u8; SESSION_KEY_LEN]);
+
+impl SessionKey {
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, AuthError> {
+        let key = bytes
+            .try_into()
+            .map_err(|_| AuthError::InvalidKeyLength(bytes.len()))?;
+        Ok(Self(key))
+    }
+
+    pub fn from_base64(encoded: &str) -> Result<Self, AuthError> {
+        let bytes = STANDARD
+            .decode(encoded.trim())
+            .map_err(|_| AuthError::InvalidKeyEncoding)?;
+        Self::from_bytes(&bytes)
+    }
+
+    pub fn derive(pairing_key: &[u8], session_nonce: &[u8]) -> Self {
+        let mut mac = keyed(pairing_key);
+        mac.update(SESSION_KEY_CONTEXT);
+        mac.update(session_nonce);
+        Self(mac.finalize().into_bytes().into())
+    }
+
+    pub fn for_session(&self, session_nonce: &[u8]) -> Self {
+        Self::derive(&self.0, session_nonce)
+    }
+
+    pub fn for_direction(&self, direction: Direction) -> Self {
+        let mut mac = keyed(&self.0);
+        mac.update(DIRECTION_KEY_CONTEXT);
+        mac.update(direction.context());
+        Self(mac.finalize().into_bytes().into())
+    }
+
+    fn sign(&self, payload: &[u8]) -> [u8; AUTH_TAG_LEN] {
+        let mut mac = keyed(&self.0);
+        mac.update(payload);
+        mac.finalize().into_bytes().into()
+    }
+
+    fn verify(&self, payload: &[u8], tag: &[u8]) -> bool {
+        let mut mac = keyed(&self.0);
+        mac.update(payload);
+        mac.verify_slice(tag).is_ok()
+    }
+}
+
+impl fmt::Debug for SessionKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SessionKey(..)")
+    }
+}
+
+fn keyed(key: &[u8]) -> HmacSha256 {
+    HmacSha256::new_from_slice(key).expect("HMAC-SHA256 accepts keys of any length")
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct ReplayWindow {
+    highest: Option<u64>,
+    bitmap: u64,
+}
+
+impl ReplayWindow {
+    fn accept(&mut self, id: u64) -> bool {
+        let Some(highest) = self.highest else {
+            self.highest = Some(id);
+            self.bitmap = 1;
+            return true;
+        };
+
+        if id > highest {
+            let shift = id - highest;
+            self.bitmap = if shift >= REPLAY_WINDOW_FRAMES {
+                1
+            } else {
+                (self.bitmap << shift) | 1
+            };
+            self.highest = Some(id);
+            return true;
+        }
+
+        let age = highest - id;
+        if age >= REPLAY_WINDOW_FRAMES || self.bitmap & (1 << age) != 0 {
+            return false;
+        }
+        self.bitmap |= 1 << age;
+        true
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct FrameAuthenticator {
+    key: SessionKey,
+    codec: WireCodec,
+    max_timestamp_skew_ms: u64,
+    next_counter: u64,
+    counters: ReplayWindow,
+    newest_timestamp_ms: Option<u64>,
+}
+
+impl FrameAuthenticator {
+    pub fn new(key: SessionKey, codec: WireCodec) -> Self {
+        Self {
+            key,
+            codec,
+            max_timestamp_skew_ms: DEFAULT_MAX_TIMESTAMP_SKEW_MS,
+            next_counter: 0,
+            counters: ReplayWindow::default(),
+            newest_timestamp_ms: None,
+        }
+    }
+
+    pub fn with_max_timestamp_skew_ms(mut self, skew_ms: u64) -> Self {
+        self.max_timestamp_skew_ms = skew_ms;
+        self
+    }
+
+    pub fn codec(&self) -> WireCodec {
+        self.codec
+    }
+
+    pub fn seal(&mut self, frame: &ControlFrame) -> Result<Vec<u8>, ControlCodecError> {
+        let body = self.codec.encode(frame)?;
+        Ok(self.seal_datagram(&body))
+    }
+
+    pub fn seal_compact_touch(&mut self, compact: &[u8]) -> Vec<u8> {
+        self.seal_datagram(compact)
+    }
+
+    pub fn seal_datagram(&mut self, datagram: &[u8]) -> Vec<u8> {
+        let mut sealed = Vec::with_capacity(datagram.len() + AUTH_OVERHEAD);
+        sealed.extend_from_slice(datagram);
+        sealed.extend_from_slice(&self.next_counter.to_be_bytes());
+        self.next_counter += 1;
+        let tag = self.key.sign(&sealed);
+        sealed.extend_from_slice(&tag);
+        sealed
+    }
+
+    pub fn open(&mut self, payload: &[u8]) -> Result<ControlFrame, ControlCodecError> {
+        let body = self.open_datagram(payload)?;
+        self.accept(body)
+    }
+
+    pub fn open_compact_touch(
+        &mut self,
+        payload: &[u8],
+        decoder: &mut CompactTouchDecoder,
+    ) -> Result<TouchEnvelope, ControlCodecError> {
+        let body = self.open_datagram(payload)?;
+        self.accept_compact_touch(body, decoder)
+    }
+
+    pub fn accept(&mut self, verified: &[u8]) -> Result<ControlFrame, ControlCodecError> {
+        let frame = self.codec.decode(verified)?;
+        self.accept_timestamp(frame.timestamp_ms())
+            .map_err(ControlCodecError::Authentication)?;
+        Ok(frame)
+    }
+
+    pub fn accept_compact_touch(
+        &mut self,
+        verified: &[u8],
+        decoder: &mut CompactTouchDecoder,
+    ) -> Result<TouchEnvelope, ControlCodecError> {
+        let touch = decoder.decode(verified)?;
+        self.accept_timestamp(touch.events.iter().map(|event| event.timestamp_ms).max())
+            .map_err(ControlCodecError::Authentication)?;
+        Ok(touch)
+    }
+
+    pub fn open_datagram<'a>(&mut self, payload: &'a [u8]) -> Result<&'a [u8], ControlCodecError> {
+        let (signed, tag) = payload
+            .split_last_chunk::<AUTH_TAG_LEN>()
+            .ok_or(ControlCodecError::Authentication(AuthError::MissingTag))?;
+        let (body, counter) = signed
+            .split_last_chunk::<AUTH_COUNTER_LEN>()
+            .filter(|(body, _)| !body.is_empty())
+            .ok_or(ControlCodecError::Authentication(AuthError::MissingTag))?;
+        if !self.key.verify(signed, tag) {
+            return Err(ControlCodecError::Authentication(AuthError::BadTag));
+        }
+
+        let counter = u64::from_be_bytes(*counter);
+        if !self.counters.accept(counter) {
+            return Err(ControlCodecError::Authentication(AuthError::ReplayedFrame(
+                counter,
+            )));
+        }
+        Ok(body)
+    }
+
+    fn accept_timestamp(&mut self, timestamp_ms: Option<u64>) -> Result<(), AuthError> {
+        let Some(timestamp_ms) = timestamp_ms else {
+            return Ok(());
+        };
+        if let Some(newest_ms) = self.newest_timestamp_ms {
+            if newest_ms.saturating_sub(timestamp_ms) > self.max_timestamp_skew_ms {
+                return Err(AuthError::StaleTimestamp {
+                    timestamp_ms,
+                    newest_ms,
+                });
+            }
+        }
+
+        self.newest_timestamp_ms = Some(
+            self.newest_timestamp_ms
+                .map_or(timestamp_ms, |newest| newest.max(timestamp_ms)),
+        );
+        Ok(())
+    }
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum AuthError {
+    #[error("session key must be {SESSION_KEY_LEN} bytes, got {0}")]
+    InvalidKeyLength(usize),
+    #[error("session key is not valid base64")]
+    InvalidKeyEncoding,
+    #[error("frame is too short to carry an authentication tag")]
+    MissingTag,
+    #[error("authentication tag does not match")]
+    BadTag,
+    #[error("sealed frame {0} was already accepted or is outside the replay window")]
+    ReplayedFrame(u64),
+    #[error(
+        "frame timestamp {timestamp_ms} ms is too far behind the newest accepted {newest_ms} ms"
+    )]
+    StaleTimestamp { timestamp_ms: u64, newest_ms: u64 },
+}
@@ -0,0 +1,122 @@
+use std::collections::VecDeque;
+
+use thiserror::Error;
+
+use crate::protocol::control::Pong;
+
+pub const OFFSET_FILTER_WINDOW: usize = 8;
+
+const RTT_GAIN: f64 = 1.0 / 8.0;
+const JITTER_GAIN: f64 = 1.0 / 16.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ClockSample {
+    pub rtt_ms: f64,
+    pub offset_ms: f64,
+}
+
+impl ClockSample {
+    pub fn from_pong(pong: &Pong, arrival_ms: u64) -> Result<Self, ClockError> {
+        if arrival_ms < pong.origin_timestamp_ms {
+            return Err(ClockError::ArrivalBeforeOrigin {
+                origin_ms: pong.origin_timestamp_ms,
+                arrival_ms,
+            });
+        }
+        if pong.transmit_timestamp_ms < pong.receive_timestamp_ms {
+            return Err(ClockError::TransmitBeforeReceive {
+                receive_ms: pong.receive_timestamp_ms,
+                transmit_ms: pong.transmit_timestamp_ms,
+            });
+        }
+
+        let t0 = pong.origin_timestamp_ms as f64;
+        let t1 = pong.receive_timestamp_ms as f64;
+        let t2 = pong.transmit_timestamp_ms as f64;
+        let t3 = arrival_ms as f64;
+
+        Ok(Self {
+            rtt_ms: ((t3 - t0) - (t2 - t1)).max(0.0),
+            offset_ms: ((t1 - t0) + (t2 - t3)) / 2.0,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ClockEstimator {
+    smoothed_rtt_ms: Option<f64>,
+    jitter_ms: f64,
+    last_rtt_ms: Option<f64>,
+    window: VecDeque<ClockSample>,
+    samples: u64,
+}
+
+impl ClockEstimator {
+    pub fn on_pong(&mut self, pong: &Pong, arrival_ms: u64) -> Result<ClockSample, ClockError> {
+        let sample = ClockSample::from_pong(pong, arrival_ms)?;
+        self.record(sample);
+        Ok(sample)
+    }
+
+    pub fn record(&mut self, sample: ClockSample) {
+        self.smoothed_rtt_ms = Some(match self.smoothed_rtt_ms {
+            Some(srtt) => srtt + (sample.rtt_ms - srtt) * RTT_GAIN,
+            None => sample.rtt_ms,
+        });
+
+        if let Some(last) = self.last_rtt_ms {
+            let delta = (sample.rtt_ms - last).abs();
+            self.jitter_ms += (delta - self.jitter_ms) * JITTER_GAIN;
+        }
+        self.last_rtt_ms = Some(sample.rtt_ms);
+
+        if self.window.len() == OFFSET_FILTER_WINDOW {
+            self.window.pop_front();
+        }
+        self.window.push_back(sample);
+        self.samples += 1;
+    }
+
+    pub fn samples(&self) -> u64 {
+        self.samples
+    }
+
+    pub fn rtt_ms(&self) -> Option<f64> {
+        self.smoothed_rtt_ms
+    }
+
+    pub fn jitter_ms(&self) -> f64 {
+        self.jitter_ms
+    }
+
+    pub fn offset_ms(&self) -> Option<f64> {
+        self.window
+            .iter()
+            .min_by(|a, b| a.rtt_ms.total_cmp(&b.rtt_ms))
+            .map(|sample| sample.offset_ms)
+    }
+
+    pub fn peer_to_host_ms(&self, peer_timestamp_ms: u64) -> Option<u64> {
+        let host = peer_timestamp_ms as f64 - self.offset_ms()?;
+        Some(host.round().max(0.0) as u64)
+    }
+
+    pub fn latency_ms(&self, peer_timestamp_ms: u64, host_now_ms: u64) -> Option<u64> {
+        let sent = self.peer_to_host_ms(peer_timestamp_ms)?;
+        Some(host_now_ms.saturating_sub(sent))
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum ClockError {
+    #[error("pong arrived at {arrival_ms} ms, before its ping was sent at {origin_ms} ms")]
+    ArrivalBeforeOrigin { origin_ms: u64, arrival_ms: u64 },
+    #[error(
+        "peer transmitted at {transmit_ms} ms, before it received the ping at {receive_ms} ms"
+    )]
+    TransmitBeforeReceive { receive_ms: u64, transmit_ms: u64 },
+}
@@ -1,6 +1,48 @@
+use std::collections::BTreeMap;
+
+use prost::Message;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
+use crate::config::profile::{Codec, ProfileUpdate};
+use crate::geometry::Rotation;
+use crate::protocol::auth::AuthError;
+use crate::protocol::display::{ContentRect, DisplayInfo, StreamProfile};
+use crate::protocol::handshake::{Hello, Welcome};
+use crate::protocol::proto;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WireCodec {
+    #[default]
+    Json,
+    Protobuf,
+}
+
+impl WireCodec {
+    pub fn detect(payload: &[u8]) -> Option<Self> {
+        let first = *payload.first()?;
+        if first == b'{' || (first.is_ascii_whitespace() && is_padded_json(payload)) {
+            return Some(WireCodec::Json);
+        }
+        (first & 0x07 == 2).then_some(WireCodec::Protobuf)
+    }
+
+    pub fn encode(self, frame: &ControlFrame) -> Result<Vec<u8>, ControlCodecError> {
+        match self {
+            WireCodec::Json => frame.to_wire_bytes(),
+            WireCodec::Protobuf => Ok(frame.to_protobuf_bytes()),
+        }
+    }
+
+    pub fn decode(self, payload: &[u8]) -> Result<ControlFrame, ControlCodecError> {
+        match self {
+            WireCodec::Json => ControlFrame::from_wire_bytes(payload),
+            WireCodec::Protobuf => ControlFrame::from_protobuf_bytes(payload),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum PointerAction {
@@ -26,11 +68,607 @@ pub struct TouchEnvelope {
     pub events: Vec<PointerEvent>,
 }
 
+pub const COMPACT_TOUCH_KEYFRAME: u8 = 0xD0;
+pub const COMPACT_TOUCH_DELTA: u8 = 0xD1;
+pub const DEFAULT_COMPACT_KEYFRAME_INTERVAL: u32 = 60;
+
+const MAX_EVENTS_PER_FRAME: usize = 32;
+const COMPACT_POINTER_MASK: u8 = 0x0f;
+const COMPACT_PRESSURE_FLAG: u8 = 0x40;
+
+// A newline is also a valid protobuf tag, so whitespace-led payloads only count
+// as JSON when they actually parse as JSON.
+fn is_padded_json(payload: &[u8]) -> bool {
+    payload.trim_ascii_start().starts_with(b"{")
+        && serde_json::from_slice::<serde::de::IgnoredAny>(payload).is_ok()
+}
+
+pub fn is_compact_touch(payload: &[u8]) -> bool {
+    matches!(
+        payload.first(),
+        Some(&COMPACT_TOUCH_KEYFRAME | &COMPACT_TOUCH_DELTA)
+    )
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct QuantizedPointer {
+    x: u16,
+    y: u16,
+    pressure: u8,
+}
+
+impl QuantizedPointer {
+    fn from_event(event: &PointerEvent) -> Self {
+        Self {
+            x: (event.x * f32::from(u16::MAX)).round() as u16,
+            y: (event.y * f32::from(u16::MAX)).round() as u16,
+            pressure: (event.pressure * f32::from(u8::MAX)).round() as u8,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct CompactTouchState {
+    frame_id: u64,
+    timestamp_ms: u64,
+    pointers: BTreeMap<u8, QuantizedPointer>,
+}
+
+impl CompactTouchState {
+    fn track(&mut self, pointer_id: u8, action: PointerAction, pointer: QuantizedPointer) {
+        match action {
+            PointerAction::Up | PointerAction::Cancel => {
+                self.pointers.remove(&pointer_id);
+            }
+            PointerAction::Down | PointerAction::Move => {
+                self.pointers.insert(pointer_id, pointer);
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CompactTouchEncoder {
+    keyframe_interval: u32,
+    since_keyframe: u32,
+    state: Option<CompactTouchState>,
+}
+
+impl Default for CompactTouchEncoder {
+    fn default() -> Self {
+        Self::new(DEFAULT_COMPACT_KEYFRAME_INTERVAL)
+    }
+}
+
+impl CompactTouchEncoder {
+    pub fn new(keyframe_interval: u32) -> Self {
+        Self {
+            keyframe_interval: keyframe_interval.max(1),
+            since_keyframe: 0,
+            state: None,
+        }
+    }
+
+    pub fn force_keyframe(&mut self) {
+        self.state = None;
+    }
+
+    pub fn encode(&mut self, touch: &TouchEnvelope) -> Result<Vec<u8>, ControlCodecError> {
+        validate_touch(touch)?;
+        if let Some(event) = touch
+            .events
+            .iter()
+            .find(|event| event.pointer_id > COMPACT_POINTER_MASK)
+        {
+            return Err(ControlCodecError::InvalidPointerId(u32::from(
+                event.pointer_id,
+            )));
+        }
+
+        let delta_base = self.state.take().filter(|state| {
+            self.since_keyframe < self.keyframe_interval
+                && touch.frame_id == state.frame_id.wrapping_add(1)
+        });
+        let mut out = Vec::with_capacity(4 + touch.events.len() * 6);
+        let mut state = match delta_base {
+            Some(state) => {
+                out.push(COMPACT_TOUCH_DELTA);
+                self.since_keyframe += 1;
+                state
+            }
+            None => {
+                out.push(COMPACT_TOUCH_KEYFRAME);
+                self.since_keyframe = 1;
+                CompactTouchState::default()
+            }
+        };
+        write_varint(&mut out, touch.frame_id);
+        write_varint(&mut out, touch.events.len() as u64);
+
+        for event in &touch.events {
+            let pointer = QuantizedPointer::from_event(event);
+            let reference = state
+                .pointers
+                .get(&event.pointer_id)
+                .copied()
+                .unwrap_or_default();
+            let pressure_changed = pointer.pressure != reference.pressure;
+
+            out.push(
+                event.pointer_id
+                    | (action_bits(event.action) << 4)
+                    | if pressure_changed {
+                        COMPACT_PRESSURE_FLAG
+                    } else {
+                        0
+                    },
+            );
+            write_signed(
+                &mut out,
+                event.timestamp_ms.wrapping_sub(state.timestamp_ms) as i64,
+            );
+            write_signed(&mut out, i64::from(pointer.x) - i64::from(reference.x));
+            write_signed(&mut out, i64::from(pointer.y) - i64::from(reference.y));
+            if pressure_changed {
+                out.push(pointer.pressure);
+            }
+
+            state.timestamp_ms = event.timestamp_ms;
+            state.track(event.pointer_id, event.action, pointer);
+        }
+
+        state.frame_id = touch.frame_id;
+        self.state = Some(state);
+        Ok(out)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct CompactTouchDecoder {
+    state: Option<CompactTouchState>,
+    last_frame_id: Option<u64>,
+    keyframe_requested: bool,
+}
+
+impl CompactTouchDecoder {
+    pub fn needs_keyframe(&self) -> bool {
+        self.state.is_none()
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    pub fn decode(&mut self, payload: &[u8]) -> Result<TouchEnvelope, ControlCodecError> {
+        match self.decode_frame(payload) {
+            Ok((touch, state)) => {
+                self.state = Some(state);
+                self.last_frame_id = Some(touch.frame_id);
+                self.keyframe_requested = false;
+                Ok(touch)
+            }
+            Err(err @ ControlCodecError::StaleCompactTouch { .. }) => Err(err),
+            Err(err) => {
+                self.state = None;
+                Err(err)
+            }
+        }
+    }
+
+    pub fn take_keyframe_request(&mut self) -> Option<ControlFrame> {
+        if self.state.is_some() || self.keyframe_requested {
+            return None;
+        }
+
+        self.keyframe_requested = true;
+        Some(ControlFrame::RequestTouchKeyframe {
+            last_frame_id: self.last_frame_id.unwrap_or_default(),
+        })
+    }
+
+    fn decode_frame(
+        &self,
+        payload: &[u8],
+    ) -> Result<(TouchEnvelope, CompactTouchState), ControlCodecError> {
+        let (&kind, mut input) = payload
+            .split_first()
+            .ok_or(ControlCodecError::EmptyPayload)?;
+        let mut state = match kind {
+            COMPACT_TOUCH_KEYFRAME => CompactTouchState::default(),
+            COMPACT_TOUCH_DELTA => self
+                .state
+                .clone()
+                .ok_or(ControlCodecError::MissingTouchKeyframe)?,
+            other => return Err(ControlCodecError::UnknownCompactTouchKind(other)),
+        };
+        let frame_id = read_varint(&mut input)?;
+        let applied = match kind {
+            COMPACT_TOUCH_DELTA => Some(state.frame_id),
+            _ => self.last_frame_id,
+        };
+        if let Some(applied) = applied {
+            if !frame_is_newer(frame_id, applied) {
+                return Err(ControlCodecError::StaleCompactTouch { frame_id, applied });
+            }
+        }
+        if kind == COMPACT_TOUCH_DELTA {
+            let expected = state.frame_id.wrapping_add(1);
+            if frame_id != expected {
+                return Err(ControlCodecError::CompactTouchGap { expected, frame_id });
+            }
+        }
+
+        let count = usize::try_from(read_varint(&mut input)?).unwrap_or(usize::MAX);
+        if count > MAX_EVENTS_PER_FRAME {
+            return Err(ControlCodecError::TooManyEvents(count));
+        }
+
+        let mut events = Vec::with_capacity(count);
+        for _ in 0..count {
+            let header = read_byte(&mut input)?;
+            let pointer_id = header & COMPACT_POINTER_MASK;
+            let action = action_from_bits((header >> 4) & 0x03);
+            let reference = state.pointers.get(&pointer_id).copied().unwrap_or_default();
+
+            let timestamp_ms = state
+                .timestamp_ms
+                .wrapping_add(read_signed(&mut input)? as u64);
+            let x = apply_delta(reference.x, read_signed(&mut input)?)?;
+            let y = apply_delta(reference.y, read_signed(&mut input)?)?;
+            let pressure = if header & COMPACT_PRESSURE_FLAG != 0 {
+                read_byte(&mut input)?
+            } else {
+                reference.pressure
+            };
+            let pointer = QuantizedPointer { x, y, pressure };
+
+            events.push(PointerEvent {
+                pointer_id,
+                action,
+                x: f32::from(x) / f32::from(u16::MAX),
+                y: f32::from(y) / f32::from(u16::MAX),
+                pressure: f32::from(pressure) / f32::from(u8::MAX),
+                timestamp_ms,
+            });
+            state.timestamp_ms = timestamp_ms;
+            state.track(pointer_id, action, pointer);
+        }
+        if !input.is_empty() {
+            return Err(ControlCodecError::TrailingCompactTouchBytes(input.len()));
+        }
+
+        let touch = TouchEnvelope { frame_id, events };
+        validate_touch(&touch)?;
+        state.frame_id = frame_id;
+        Ok((touch, state))
+    }
+}
+
+fn frame_is_newer(frame_id: u64, applied: u64) -> bool {
+    (frame_id.wrapping_sub(applied) as i64) > 0
+}
+
+fn action_bits(action: PointerAction) -> u8 {
+    match action {
+        PointerAction::Down => 0,
+        PointerAction::Move => 1,
+        PointerAction::Up => 2,
+        PointerAction::Cancel => 3,
+    }
+}
+
+fn action_from_bits(bits: u8) -> PointerAction {
+    match bits {
+        0 => PointerAction::Down,
+        1 => PointerAction::Move,
+        2 => PointerAction::Up,
+        _ => PointerAction::Cancel,
+    }
+}
+
+fn apply_delta(reference: u16, delta: i64) -> Result<u16, ControlCodecError> {
+    u16::try_from(i64::from(reference) + delta)
+        .map_err(|_| ControlCodecError::InvalidCoordinateRange)
+}
+
+fn write_varint(out: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        out.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+fn write_signed(out: &mut Vec<u8>, value: i64) {
+    write_varint(out, ((value << 1) ^ (value >> 63)) as u64);
+}
+
+fn read_byte(input: &mut &[u8]) -> Result<u8, ControlCodecError> {
+    let (&byte, rest) = input
+        .split_first()
+        .ok_or(ControlCodecError::TruncatedCompactTouch)?;
+    *input = rest;
+    Ok(byte)
+}
+
+fn read_varint(input: &mut &[u8]) -> Result<u64, ControlCodecError> {
+    let mut value = 0_u64;
+    for shift in (0..64).step_by(7) {
+        let byte = read_byte(input)?;
+        value |= u64::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err(ControlCodecError::VarintOverflow)
+}
+
+fn read_signed(input: &mut &[u8]) -> Result<i64, ControlCodecError> {
+    let value = read_varint(input)?;
+    Ok((value >> 1) as i64 ^ -((value & 1) as i64))
+}
+
+pub const MAX_ANDROID_KEYCODE: u32 = 316;
+pub const MAX_TEXT_CHARS: usize = 512;
+
+const KNOWN_META_STATE_MASK: u32 = 0x0077_70ff;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyAction {
+    Down,
+    Up,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyEvent {
+    pub keycode: u32,
+    pub action: KeyAction,
+    #[serde(default)]
+    pub meta_state: u32,
+    pub timestamp_ms: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SystemKey {
+    Back,
+    Home,
+    Recents,
+    VolumeUp,
+    VolumeDown,
+    VolumeMute,
+}
+
+impl SystemKey {
+    pub fn keycode(self) -> u32 {
+        match self {
+            SystemKey::Back => 4,
+            SystemKey::Home => 3,
+            SystemKey::Recents => 187,
+            SystemKey::VolumeUp => 24,
+            SystemKey::VolumeDown => 25,
+            SystemKey::VolumeMute => 164,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GamepadButton {
+    A,
+    B,
+    X,
+    Y,
+    LeftBumper,
+    RightBumper,
+    Back,
+    Start,
+    Guide,
+    LeftStick,
+    RightStick,
+    DpadUp,
+    DpadDown,
+    DpadLeft,
+    DpadRight,
+}
+
+impl GamepadButton {
+    pub const ALL: [GamepadButton; 15] = [
+        GamepadButton::A,
+        GamepadButton::B,
+        GamepadButton::X,
+        GamepadButton::Y,
+        GamepadButton::LeftBumper,
+        GamepadButton::RightBumper,
+        GamepadButton::Back,
+        GamepadButton::Start,
+        GamepadButton::Guide,
+        GamepadButton::LeftStick,
+        GamepadButton::RightStick,
+        GamepadButton::DpadUp,
+        GamepadButton::DpadDown,
+        GamepadButton::DpadLeft,
+        GamepadButton::DpadRight,
+    ];
+
+    pub fn bit(self) -> u32 {
+        1 << self as u32
+    }
+}
+
+const KNOWN_GAMEPAD_BUTTON_MASK: u32 = (1 << GamepadButton::ALL.len()) - 1;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct AnalogStick {
+    pub x: f32,
+    pub y: f32,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct GamepadState {
+    pub buttons: u32,
+    pub left_trigger: f32,
+    pub right_trigger: f32,
+    pub left_stick: AnalogStick,
+    pub right_stick: AnalogStick,
+    pub timestamp_ms: u64,
+}
+
+impl GamepadState {
+    pub fn is_pressed(&self, button: GamepadButton) -> bool {
+        self.buttons & button.bit() != 0
+    }
+
+    pub fn press(&mut self, button: GamepadButton) {
+        self.buttons |= button.bit();
+    }
+
+    pub fn release(&mut self, button: GamepadButton) {
+        self.buttons &= !button.bit();
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MouseButton {
+    Primary,
+    Secondary,
+    Middle,
+    Back,
+    Forward,
+}
+
+impl MouseButton {
+    pub const ALL: [MouseButton; 5] = [
+        MouseButton::Primary,
+        MouseButton::Secondary,
+        MouseButton::Middle,
+        MouseButton::Back,
+        MouseButton::Forward,
+    ];
+
+    pub fn bit(self) -> u32 {
+        1 << self as u32
+    }
+}
+
+const KNOWN_MOUSE_BUTTON_MASK: u32 = (1 << MouseButton::ALL.len()) - 1;
+pub const MAX_SCROLL_DETENTS: f32 = 32.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum MouseMotion {
+    Absolute { x: f32, y: f32 },
+    Relative { dx: f32, dy: f32 },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MouseEvent {
+    pub motion: MouseMotion,
+    #[serde(default)]
+    pub buttons: u32,
+    #[serde(default)]
+    pub scroll_x: f32,
+    #[serde(default)]
+    pub scroll_y: f32,
+    #[serde(default)]
+    pub hover: bool,
+    pub timestamp_ms: u64,
+}
+
+impl MouseEvent {
+    pub fn is_pressed(&self, button: MouseButton) -> bool {
+        self.buttons & button.bit() != 0
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyframeReason {
+    DecodeError,
+    PacketLoss,
+    StreamStart,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProfileRequest {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub codec: Option<Codec>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub width: Option<u16>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub height: Option<u16>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fps: Option<u16>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target_bitrate_kbps: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub keyframe_interval_ms: Option<u32>,
+}
+
+impl ProfileRequest {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+impl From<ProfileRequest> for ProfileUpdate {
+    fn from(request: ProfileRequest) -> Self {
+        Self {
+            resolution: request.width.zip(request.height),
+            fps: request.fps,
+            codec: request.codec,
+            target_bitrate_kbps: request.target_bitrate_kbps,
+            keyframe_interval_ms: request.keyframe_interval_ms,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Pong {
+    pub origin_timestamp_ms: u64,
+    pub receive_timestamp_ms: u64,
+    pub transmit_timestamp_ms: u64,
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(tag = "kind", content = "payload", rename_all = "snake_case")]
 pub enum ControlFrame {
     Touch(TouchEnvelope),
-    Ping { timestamp_ms: u64 },
+    Ping {
+        timestamp_ms: u64,
+    },
+    Pong(Pong),
+    Hello(Hello),
+    Welcome(Welcome),
+    Key(KeyEvent),
+    Text {
+        text: String,
+    },
+    SystemKey(SystemKey),
+    Gamepad(GamepadState),
+    Mouse(MouseEvent),
+    DisplayInfo(DisplayInfo),
+    OrientationChanged {
+        rotation: Rotation,
+        content: ContentRect,
+    },
+    SessionProfileChanged(StreamProfile),
+    RequestKeyframe {
+        reason: KeyframeReason,
+        timestamp_ms: u64,
+    },
+    PauseStream {
+        timestamp_ms: u64,
+    },
+    ResumeStream {
+        timestamp_ms: u64,
+    },
+    RequestProfile(ProfileRequest),
+    RequestTouchKeyframe {
+        last_frame_id: u64,
+    },
 }
 
 impl ControlFrame {
@@ -49,35 +687,225 @@ impl ControlFrame {
         Ok(frame)
     }
 
+    pub fn to_protobuf_bytes(&self) -> Vec<u8> {
+        proto::ControlFrame::from(self).encode_to_vec()
+    }
+
+    pub fn from_protobuf_bytes(payload: &[u8]) -> Result<Self, ControlCodecError> {
+        if payload.is_empty() {
+            return Err(ControlCodecError::EmptyPayload);
+        }
+
+        let decoded =
+            proto::ControlFrame::decode(payload).map_err(ControlCodecError::ProtobufDecode)?;
+        let frame = ControlFrame::try_from(decoded)?;
+        frame.validate()?;
+        Ok(frame)
+    }
+
+    pub fn is_host_originated(&self) -> bool {
+        matches!(
+            self,
+            ControlFrame::Welcome(_)
+                | ControlFrame::DisplayInfo(_)
+                | ControlFrame::OrientationChanged { .. }
+                | ControlFrame::SessionProfileChanged(_)
+                | ControlFrame::RequestTouchKeyframe { .. }
+        )
+    }
+
+    pub fn is_stream_control(&self) -> bool {
+        matches!(
+            self,
+            ControlFrame::RequestKeyframe { .. }
+                | ControlFrame::PauseStream { .. }
+                | ControlFrame::ResumeStream { .. }
+                | ControlFrame::RequestProfile(_)
+        )
+    }
+
+    pub fn timestamp_ms(&self) -> Option<u64> {
+        match self {
+            ControlFrame::Touch(touch) => touch.events.iter().map(|event| event.timestamp_ms).max(),
+            ControlFrame::Ping { timestamp_ms } => Some(*timestamp_ms),
+            ControlFrame::Pong(pong) => Some(pong.transmit_timestamp_ms),
+            ControlFrame::Key(key) => Some(key.timestamp_ms),
+            ControlFrame::Gamepad(state) => Some(state.timestamp_ms),
+            ControlFrame::Mouse(mouse) => Some(mouse.timestamp_ms),
+            ControlFrame::RequestKeyframe { timestamp_ms, .. }
+            | ControlFrame::PauseStream { timestamp_ms }
+            | ControlFrame::ResumeStream { timestamp_ms } => Some(*timestamp_ms),
+            _ => None,
+        }
+    }
+
     fn validate(&self) -> Result<(), ControlCodecError> {
-        const MAX_EVENTS_PER_FRAME: usize = 32;
+        match self {
+            ControlFrame::Touch(touch) => validate_touch(touch),
+            ControlFrame::Key(key) => validate_key(key),
+            ControlFrame::Text { text } => validate_text(text),
+            ControlFrame::Gamepad(state) => validate_gamepad(state),
+            ControlFrame::Mouse(mouse) => validate_mouse(mouse),
+            ControlFrame::RequestProfile(request) => validate_profile_request(request),
+            ControlFrame::DisplayInfo(info) => validate_display_info(info),
+            ControlFrame::OrientationChanged { content, .. } => validate_content_rect(content),
+            _ => Ok(()),
+        }
+    }
+}
 
-        if let ControlFrame::Touch(touch) = self {
-            if touch.events.is_empty() {
-                return Err(ControlCodecError::EmptyTouchFrame);
-            }
+fn validate_touch(touch: &TouchEnvelope) -> Result<(), ControlCodecError> {
+    if touch.events.is_empty() {
+        return Err(ControlCodecError::EmptyTouchFrame);
+    }
 
-            if touch.events.len() > MAX_EVENTS_PER_FRAME {
-                return Err(ControlCodecError::TooManyEvents(touch.events.len()));
-            }
+    if touch.events.len() > MAX_EVENTS_PER_FRAME {
+        return Err(ControlCodecError::TooManyEvents(touch.events.len()));
+    }
 
-            for event in &touch.events {
-                if !event.x.is_finite()
-                    || !event.y.is_finite()
-                    || !(0.0..=1.0).contains(&event.x)
-                    || !(0.0..=1.0).contains(&event.y)
-                {
-                    return Err(ControlCodecError::InvalidCoordinateRange);
-                }
-
-                if !event.pressure.is_finite() || !(0.0..=1.0).contains(&event.pressure) {
-                    return Err(ControlCodecError::InvalidPressureRange);
-                }
-            }
+    for event in &touch.events {
+        if !is_unit(event.x) || !is_unit(event.y) {
+            return Err(ControlCodecError::InvalidCoordinateRange);
         }
 
-        Ok(())
+        if !is_unit(event.pressure) {
+            return Err(ControlCodecError::InvalidPressureRange);
+        }
+    }
+
+    Ok(())
+}
+
+pub(crate) fn is_unit(value: f32) -> bool {
+    value.is_finite() && (0.0..=1.0).contains(&value)
+}
+
+fn validate_key(key: &KeyEvent) -> Result<(), ControlCodecError> {
+    if !(1..=MAX_ANDROID_KEYCODE).contains(&key.keycode) {
+        return Err(ControlCodecError::InvalidKeycode(key.keycode));
+    }
+
+    if key.meta_state & !KNOWN_META_STATE_MASK != 0 {
+        return Err(ControlCodecError::InvalidMetaState(key.meta_state));
+    }
+
+    Ok(())
+}
+
+fn validate_text(text: &str) -> Result<(), ControlCodecError> {
+    if text.is_empty() {
+        return Err(ControlCodecError::EmptyText);
+    }
+
+    let chars = text.chars().count();
+    if chars > MAX_TEXT_CHARS {
+        return Err(ControlCodecError::TextTooLong(chars));
+    }
+
+    if text.chars().any(char::is_control) {
+        return Err(ControlCodecError::TextControlCharacter);
     }
+
+    Ok(())
+}
+
+fn validate_gamepad(state: &GamepadState) -> Result<(), ControlCodecError> {
+    if state.buttons & !KNOWN_GAMEPAD_BUTTON_MASK != 0 {
+        return Err(ControlCodecError::UnknownGamepadButtons(state.buttons));
+    }
+
+    for trigger in [state.left_trigger, state.right_trigger] {
+        if !trigger.is_finite() || !(0.0..=1.0).contains(&trigger) {
+            return Err(ControlCodecError::InvalidTriggerRange);
+        }
+    }
+
+    for stick in [state.left_stick, state.right_stick] {
+        if !stick.x.is_finite()
+            || !stick.y.is_finite()
+            || !(-1.0..=1.0).contains(&stick.x)
+            || !(-1.0..=1.0).contains(&stick.y)
+        {
+            return Err(ControlCodecError::InvalidStickRange);
+        }
+    }
+
+    Ok(())
+}
+
+fn validate_mouse(mouse: &MouseEvent) -> Result<(), ControlCodecError> {
+    let (x, y, range) = match mouse.motion {
+        MouseMotion::Absolute { x, y } => (x, y, 0.0..=1.0),
+        MouseMotion::Relative { dx, dy } => (dx, dy, -1.0..=1.0),
+    };
+    if !x.is_finite() || !y.is_finite() || !range.contains(&x) || !range.contains(&y) {
+        return Err(ControlCodecError::InvalidCoordinateRange);
+    }
+
+    if mouse.buttons & !KNOWN_MOUSE_BUTTON_MASK != 0 {
+        return Err(ControlCodecError::UnknownMouseButtons(mouse.buttons));
+    }
+
+    for delta in [mouse.scroll_x, mouse.scroll_y] {
+        if !delta.is_finite() || delta.abs() > MAX_SCROLL_DETENTS {
+            return Err(ControlCodecError::InvalidScrollDelta);
+        }
+    }
+
+    if mouse.hover && mouse.buttons != 0 {
+        return Err(ControlCodecError::HoverWithButtons);
+    }
+
+    Ok(())
+}
+
+fn validate_profile_request(request: &ProfileRequest) -> Result<(), ControlCodecError> {
+    if request.is_empty() {
+        return Err(ControlCodecError::EmptyProfileRequest);
+    }
+
+    if request.width.is_some() != request.height.is_some() {
+        return Err(ControlCodecError::PartialResolution);
+    }
+
+    let fields = [
+        ("width", request.width.map(u32::from)),
+        ("height", request.height.map(u32::from)),
+        ("fps", request.fps.map(u32::from)),
+        ("target_bitrate_kbps", request.target_bitrate_kbps),
+        ("keyframe_interval_ms", request.keyframe_interval_ms),
+    ];
+    for (field, value) in fields {
+        if value == Some(0) {
+            return Err(ControlCodecError::FieldOutOfRange(field));
+        }
+    }
+
+    Ok(())
+}
+
+fn validate_display_info(info: &DisplayInfo) -> Result<(), ControlCodecError> {
+    if info.width == 0 || info.height == 0 {
+        return Err(ControlCodecError::EmptyDisplay);
+    }
+
+    validate_content_rect(&info.content)
+}
+
+fn validate_content_rect(content: &ContentRect) -> Result<(), ControlCodecError> {
+    if content.width == 0
+        || content.height == 0
+        || content.frame_width == 0
+        || content.frame_height == 0
+    {
+        return Err(ControlCodecError::EmptyContentRect);
+    }
+
+    if !content.is_within_frame() {
+        return Err(ControlCodecError::ContentOutsideFrame);
+    }
+
+    Ok(())
 }
 
 #[derive(Debug, Error)]
@@ -88,6 +916,78 @@ pub enum ControlCodecError {
     Serialize(serde_json::Error),
     #[error("failed to decode control frame: {0}")]
     Deserialize(serde_json::Error),
+    #[error("failed to decode protobuf control frame: {0}")]
+    ProtobufDecode(prost::DecodeError),
+    #[error("control frame failed authentication: {0}")]
+    Authentication(AuthError),
+    #[error("control frame has no payload")]
+    MissingPayload,
+    #[error("pointer id {0} does not fit in a u8")]
+    InvalidPointerId(u32),
+    #[error("unknown pointer action value {0}")]
+    UnknownPointerAction(i32),
+    #[error("unknown key action value {0}")]
+    UnknownKeyAction(i32),
+    #[error("unknown system key value {0}")]
+    UnknownSystemKey(i32),
+    #[error("keycode {0} is outside the Android keycode range")]
+    InvalidKeycode(u32),
+    #[error("meta state {0:#x} contains unknown modifier bits")]
+    InvalidMetaState(u32),
+    #[error("text frame cannot be empty")]
+    EmptyText,
+    #[error("text frame exceeds max length: {0} characters")]
+    TextTooLong(usize),
+    #[error("text frame must not contain control characters")]
+    TextControlCharacter,
+    #[error("gamepad buttons {0:#x} contain unknown button bits")]
+    UnknownGamepadButtons(u32),
+    #[error("gamepad trigger values must be finite and within [0.0, 1.0]")]
+    InvalidTriggerRange,
+    #[error("gamepad stick axes must be finite and within [-1.0, 1.0]")]
+    InvalidStickRange,
+    #[error("mouse buttons {0:#x} contain unknown button bits")]
+    UnknownMouseButtons(u32),
+    #[error("mouse scroll deltas must be finite and within {MAX_SCROLL_DETENTS} detents")]
+    InvalidScrollDelta,
+    #[error("hovering mouse event cannot have buttons pressed")]
+    HoverWithButtons,
+    #[error("unknown display rotation value {0}")]
+    UnknownRotation(i32),
+    #[error("display size must be non-zero")]
+    EmptyDisplay,
+    #[error("content rect and its frame must be non-empty")]
+    EmptyContentRect,
+    #[error("content rect extends outside its frame")]
+    ContentOutsideFrame,
+    #[error("unknown keyframe reason value {0}")]
+    UnknownKeyframeReason(i32),
+    #[error("profile request must change at least one field")]
+    EmptyProfileRequest,
+    #[error("profile request must set width and height together")]
+    PartialResolution,
+    #[error("unknown codec value {0}")]
+    UnknownCodec(i32),
+    #[error("control frame is missing required field {0}")]
+    MissingField(&'static str),
+    #[error("control frame field {0} is out of range")]
+    FieldOutOfRange(&'static str),
+    #[error("compact touch frame is truncated")]
+    TruncatedCompactTouch,
+    #[error("compact touch frame has {0} unexpected trailing bytes")]
+    TrailingCompactTouchBytes(usize),
+    #[error("unknown compact touch frame kind {0:#04x}")]
+    UnknownCompactTouchKind(u8),
+    #[error("compact touch delta cannot be applied without a keyframe")]
+    MissingTouchKeyframe,
+    #[error(
+        "compact touch delta for frame {frame_id} arrived while frame {expected} was expected"
+    )]
+    CompactTouchGap { expected: u64, frame_id: u64 },
+    #[error("compact touch frame {frame_id} is not newer than applied frame {applied}")]
+    StaleCompactTouch { frame_id: u64, applied: u64 },
+    #[error("compact touch varint is longer than 64 bits")]
+    VarintOverflow,
     #[error("touch frame cannot be empty")]
     EmptyTouchFrame,
     #[error("touch frame exceeds max event count: {0}")]
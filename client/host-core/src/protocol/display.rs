@@ -0,0 +1,183 @@
+use serde::{Deserialize, Serialize};
+
+use crate::config::profile::{Codec, RuntimeProfile};
+use crate::geometry::Rotation;
+use crate::protocol::control::ControlFrame;
+
+/// Emulator content inside the encoded stream frame, in stream pixels. Before a
+/// profile is known the frame is the emulator display in its current orientation.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContentRect {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+    pub frame_width: u32,
+    pub frame_height: u32,
+}
+
+impl ContentRect {
+    pub fn letterboxed(
+        content_width: u32,
+        content_height: u32,
+        frame_width: u32,
+        frame_height: u32,
+    ) -> Self {
+        if content_width == 0 || content_height == 0 {
+            return Self {
+                frame_width,
+                frame_height,
+                ..Self::default()
+            };
+        }
+
+        let scale = (frame_width as f64 / content_width as f64)
+            .min(frame_height as f64 / content_height as f64);
+        let width = ((content_width as f64 * scale).round() as u32).min(frame_width);
+        let height = ((content_height as f64 * scale).round() as u32).min(frame_height);
+
+        Self {
+            x: (frame_width - width) / 2,
+            y: (frame_height - height) / 2,
+            width,
+            height,
+            frame_width,
+            frame_height,
+        }
+    }
+
+    pub fn is_within_frame(&self) -> bool {
+        u64::from(self.x) + u64::from(self.width) <= u64::from(self.frame_width)
+            && u64::from(self.y) + u64::from(self.height) <= u64::from(self.frame_height)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DisplayInfo {
+    pub width: u32,
+    pub height: u32,
+    pub rotation: Rotation,
+    pub content: ContentRect,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StreamProfile {
+    pub codec: Codec,
+    pub width: u16,
+    pub height: u16,
+    pub fps: u16,
+    pub target_bitrate_kbps: u32,
+    pub keyframe_interval_ms: u32,
+}
+
+impl From<&RuntimeProfile> for StreamProfile {
+    fn from(profile: &RuntimeProfile) -> Self {
+        Self {
+            codec: profile.codec,
+            width: profile.width,
+            height: profile.height,
+            fps: profile.fps,
+            target_bitrate_kbps: profile.target_bitrate_kbps,
+            keyframe_interval_ms: profile.keyframe_interval_ms,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct EmulatorDisplay {
+    width: u32,
+    height: u32,
+    rotation: Rotation,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct DisplayAnnouncer {
+    display: Option<EmulatorDisplay>,
+    profile: Option<StreamProfile>,
+}
+
+impl DisplayAnnouncer {
+    pub fn display_info(&self) -> Option<DisplayInfo> {
+        let display = self.display?;
+        Some(DisplayInfo {
+            width: display.width,
+            height: display.height,
+            rotation: display.rotation,
+            content: self.content_rect(display),
+        })
+    }
+
+    pub fn update_display(
+        &mut self,
+        width: u32,
+        height: u32,
+        rotation: Rotation,
+    ) -> Vec<ControlFrame> {
+        let next = EmulatorDisplay {
+            width,
+            height,
+            rotation,
+        };
+        let previous = self.display.replace(next);
+
+        match previous {
+            Some(previous) if previous == next => Vec::new(),
+            Some(previous) if previous.width == width && previous.height == height => {
+                vec![ControlFrame::OrientationChanged {
+                    rotation,
+                    content: self.content_rect(next),
+                }]
+            }
+            _ => self
+                .display_info()
+                .map(ControlFrame::DisplayInfo)
+                .into_iter()
+                .collect(),
+        }
+    }
+
+    pub fn update_rotation(&mut self, rotation: Rotation) -> Vec<ControlFrame> {
+        match self.display {
+            Some(display) => self.update_display(display.width, display.height, rotation),
+            None => Vec::new(),
+        }
+    }
+
+    pub fn update_profile(&mut self, profile: &RuntimeProfile) -> Vec<ControlFrame> {
+        let next = StreamProfile::from(profile);
+        let previous = self.profile.replace(next);
+        if previous == Some(next) {
+            return Vec::new();
+        }
+
+        let mut frames = vec![ControlFrame::SessionProfileChanged(next)];
+        let resized = previous
+            .is_none_or(|previous| (previous.width, previous.height) != (next.width, next.height));
+        if resized {
+            frames.extend(self.display_info().map(ControlFrame::DisplayInfo));
+        }
+        frames
+    }
+
+    pub fn snapshot(&self) -> Vec<ControlFrame> {
+        self.profile
+            .map(ControlFrame::SessionProfileChanged)
+            .into_iter()
+            .chain(self.display_info().map(ControlFrame::DisplayInfo))
+            .collect()
+    }
+
+    fn content_rect(&self, display: EmulatorDisplay) -> ContentRect {
+        let (width, height) = if display.rotation.swaps_axes() {
+            (display.height, display.width)
+        } else {
+            (display.width, display.height)
+        };
+
+        let (frame_width, frame_height) = match self.profile {
+            Some(profile) => (u32::from(profile.width), u32::from(profile.height)),
+            None => (width, height),
+        };
+        ContentRect::letterboxed(width, height, frame_width, frame_height)
+    }
+}
@@ -0,0 +1,329 @@
+use std::collections::{BTreeMap, VecDeque};
+
+use thiserror::Error;
+
+use crate::protocol::auth::AUTH_OVERHEAD;
+use crate::transport::webrtc::TransportDescriptor;
+
+pub const FRAGMENT_MAGIC: [u8; 2] = [0xF7, 0x01];
+pub const FRAGMENT_HEADER_LEN: usize = 10;
+pub const MAX_FRAGMENTS: u16 = 64;
+pub const DEFAULT_REASSEMBLY_TIMEOUT_MS: u64 = 500;
+pub const DEFAULT_MAX_PENDING_MESSAGES: usize = 16;
+pub const DEFAULT_MAX_BUFFERED_BYTES: usize = 256 * 1024;
+
+const COMPLETED_HISTORY: usize = 64;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FragmentHeader {
+    pub message_id: u32,
+    pub index: u16,
+    pub count: u16,
+}
+
+impl FragmentHeader {
+    pub fn parse(datagram: &[u8]) -> Option<Result<(Self, &[u8]), FragmentError>> {
+        if !is_fragment(datagram) {
+            return None;
+        }
+        let Some((header, body)) = datagram.split_first_chunk::<FRAGMENT_HEADER_LEN>() else {
+            return Some(Err(FragmentError::TruncatedHeader(datagram.len())));
+        };
+
+        let header = Self {
+            message_id: u32::from_be_bytes([header[2], header[3], header[4], header[5]]),
+            index: u16::from_be_bytes([header[6], header[7]]),
+            count: u16::from_be_bytes([header[8], header[9]]),
+        };
+        Some(header.validate().map(|()| (header, body)))
+    }
+
+    fn validate(&self) -> Result<(), FragmentError> {
+        if !(1..=MAX_FRAGMENTS).contains(&self.count) {
+            return Err(FragmentError::InvalidFragmentCount(self.count));
+        }
+        if self.index >= self.count {
+            return Err(FragmentError::InvalidFragmentIndex {
+                index: self.index,
+                count: self.count,
+            });
+        }
+        Ok(())
+    }
+
+    fn write(&self, datagram: &mut Vec<u8>) {
+        datagram.extend_from_slice(&FRAGMENT_MAGIC);
+        datagram.extend_from_slice(&self.message_id.to_be_bytes());
+        datagram.extend_from_slice(&self.index.to_be_bytes());
+        datagram.extend_from_slice(&self.count.to_be_bytes());
+    }
+}
+
+pub fn is_fragment(datagram: &[u8]) -> bool {
+    datagram.starts_with(&FRAGMENT_MAGIC)
+}
+
+#[derive(Debug, Clone)]
+pub struct Fragmenter {
+    max_datagram_size: usize,
+    next_message_id: u32,
+}
+
+impl Fragmenter {
+    pub fn new(max_datagram_size: u16) -> Result<Self, FragmentError> {
+        if usize::from(max_datagram_size) <= FRAGMENT_HEADER_LEN {
+            return Err(FragmentError::DatagramTooSmall(max_datagram_size));
+        }
+
+        Ok(Self {
+            max_datagram_size: usize::from(max_datagram_size),
+            next_message_id: 0,
+        })
+    }
+
+    pub fn for_transport(
+        transport: &TransportDescriptor,
+        authenticated: bool,
+    ) -> Result<Self, FragmentError> {
+        let overhead = if authenticated { AUTH_OVERHEAD } else { 0 };
+        Self::new(
+            transport
+                .max_datagram_size
+                .saturating_sub(u16::try_from(overhead).unwrap_or(u16::MAX)),
+        )
+    }
+
+    pub fn max_payload_len(&self) -> usize {
+        (self.max_datagram_size - FRAGMENT_HEADER_LEN) * usize::from(MAX_FRAGMENTS)
+    }
+
+    pub fn split(&mut self, payload: &[u8]) -> Result<Vec<Vec<u8>>, FragmentError> {
+        if payload.len() <= self.max_datagram_size && !is_fragment(payload) {
+            return Ok(vec![payload.to_vec()]);
+        }
+        if payload.len() > self.max_payload_len() {
+            return Err(FragmentError::PayloadTooLarge {
+                len: payload.len(),
+                max: self.max_payload_len(),
+            });
+        }
+
+        let chunk_len = self.max_datagram_size - FRAGMENT_HEADER_LEN;
+        let chunks = payload.chunks(chunk_len).collect::<Vec<_>>();
+        let count = u16::try_from(chunks.len()).unwrap_or(MAX_FRAGMENTS);
+        let message_id = self.next_message_id;
+        self.next_message_id = self.next_message_id.wrapping_add(1);
+
+        Ok(chunks
+            .into_iter()
+            .zip(0..)
+            .map(|(chunk, index)| {
+                let mut datagram = Vec::with_capacity(FRAGMENT_HEADER_LEN + chunk.len());
+                FragmentHeader {
+                    message_id,
+                    index,
+                    count,
+                }
+                .write(&mut datagram);
+                datagram.extend_from_slice(chunk);
+                datagram
+            })
+            .collect())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReassemblyConfig {
+    pub timeout_ms: u64,
+    pub max_pending_messages: usize,
+    pub max_buffered_bytes: usize,
+}
+
+impl Default for ReassemblyConfig {
+    fn default() -> Self {
+        Self {
+            timeout_ms: DEFAULT_REASSEMBLY_TIMEOUT_MS,
+            max_pending_messages: DEFAULT_MAX_PENDING_MESSAGES,
+            max_buffered_bytes: DEFAULT_MAX_BUFFERED_BYTES,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ReassemblyStats {
+    pub completed: u64,
+    pub expired: u64,
+    pub evicted: u64,
+    pub duplicates: u64,
+    pub rejected: u64,
+}
+
+#[derive(Debug, Clone, Default)]
+struct Partial {
+    fragments: Vec<Option<Vec<u8>>>,
+    received: u16,
+    bytes: usize,
+    first_ms: u64,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Reassembler {
+    config: ReassemblyConfig,
+    pending: BTreeMap<u32, Partial>,
+    completed: VecDeque<u32>,
+    buffered_bytes: usize,
+    stats: ReassemblyStats,
+}
+
+impl Reassembler {
+    pub fn new(config: ReassemblyConfig) -> Self {
+        Self {
+            config,
+            ..Self::default()
+        }
+    }
+
+    pub fn stats(&self) -> ReassemblyStats {
+        self.stats
+    }
+
+    pub fn pending(&self) -> usize {
+        self.pending.len()
+    }
+
+    pub fn buffered_bytes(&self) -> usize {
+        self.buffered_bytes
+    }
+
+    pub fn push(&mut self, datagram: &[u8], now_ms: u64) -> Result<Option<Vec<u8>>, FragmentError> {
+        self.expire(now_ms);
+
+        let (header, body) = match FragmentHeader::parse(datagram) {
+            None => return Ok(Some(datagram.to_vec())),
+            Some(Ok(parsed)) => parsed,
+            Some(Err(err)) => {
+                self.stats.rejected += 1;
+                return Err(err);
+            }
+        };
+        if body.len() > self.config.max_buffered_bytes {
+            self.stats.rejected += 1;
+            return Err(FragmentError::FragmentTooLarge(body.len()));
+        }
+        if self.completed.contains(&header.message_id) {
+            self.stats.duplicates += 1;
+            return Ok(None);
+        }
+
+        if let Some(partial) = self.pending.get(&header.message_id) {
+            if partial.fragments.len() != usize::from(header.count) {
+                self.drop_partial(header.message_id);
+                self.stats.rejected += 1;
+                return Err(FragmentError::InconsistentFragmentCount(header.message_id));
+            }
+            if partial.fragments[usize::from(header.index)].is_some() {
+                self.stats.duplicates += 1;
+                return Ok(None);
+            }
+        } else {
+            while self.pending.len() >= self.config.max_pending_messages.max(1) {
+                let Some(oldest) = self.oldest(None) else {
+                    break;
+                };
+                self.drop_partial(oldest);
+                self.stats.evicted += 1;
+            }
+        }
+
+        while self.buffered_bytes + body.len() > self.config.max_buffered_bytes {
+            let Some(oldest) = self.oldest(Some(header.message_id)) else {
+                self.drop_partial(header.message_id);
+                self.stats.evicted += 1;
+                return Ok(None);
+            };
+            self.drop_partial(oldest);
+            self.stats.evicted += 1;
+        }
+
+        let partial = self
+            .pending
+            .entry(header.message_id)
+            .or_insert_with(|| Partial {
+                fragments: vec![None; usize::from(header.count)],
+                received: 0,
+                bytes: 0,
+                first_ms: now_ms,
+            });
+        partial.fragments[usize::from(header.index)] = Some(body.to_vec());
+        partial.received += 1;
+        partial.bytes += body.len();
+        self.buffered_bytes += body.len();
+        if partial.received < header.count {
+            return Ok(None);
+        }
+
+        let partial = self.drop_partial(header.message_id).unwrap_or_default();
+        if self.completed.len() == COMPLETED_HISTORY {
+            self.completed.pop_front();
+        }
+        self.completed.push_back(header.message_id);
+        self.stats.completed += 1;
+        Ok(Some(
+            partial.fragments.into_iter().flatten().flatten().collect(),
+        ))
+    }
+
+    pub fn expire(&mut self, now_ms: u64) -> usize {
+        let expired = self
+            .pending
+            .iter()
+            .filter(|(_, partial)| {
+                now_ms.saturating_sub(partial.first_ms) >= self.config.timeout_ms
+            })
+            .map(|(&message_id, _)| message_id)
+            .collect::<Vec<_>>();
+        for &message_id in &expired {
+            self.drop_partial(message_id);
+        }
+        self.stats.expired += expired.len() as u64;
+        expired.len()
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::new(self.config);
+    }
+
+    fn oldest(&self, except: Option<u32>) -> Option<u32> {
+        self.pending
+            .iter()
+            .filter(|(&id, _)| Some(id) != except)
+            .min_by_key(|(&id, partial)| (partial.first_ms, id))
+            .map(|(&id, _)| id)
+    }
+
+    fn drop_partial(&mut self, message_id: u32) -> Option<Partial> {
+        let partial = self.pending.remove(&message_id)?;
+        self.buffered_bytes -= partial.bytes;
+        Some(partial)
+    }
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum FragmentError {
+    #[error(
+        "max datagram size {0} leaves no room after the {FRAGMENT_HEADER_LEN}-byte fragment header"
+    )]
+    DatagramTooSmall(u16),
+    #[error("payload of {len} bytes exceeds the fragmentation limit of {max} bytes")]
+    PayloadTooLarge { len: usize, max: usize },
+    #[error("fragment datagram of {0} bytes is shorter than the fragment header")]
+    TruncatedHeader(usize),
+    #[error("fragment count {0} must be between 1 and {MAX_FRAGMENTS}")]
+    InvalidFragmentCount(u16),
+    #[error("fragment index {index} is outside a set of {count} fragments")]
+    InvalidFragmentIndex { index: u16, count: u16 },
+    #[error("fragment set {0} changed its fragment count mid-message")]
+    InconsistentFragmentCount(u32),
+    #[error("fragment of {0} bytes exceeds the reassembly buffer")]
+    FragmentTooLarge(usize),
+}
@@ -0,0 +1,273 @@
+use std::fmt;
+
+use serde::{Deserialize, Deserializer, Serialize};
+use thiserror::Error;
+
+use crate::config::profile::{
+    nearest_presets, Codec, ProfileError, ProfileUpdate, RuntimeProfile, StreamLevel,
+};
+use crate::pipeline::HostCapability;
+
+pub const PROTOCOL_VERSION: ProtocolVersion = ProtocolVersion { major: 1, minor: 0 };
+pub const MAX_TOUCH_POINTERS: u8 = 10;
+
+pub const FEATURE_PROTOBUF_CODEC: &str = "protobuf_codec";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct ProtocolVersion {
+    pub major: u16,
+    pub minor: u16,
+}
+
+impl fmt::Display for ProtocolVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}", self.major, self.minor)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct InputCapabilities {
+    pub touch: bool,
+    pub max_pointers: u8,
+    pub keyboard: bool,
+    pub gamepad: bool,
+    pub mouse: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Hello {
+    pub protocol_version: ProtocolVersion,
+    pub app_version: String,
+    #[serde(deserialize_with = "known_codecs")]
+    pub codecs: Vec<Codec>,
+    pub max_decode_width: u16,
+    pub max_decode_height: u16,
+    pub max_refresh_hz: u16,
+    pub input: InputCapabilities,
+    #[serde(default)]
+    pub features: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Welcome {
+    pub protocol_version: ProtocolVersion,
+    pub codec: Codec,
+    pub width: u16,
+    pub height: u16,
+    pub fps: u16,
+    pub input: InputCapabilities,
+    #[serde(default)]
+    pub features: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_nonce: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Downgrade {
+    ProtocolVersion {
+        requested: ProtocolVersion,
+        agreed: ProtocolVersion,
+    },
+    Codec {
+        requested: Codec,
+        selected: Codec,
+    },
+    Stream {
+        requested: StreamLevel,
+        selected: StreamLevel,
+    },
+    Pointers {
+        requested: u8,
+        allowed: u8,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Negotiated {
+    pub welcome: Welcome,
+    pub profile: RuntimeProfile,
+    pub downgrades: Vec<Downgrade>,
+}
+
+#[derive(Debug, Clone)]
+pub struct HostOffer<'a> {
+    pub profile: &'a RuntimeProfile,
+    pub capability: &'a HostCapability,
+    pub features: &'a [&'a str],
+    pub session_nonce: Option<&'a str>,
+}
+
+impl HostOffer<'_> {
+    pub fn negotiate(&self, hello: &Hello) -> Result<Negotiated, HandshakeError> {
+        let mut downgrades = Vec::new();
+
+        let protocol_version = negotiate_version(hello.protocol_version)?;
+        if protocol_version != hello.protocol_version {
+            downgrades.push(Downgrade::ProtocolVersion {
+                requested: hello.protocol_version,
+                agreed: protocol_version,
+            });
+        }
+
+        if !(hello.input.touch || hello.input.keyboard || hello.input.gamepad || hello.input.mouse)
+        {
+            return Err(HandshakeError::NoInputCapability);
+        }
+
+        let codec = self
+            .capability
+            .negotiate_codec(&hello.codecs, Some(self.profile.codec))
+            .map_err(|_| HandshakeError::NoCommonCodec {
+                host: self.capability.codecs.clone(),
+                peer: hello.codecs.clone(),
+            })?;
+        if codec != self.profile.codec {
+            downgrades.push(Downgrade::Codec {
+                requested: self.profile.codec,
+                selected: codec,
+            });
+        }
+
+        let decode_long = hello.max_decode_width.max(hello.max_decode_height);
+        let decode_short = hello.max_decode_width.min(hello.max_decode_height);
+        let decoder = HostCapability {
+            max_width: self.capability.max_width.min(decode_long),
+            max_height: self.capability.max_height.min(decode_short),
+            max_fps: self.capability.max_fps.min(hello.max_refresh_hz),
+            codecs: vec![codec],
+        };
+
+        let mut update = ProfileUpdate {
+            codec: Some(codec),
+            ..ProfileUpdate::default()
+        };
+        let requested = self.profile.stream_level();
+        if requested.width > decoder.max_width
+            || requested.height > decoder.max_height
+            || requested.fps > decoder.max_fps
+        {
+            let candidate = nearest_presets(
+                requested.width,
+                requested.height,
+                requested.fps,
+                requested.target_bitrate_kbps,
+                codec,
+                Some(&decoder),
+            )
+            .into_iter()
+            .next()
+            .ok_or(HandshakeError::DecoderTooSmall {
+                max_width: hello.max_decode_width,
+                max_height: hello.max_decode_height,
+                max_refresh_hz: hello.max_refresh_hz,
+            })?;
+
+            update.resolution = Some((candidate.width, candidate.height));
+            update.fps = Some(candidate.fps);
+            update.target_bitrate_kbps = Some(candidate.target_bitrate_kbps);
+        }
+
+        let profile = update
+            .apply_to(self.profile)
+            .map_err(HandshakeError::Profile)?;
+        if profile.stream_level() != requested {
+            downgrades.push(Downgrade::Stream {
+                requested,
+                selected: profile.stream_level(),
+            });
+        }
+
+        let mut input = hello.input;
+        if input.touch {
+            if input.max_pointers == 0 {
+                return Err(HandshakeError::NoInputCapability);
+            }
+            if input.max_pointers > MAX_TOUCH_POINTERS {
+                downgrades.push(Downgrade::Pointers {
+                    requested: input.max_pointers,
+                    allowed: MAX_TOUCH_POINTERS,
+                });
+                input.max_pointers = MAX_TOUCH_POINTERS;
+            }
+        } else {
+            input.max_pointers = 0;
+        }
+
+        let features = hello
+            .features
+            .iter()
+            .filter(|feature| self.features.contains(&feature.as_str()))
+            .cloned()
+            .collect();
+
+        Ok(Negotiated {
+            welcome: Welcome {
+                protocol_version,
+                codec: profile.codec,
+                width: profile.width,
+                height: profile.height,
+                fps: profile.fps,
+                input,
+                features,
+                session_nonce: self.session_nonce.map(str::to_owned),
+            },
+            profile,
+            downgrades,
+        })
+    }
+}
+
+pub fn negotiate_version(peer: ProtocolVersion) -> Result<ProtocolVersion, HandshakeError> {
+    if peer.major != PROTOCOL_VERSION.major {
+        return Err(HandshakeError::IncompatibleVersion {
+            host: PROTOCOL_VERSION,
+            peer,
+        });
+    }
+
+    Ok(PROTOCOL_VERSION.min(ProtocolVersion {
+        major: PROTOCOL_VERSION.major,
+        minor: peer.minor,
+    }))
+}
+
+fn known_codecs<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<Codec>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Advertised {
+        Known(Codec),
+        Unknown(serde::de::IgnoredAny),
+    }
+
+    Ok(Vec::<Advertised>::deserialize(deserializer)?
+        .into_iter()
+        .filter_map(|codec| match codec {
+            Advertised::Known(codec) => Some(codec),
+            Advertised::Unknown(_) => None,
+        })
+        .collect())
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum HandshakeError {
+    #[error("protocol version {peer} is incompatible with host version {host}")]
+    IncompatibleVersion {
+        host: ProtocolVersion,
+        peer: ProtocolVersion,
+    },
+    #[error("no common codec: host supports {host:?}, peer decodes {peer:?}")]
+    NoCommonCodec { host: Vec<Codec>, peer: Vec<Codec> },
+    #[error(
+        "peer decoder limit {max_width}x{max_height}@{max_refresh_hz} is below every supported preset"
+    )]
+    DecoderTooSmall {
+        max_width: u16,
+        max_height: u16,
+        max_refresh_hz: u16,
+    },
+    #[error("peer did not advertise any usable input capability")]
+    NoInputCapability,
+    #[error("negotiated profile is invalid: {0}")]
+    Profile(ProfileError),
+}
@@ -0,0 +1,328 @@
+use thiserror::Error;
+
+use crate::protocol::control::{is_unit, PointerAction, PointerEvent, Pong};
+
+pub const DISCOVERY_PORT: u16 = 42042;
+pub const CONTROL_PORT: u16 = 42043;
+pub const HOST_TOUCH_PORT: u16 = 42044;
+
+const SEPARATOR: char = '|';
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LanMessage {
+    Discover {
+        desktop_name: String,
+        reply_port: u16,
+    },
+    Device {
+        model: String,
+        version: String,
+        control_port: u16,
+    },
+    ConnectRequest {
+        desktop_name: String,
+        touch_port: u16,
+        session_nonce: Option<String>,
+    },
+    ConnectAccept {
+        model: String,
+    },
+    ConnectReject {
+        reason: String,
+    },
+    Disconnect {
+        origin: String,
+    },
+    Disconnected {
+        reason: String,
+    },
+    Ping {
+        timestamp_ms: Option<u64>,
+    },
+    Pong {
+        timestamps: Option<Pong>,
+    },
+    Touch {
+        frame_id: u64,
+        event: PointerEvent,
+    },
+}
+
+impl LanMessage {
+    pub fn tag(&self) -> &'static str {
+        match self {
+            LanMessage::Discover { .. } => "LMC_DISCOVER",
+            LanMessage::Device { .. } => "LMC_DEVICE",
+            LanMessage::ConnectRequest { .. } => "LMC_CONNECT_REQUEST",
+            LanMessage::ConnectAccept { .. } => "LMC_CONNECT_ACCEPT",
+            LanMessage::ConnectReject { .. } => "LMC_CONNECT_REJECT",
+            LanMessage::Disconnect { .. } => "LMC_DISCONNECT",
+            LanMessage::Disconnected { .. } => "LMC_DISCONNECTED",
+            LanMessage::Ping { .. } => "LMC_PING",
+            LanMessage::Pong { .. } => "LMC_PONG",
+            LanMessage::Touch { .. } => "LMC_TOUCH",
+        }
+    }
+
+    pub fn encode(&self) -> Result<String, LanCodecError> {
+        let fields = match self {
+            LanMessage::Discover {
+                desktop_name,
+                reply_port,
+            } => vec![
+                text_field("desktop_name", desktop_name)?,
+                reply_port.to_string(),
+            ],
+            LanMessage::Device {
+                model,
+                version,
+                control_port,
+            } => vec![
+                text_field("model", model)?,
+                text_field("version", version)?,
+                control_port.to_string(),
+            ],
+            LanMessage::ConnectRequest {
+                desktop_name,
+                touch_port,
+                session_nonce,
+            } => {
+                let mut fields = vec![
+                    text_field("desktop_name", desktop_name)?,
+                    touch_port.to_string(),
+                ];
+                if let Some(nonce) = session_nonce {
+                    fields.push(text_field("session_nonce", nonce)?);
+                }
+                fields
+            }
+            LanMessage::ConnectAccept { model } => vec![text_field("model", model)?],
+            LanMessage::ConnectReject { reason } => vec![text_field("reason", reason)?],
+            LanMessage::Disconnect { origin } => vec![text_field("origin", origin)?],
+            LanMessage::Disconnected { reason } => vec![text_field("reason", reason)?],
+            LanMessage::Ping { timestamp_ms } => timestamp_ms.iter().map(u64::to_string).collect(),
+            LanMessage::Pong { timestamps } => timestamps
+                .iter()
+                .flat_map(|pong| {
+                    [
+                        pong.origin_timestamp_ms,
+                        pong.receive_timestamp_ms,
+                        pong.transmit_timestamp_ms,
+                    ]
+                })
+                .map(|timestamp| timestamp.to_string())
+                .collect(),
+            LanMessage::Touch { frame_id, event } => vec![
+                frame_id.to_string(),
+                event.pointer_id.to_string(),
+                action_name(event.action).to_string(),
+                format!("{:.5}", event.x),
+                format!("{:.5}", event.y),
+                format!("{:.4}", event.pressure),
+                event.timestamp_ms.to_string(),
+            ],
+        };
+
+        let mut message = self.tag().to_string();
+        for field in fields {
+            message.push(SEPARATOR);
+            message.push_str(&field);
+        }
+        Ok(message)
+    }
+
+    pub fn decode(message: &str) -> Result<Self, LanCodecError> {
+        let message = message.trim();
+        if message.is_empty() {
+            return Err(LanCodecError::EmptyMessage);
+        }
+
+        let mut parts = message.split(SEPARATOR);
+        let tag = parts.next().unwrap_or_default();
+        let fields = Fields {
+            tag,
+            values: parts.collect(),
+        };
+
+        match tag {
+            "LMC_DISCOVER" => {
+                fields.expect_len(2)?;
+                Ok(LanMessage::Discover {
+                    desktop_name: fields.values[0].to_string(),
+                    reply_port: fields.parse(1, "reply_port")?,
+                })
+            }
+            "LMC_DEVICE" => {
+                fields.expect_len(3)?;
+                Ok(LanMessage::Device {
+                    model: fields.values[0].to_string(),
+                    version: fields.values[1].to_string(),
+                    control_port: fields.parse(2, "control_port")?,
+                })
+            }
+            "LMC_CONNECT_REQUEST" => {
+                fields.expect_len(2)?;
+                Ok(LanMessage::ConnectRequest {
+                    desktop_name: fields.values[0].to_string(),
+                    touch_port: fields.parse(1, "touch_port")?,
+                    session_nonce: fields
+                        .values
+                        .get(2)
+                        .filter(|nonce| !nonce.is_empty())
+                        .map(ToString::to_string),
+                })
+            }
+            "LMC_CONNECT_ACCEPT" => {
+                fields.expect_len(1)?;
+                Ok(LanMessage::ConnectAccept {
+                    model: fields.values[0].to_string(),
+                })
+            }
+            "LMC_CONNECT_REJECT" => {
+                fields.expect_len(1)?;
+                Ok(LanMessage::ConnectReject {
+                    reason: fields.values[0].to_string(),
+                })
+            }
+            "LMC_DISCONNECT" => {
+                fields.expect_len(1)?;
+                Ok(LanMessage::Disconnect {
+                    origin: fields.values[0].to_string(),
+                })
+            }
+            "LMC_DISCONNECTED" => {
+                fields.expect_len(1)?;
+                Ok(LanMessage::Disconnected {
+                    reason: fields.values[0].to_string(),
+                })
+            }
+            "LMC_PING" => Ok(LanMessage::Ping {
+                timestamp_ms: match fields.values.first() {
+                    Some(value) if !value.is_empty() => Some(fields.parse(0, "timestamp_ms")?),
+                    _ => None,
+                },
+            }),
+            "LMC_PONG" => {
+                if fields.values.first().is_none_or(|value| value.is_empty()) {
+                    return Ok(LanMessage::Pong { timestamps: None });
+                }
+                fields.expect_len(3)?;
+                Ok(LanMessage::Pong {
+                    timestamps: Some(Pong {
+                        origin_timestamp_ms: fields.parse(0, "origin_timestamp_ms")?,
+                        receive_timestamp_ms: fields.parse(1, "receive_timestamp_ms")?,
+                        transmit_timestamp_ms: fields.parse(2, "transmit_timestamp_ms")?,
+                    }),
+                })
+            }
+            "LMC_TOUCH" => {
+                fields.expect_len(7)?;
+                Ok(LanMessage::Touch {
+                    frame_id: fields.parse(0, "frame_id")?,
+                    event: PointerEvent {
+                        pointer_id: fields.parse(1, "pointer_id")?,
+                        action: parse_action(fields.values[2])
+                            .ok_or_else(|| fields.invalid("action", 2))?,
+                        x: fields.parse_unit(3, "x")?,
+                        y: fields.parse_unit(4, "y")?,
+                        pressure: fields.parse_unit(5, "pressure")?,
+                        timestamp_ms: fields.parse(6, "timestamp_ms")?,
+                    },
+                })
+            }
+            other => Err(LanCodecError::UnknownTag(other.to_string())),
+        }
+    }
+}
+
+struct Fields<'a> {
+    tag: &'a str,
+    values: Vec<&'a str>,
+}
+
+impl Fields<'_> {
+    fn expect_len(&self, expected: usize) -> Result<(), LanCodecError> {
+        if self.values.len() < expected {
+            return Err(LanCodecError::FieldCount {
+                tag: self.tag.to_string(),
+                expected,
+                actual: self.values.len(),
+            });
+        }
+        Ok(())
+    }
+
+    fn parse<T: std::str::FromStr>(
+        &self,
+        index: usize,
+        field: &'static str,
+    ) -> Result<T, LanCodecError> {
+        self.values[index]
+            .parse::<T>()
+            .map_err(|_| self.invalid(field, index))
+    }
+
+    fn parse_unit(&self, index: usize, field: &'static str) -> Result<f32, LanCodecError> {
+        let value = self.parse::<f32>(index, field)?;
+        if !is_unit(value) {
+            return Err(self.invalid(field, index));
+        }
+        Ok(value)
+    }
+
+    fn invalid(&self, field: &'static str, index: usize) -> LanCodecError {
+        LanCodecError::InvalidField {
+            tag: self.tag.to_string(),
+            field,
+            value: self.values[index].to_string(),
+        }
+    }
+}
+
+fn text_field(field: &'static str, value: &str) -> Result<String, LanCodecError> {
+    if value.contains(SEPARATOR) {
+        return Err(LanCodecError::ReservedSeparator(field));
+    }
+    Ok(value.to_string())
+}
+
+fn action_name(action: PointerAction) -> &'static str {
+    match action {
+        PointerAction::Down => "DOWN",
+        PointerAction::Move => "MOVE",
+        PointerAction::Up => "UP",
+        PointerAction::Cancel => "CANCEL",
+    }
+}
+
+fn parse_action(value: &str) -> Option<PointerAction> {
+    match value {
+        "DOWN" => Some(PointerAction::Down),
+        "MOVE" => Some(PointerAction::Move),
+        "UP" => Some(PointerAction::Up),
+        "CANCEL" => Some(PointerAction::Cancel),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum LanCodecError {
+    #[error("LAN message is empty")]
+    EmptyMessage,
+    #[error("unknown LAN message tag: {0}")]
+    UnknownTag(String),
+    #[error("{tag} expects at least {expected} fields, got {actual}")]
+    FieldCount {
+        tag: String,
+        expected: usize,
+        actual: usize,
+    },
+    #[error("{tag} has invalid {field} value {value:?}")]
+    InvalidField {
+        tag: String,
+        field: &'static str,
+        value: String,
+    },
+    #[error("{0} must not contain the '|' separator")]
+    ReservedSeparator(&'static str),
+}
@@ -0,0 +1,191 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::protocol::control::{PointerAction, PointerEvent, TouchEnvelope};
+use crate::protocol::handshake::MAX_TOUCH_POINTERS;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LifecyclePolicy {
+    #[default]
+    Reject,
+    Repair,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LifecycleStats {
+    pub rejected_frames: u64,
+    pub dropped_events: u64,
+    pub synthesized_releases: u64,
+}
+
+#[derive(Debug, Clone)]
+pub struct PointerValidator {
+    policy: LifecyclePolicy,
+    max_pointers: u8,
+    active: BTreeMap<u8, PointerEvent>,
+    stats: LifecycleStats,
+}
+
+impl Default for PointerValidator {
+    fn default() -> Self {
+        Self::new(LifecyclePolicy::default(), MAX_TOUCH_POINTERS)
+    }
+}
+
+impl PointerValidator {
+    pub fn new(policy: LifecyclePolicy, max_pointers: u8) -> Self {
+        Self {
+            policy,
+            max_pointers: max_pointers.min(MAX_TOUCH_POINTERS),
+            active: BTreeMap::new(),
+            stats: LifecycleStats::default(),
+        }
+    }
+
+    pub fn policy(&self) -> LifecyclePolicy {
+        self.policy
+    }
+
+    pub fn max_pointers(&self) -> u8 {
+        self.max_pointers
+    }
+
+    pub fn stats(&self) -> LifecycleStats {
+        self.stats
+    }
+
+    pub fn active_pointers(&self) -> Vec<u8> {
+        self.active.keys().copied().collect()
+    }
+
+    pub fn is_active(&self, pointer_id: u8) -> bool {
+        self.active.contains_key(&pointer_id)
+    }
+
+    pub fn apply(
+        &mut self,
+        envelope: TouchEnvelope,
+    ) -> Result<TouchEnvelope, PointerLifecycleError> {
+        match self.policy {
+            LifecyclePolicy::Reject => self.check(envelope),
+            LifecyclePolicy::Repair => Ok(self.repair(envelope)),
+        }
+    }
+
+    pub fn check(
+        &mut self,
+        envelope: TouchEnvelope,
+    ) -> Result<TouchEnvelope, PointerLifecycleError> {
+        let mut active = self.active.clone();
+        for event in &envelope.events {
+            let result = match event.action {
+                PointerAction::Down if active.contains_key(&event.pointer_id) => {
+                    Err(PointerLifecycleError::DuplicateDown(event.pointer_id))
+                }
+                PointerAction::Down if active.len() >= usize::from(self.max_pointers) => {
+                    Err(PointerLifecycleError::TooManyPointers {
+                        pointer_id: event.pointer_id,
+                        max: self.max_pointers,
+                    })
+                }
+                PointerAction::Down => {
+                    active.insert(event.pointer_id, event.clone());
+                    Ok(())
+                }
+                PointerAction::Move => match active.get_mut(&event.pointer_id) {
+                    Some(last) => {
+                        *last = event.clone();
+                        Ok(())
+                    }
+                    None => Err(PointerLifecycleError::MoveWithoutDown(event.pointer_id)),
+                },
+                PointerAction::Up | PointerAction::Cancel => active
+                    .remove(&event.pointer_id)
+                    .map(|_| ())
+                    .ok_or(PointerLifecycleError::ReleaseWithoutDown(event.pointer_id)),
+            };
+
+            if let Err(err) = result {
+                self.stats.rejected_frames += 1;
+                return Err(err);
+            }
+        }
+
+        self.active = active;
+        Ok(envelope)
+    }
+
+    pub fn repair(&mut self, envelope: TouchEnvelope) -> TouchEnvelope {
+        let mut events = Vec::with_capacity(envelope.events.len());
+        for event in envelope.events {
+            let id = event.pointer_id;
+            match event.action {
+                PointerAction::Down => {
+                    if let Some(stale) = self.active.remove(&id) {
+                        events.push(self.release(stale));
+                    }
+                    if self.active.len() >= usize::from(self.max_pointers) {
+                        self.stats.dropped_events += 1;
+                        continue;
+                    }
+                    self.active.insert(id, event.clone());
+                    events.push(event);
+                }
+                PointerAction::Move => match self.active.get_mut(&id) {
+                    Some(last) => {
+                        *last = event.clone();
+                        events.push(event);
+                    }
+                    None => self.stats.dropped_events += 1,
+                },
+                PointerAction::Up | PointerAction::Cancel => {
+                    if self.active.remove(&id).is_some() {
+                        events.push(event);
+                    } else {
+                        self.stats.dropped_events += 1;
+                    }
+                }
+            }
+        }
+
+        TouchEnvelope {
+            frame_id: envelope.frame_id,
+            events,
+        }
+    }
+
+    pub fn release_all(&mut self) -> Vec<PointerEvent> {
+        std::mem::take(&mut self.active)
+            .into_values()
+            .map(|last| self.release(last))
+            .collect()
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::new(self.policy, self.max_pointers);
+    }
+
+    fn release(&mut self, last: PointerEvent) -> PointerEvent {
+        self.stats.synthesized_releases += 1;
+        PointerEvent {
+            action: PointerAction::Cancel,
+            pressure: 0.0,
+            ..last
+        }
+    }
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum PointerLifecycleError {
+    #[error("pointer {0} moved without going down")]
+    MoveWithoutDown(u8),
+    #[error("pointer {0} was released without going down")]
+    ReleaseWithoutDown(u8),
+    #[error("pointer {0} went down while already active")]
+    DuplicateDown(u8),
+    #[error("pointer {pointer_id} exceeds the limit of {max} active pointers")]
+    TooManyPointers { pointer_id: u8, max: u8 },
+}
@@ -1 +1,10 @@
+pub mod auth;
+pub mod clock;
 pub mod control;
+pub mod display;
+pub mod fragment;
+pub mod handshake;
+pub mod lan;
+pub mod lifecycle;
+mod proto;
+pub mod sequence;
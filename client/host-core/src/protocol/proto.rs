@@ -0,0 +1,558 @@
+use crate::config::profile::Codec;
+use crate::geometry::Rotation;
+use crate::protocol::control::{self, ControlCodecError};
+use crate::protocol::{display, handshake};
+
+include!(concat!(env!("OUT_DIR"), "/linkmycomputer.control.rs"));
+
+impl From<control::PointerAction> for PointerAction {
+    fn from(action: control::PointerAction) -> Self {
+        match action {
+            control::PointerAction::Down => PointerAction::Down,
+            control::PointerAction::Move => PointerAction::Move,
+            control::PointerAction::Up => PointerAction::Up,
+            control::PointerAction::Cancel => PointerAction::Cancel,
+        }
+    }
+}
+
+impl From<control::KeyAction> for KeyAction {
+    fn from(action: control::KeyAction) -> Self {
+        match action {
+            control::KeyAction::Down => KeyAction::Down,
+            control::KeyAction::Up => KeyAction::Up,
+        }
+    }
+}
+
+impl From<control::SystemKey> for SystemKey {
+    fn from(key: control::SystemKey) -> Self {
+        match key {
+            control::SystemKey::Back => SystemKey::Back,
+            control::SystemKey::Home => SystemKey::Home,
+            control::SystemKey::Recents => SystemKey::Recents,
+            control::SystemKey::VolumeUp => SystemKey::VolumeUp,
+            control::SystemKey::VolumeDown => SystemKey::VolumeDown,
+            control::SystemKey::VolumeMute => SystemKey::VolumeMute,
+        }
+    }
+}
+
+impl From<control::AnalogStick> for AnalogStick {
+    fn from(stick: control::AnalogStick) -> Self {
+        AnalogStick {
+            x: stick.x,
+            y: stick.y,
+        }
+    }
+}
+
+impl From<AnalogStick> for control::AnalogStick {
+    fn from(stick: AnalogStick) -> Self {
+        control::AnalogStick {
+            x: stick.x,
+            y: stick.y,
+        }
+    }
+}
+
+impl From<Codec> for VideoCodec {
+    fn from(codec: Codec) -> Self {
+        match codec {
+            Codec::H264 => VideoCodec::H264,
+            Codec::Hevc => VideoCodec::Hevc,
+            Codec::Av1 => VideoCodec::Av1,
+        }
+    }
+}
+
+impl From<control::KeyframeReason> for KeyframeReason {
+    fn from(reason: control::KeyframeReason) -> Self {
+        match reason {
+            control::KeyframeReason::DecodeError => KeyframeReason::DecodeError,
+            control::KeyframeReason::PacketLoss => KeyframeReason::PacketLoss,
+            control::KeyframeReason::StreamStart => KeyframeReason::StreamStart,
+        }
+    }
+}
+
+impl From<Rotation> for DisplayRotation {
+    fn from(rotation: Rotation) -> Self {
+        match rotation {
+            Rotation::Deg0 => DisplayRotation::DisplayRotation0,
+            Rotation::Deg90 => DisplayRotation::DisplayRotation90,
+            Rotation::Deg180 => DisplayRotation::DisplayRotation180,
+            Rotation::Deg270 => DisplayRotation::DisplayRotation270,
+        }
+    }
+}
+
+impl From<display::ContentRect> for ContentRect {
+    fn from(rect: display::ContentRect) -> Self {
+        ContentRect {
+            x: rect.x,
+            y: rect.y,
+            width: rect.width,
+            height: rect.height,
+            frame_width: rect.frame_width,
+            frame_height: rect.frame_height,
+        }
+    }
+}
+
+impl From<ContentRect> for display::ContentRect {
+    fn from(rect: ContentRect) -> Self {
+        display::ContentRect {
+            x: rect.x,
+            y: rect.y,
+            width: rect.width,
+            height: rect.height,
+            frame_width: rect.frame_width,
+            frame_height: rect.frame_height,
+        }
+    }
+}
+
+impl From<handshake::ProtocolVersion> for ProtocolVersion {
+    fn from(version: handshake::ProtocolVersion) -> Self {
+        ProtocolVersion {
+            major: u32::from(version.major),
+            minor: u32::from(version.minor),
+        }
+    }
+}
+
+impl From<handshake::InputCapabilities> for InputCapabilities {
+    fn from(input: handshake::InputCapabilities) -> Self {
+        InputCapabilities {
+            touch: input.touch,
+            max_pointers: u32::from(input.max_pointers),
+            keyboard: input.keyboard,
+            gamepad: input.gamepad,
+            mouse: input.mouse,
+        }
+    }
+}
+
+impl From<&control::ControlFrame> for ControlFrame {
+    fn from(frame: &control::ControlFrame) -> Self {
+        let payload = match frame {
+            control::ControlFrame::Touch(touch) => control_frame::Payload::Touch(TouchEnvelope {
+                frame_id: touch.frame_id,
+                events: touch
+                    .events
+                    .iter()
+                    .map(|event| PointerEvent {
+                        pointer_id: u32::from(event.pointer_id),
+                        action: PointerAction::from(event.action).into(),
+                        x: event.x,
+                        y: event.y,
+                        pressure: event.pressure,
+                        timestamp_ms: event.timestamp_ms,
+                    })
+                    .collect(),
+            }),
+            control::ControlFrame::Ping { timestamp_ms } => control_frame::Payload::Ping(Ping {
+                timestamp_ms: *timestamp_ms,
+            }),
+            control::ControlFrame::Pong(pong) => control_frame::Payload::Pong(Pong {
+                origin_timestamp_ms: pong.origin_timestamp_ms,
+                receive_timestamp_ms: pong.receive_timestamp_ms,
+                transmit_timestamp_ms: pong.transmit_timestamp_ms,
+            }),
+            control::ControlFrame::Hello(hello) => control_frame::Payload::Hello(Hello {
+                protocol_version: Some(hello.protocol_version.into()),
+                app_version: hello.app_version.clone(),
+                codecs: hello
+                    .codecs
+                    .iter()
+                    .map(|&codec| VideoCodec::from(codec).into())
+                    .collect(),
+                max_decode_width: u32::from(hello.max_decode_width),
+                max_decode_height: u32::from(hello.max_decode_height),
+                max_refresh_hz: u32::from(hello.max_refresh_hz),
+                input: Some(hello.input.into()),
+                features: hello.features.clone(),
+            }),
+            control::ControlFrame::Welcome(welcome) => control_frame::Payload::Welcome(Welcome {
+                protocol_version: Some(welcome.protocol_version.into()),
+                codec: VideoCodec::from(welcome.codec).into(),
+                width: u32::from(welcome.width),
+                height: u32::from(welcome.height),
+                fps: u32::from(welcome.fps),
+                input: Some(welcome.input.into()),
+                features: welcome.features.clone(),
+                session_nonce: welcome.session_nonce.clone(),
+            }),
+            control::ControlFrame::Key(key) => control_frame::Payload::Key(KeyEvent {
+                keycode: key.keycode,
+                action: KeyAction::from(key.action).into(),
+                meta_state: key.meta_state,
+                timestamp_ms: key.timestamp_ms,
+            }),
+            control::ControlFrame::Text { text } => {
+                control_frame::Payload::Text(TextInput { text: text.clone() })
+            }
+            control::ControlFrame::SystemKey(key) => {
+                control_frame::Payload::SystemKey(SystemKeyPress {
+                    key: SystemKey::from(*key).into(),
+                })
+            }
+            control::ControlFrame::Gamepad(state) => {
+                control_frame::Payload::Gamepad(GamepadState {
+                    buttons: state.buttons,
+                    left_trigger: state.left_trigger,
+                    right_trigger: state.right_trigger,
+                    left_stick: Some(state.left_stick.into()),
+                    right_stick: Some(state.right_stick.into()),
+                    timestamp_ms: state.timestamp_ms,
+                })
+            }
+            control::ControlFrame::Mouse(mouse) => control_frame::Payload::Mouse(MouseEvent {
+                motion: Some(match mouse.motion {
+                    control::MouseMotion::Absolute { x, y } => {
+                        mouse_event::Motion::Absolute(AbsolutePosition { x, y })
+                    }
+                    control::MouseMotion::Relative { dx, dy } => {
+                        mouse_event::Motion::Relative(RelativeMotion { dx, dy })
+                    }
+                }),
+                buttons: mouse.buttons,
+                scroll_x: mouse.scroll_x,
+                scroll_y: mouse.scroll_y,
+                hover: mouse.hover,
+                timestamp_ms: mouse.timestamp_ms,
+            }),
+            control::ControlFrame::DisplayInfo(info) => {
+                control_frame::Payload::DisplayInfo(DisplayInfo {
+                    width: info.width,
+                    height: info.height,
+                    rotation: DisplayRotation::from(info.rotation).into(),
+                    content: Some(info.content.into()),
+                })
+            }
+            control::ControlFrame::OrientationChanged { rotation, content } => {
+                control_frame::Payload::OrientationChanged(OrientationChanged {
+                    rotation: DisplayRotation::from(*rotation).into(),
+                    content: Some((*content).into()),
+                })
+            }
+            control::ControlFrame::SessionProfileChanged(profile) => {
+                control_frame::Payload::SessionProfileChanged(StreamProfile {
+                    codec: VideoCodec::from(profile.codec).into(),
+                    width: u32::from(profile.width),
+                    height: u32::from(profile.height),
+                    fps: u32::from(profile.fps),
+                    target_bitrate_kbps: profile.target_bitrate_kbps,
+                    keyframe_interval_ms: profile.keyframe_interval_ms,
+                })
+            }
+            control::ControlFrame::RequestKeyframe {
+                reason,
+                timestamp_ms,
+            } => control_frame::Payload::KeyframeRequest(KeyframeRequest {
+                reason: KeyframeReason::from(*reason).into(),
+                timestamp_ms: *timestamp_ms,
+            }),
+            control::ControlFrame::PauseStream { timestamp_ms } => {
+                control_frame::Payload::PauseStream(StreamPause {
+                    timestamp_ms: *timestamp_ms,
+                })
+            }
+            control::ControlFrame::ResumeStream { timestamp_ms } => {
+                control_frame::Payload::ResumeStream(StreamResume {
+                    timestamp_ms: *timestamp_ms,
+                })
+            }
+            control::ControlFrame::RequestProfile(request) => {
+                control_frame::Payload::ProfileRequest(ProfileRequest {
+                    codec: request.codec.map(|codec| VideoCodec::from(codec).into()),
+                    width: request.width.map(u32::from),
+                    height: request.height.map(u32::from),
+                    fps: request.fps.map(u32::from),
+                    target_bitrate_kbps: request.target_bitrate_kbps,
+                    keyframe_interval_ms: request.keyframe_interval_ms,
+                })
+            }
+            control::ControlFrame::RequestTouchKeyframe { last_frame_id } => {
+                control_frame::Payload::TouchKeyframeRequest(TouchKeyframeRequest {
+                    last_frame_id: *last_frame_id,
+                })
+            }
+        };
+
+        ControlFrame {
+            payload: Some(payload),
+        }
+    }
+}
+
+impl TryFrom<PointerEvent> for control::PointerEvent {
+    type Error = ControlCodecError;
+
+    fn try_from(event: PointerEvent) -> Result<Self, Self::Error> {
+        let pointer_id = u8::try_from(event.pointer_id)
+            .map_err(|_| ControlCodecError::InvalidPointerId(event.pointer_id))?;
+        let action = match PointerAction::try_from(event.action) {
+            Ok(PointerAction::Down) => control::PointerAction::Down,
+            Ok(PointerAction::Move) => control::PointerAction::Move,
+            Ok(PointerAction::Up) => control::PointerAction::Up,
+            Ok(PointerAction::Cancel) => control::PointerAction::Cancel,
+            Ok(PointerAction::Unspecified) | Err(_) => {
+                return Err(ControlCodecError::UnknownPointerAction(event.action))
+            }
+        };
+
+        Ok(control::PointerEvent {
+            pointer_id,
+            action,
+            x: event.x,
+            y: event.y,
+            pressure: event.pressure,
+            timestamp_ms: event.timestamp_ms,
+        })
+    }
+}
+
+impl TryFrom<ProtocolVersion> for handshake::ProtocolVersion {
+    type Error = ControlCodecError;
+
+    fn try_from(version: ProtocolVersion) -> Result<Self, Self::Error> {
+        Ok(handshake::ProtocolVersion {
+            major: narrow(version.major, "protocol_version.major")?,
+            minor: narrow(version.minor, "protocol_version.minor")?,
+        })
+    }
+}
+
+impl TryFrom<InputCapabilities> for handshake::InputCapabilities {
+    type Error = ControlCodecError;
+
+    fn try_from(input: InputCapabilities) -> Result<Self, Self::Error> {
+        Ok(handshake::InputCapabilities {
+            touch: input.touch,
+            max_pointers: narrow(input.max_pointers, "input.max_pointers")?,
+            keyboard: input.keyboard,
+            gamepad: input.gamepad,
+            mouse: input.mouse,
+        })
+    }
+}
+
+impl TryFrom<ControlFrame> for control::ControlFrame {
+    type Error = ControlCodecError;
+
+    fn try_from(frame: ControlFrame) -> Result<Self, Self::Error> {
+        match frame.payload.ok_or(ControlCodecError::MissingPayload)? {
+            control_frame::Payload::Touch(touch) => {
+                Ok(control::ControlFrame::Touch(control::TouchEnvelope {
+                    frame_id: touch.frame_id,
+                    events: touch
+                        .events
+                        .into_iter()
+                        .map(control::PointerEvent::try_from)
+                        .collect::<Result<_, _>>()?,
+                }))
+            }
+            control_frame::Payload::Ping(ping) => Ok(control::ControlFrame::Ping {
+                timestamp_ms: ping.timestamp_ms,
+            }),
+            control_frame::Payload::Pong(pong) => Ok(control::ControlFrame::Pong(control::Pong {
+                origin_timestamp_ms: pong.origin_timestamp_ms,
+                receive_timestamp_ms: pong.receive_timestamp_ms,
+                transmit_timestamp_ms: pong.transmit_timestamp_ms,
+            })),
+            control_frame::Payload::Hello(hello) => {
+                Ok(control::ControlFrame::Hello(handshake::Hello {
+                    protocol_version: required(hello.protocol_version, "hello.protocol_version")?
+                        .try_into()?,
+                    app_version: hello.app_version,
+                    codecs: hello
+                        .codecs
+                        .into_iter()
+                        .filter_map(|value| codec_from_wire(value).ok())
+                        .collect(),
+                    max_decode_width: narrow(hello.max_decode_width, "hello.max_decode_width")?,
+                    max_decode_height: narrow(hello.max_decode_height, "hello.max_decode_height")?,
+                    max_refresh_hz: narrow(hello.max_refresh_hz, "hello.max_refresh_hz")?,
+                    input: required(hello.input, "hello.input")?.try_into()?,
+                    features: hello.features,
+                }))
+            }
+            control_frame::Payload::Welcome(welcome) => {
+                Ok(control::ControlFrame::Welcome(handshake::Welcome {
+                    protocol_version: required(
+                        welcome.protocol_version,
+                        "welcome.protocol_version",
+                    )?
+                    .try_into()?,
+                    codec: codec_from_wire(welcome.codec)?,
+                    width: narrow(welcome.width, "welcome.width")?,
+                    height: narrow(welcome.height, "welcome.height")?,
+                    fps: narrow(welcome.fps, "welcome.fps")?,
+                    input: required(welcome.input, "welcome.input")?.try_into()?,
+                    features: welcome.features,
+                    session_nonce: welcome.session_nonce,
+                }))
+            }
+            control_frame::Payload::Key(key) => Ok(control::ControlFrame::Key(control::KeyEvent {
+                keycode: key.keycode,
+                action: match KeyAction::try_from(key.action) {
+                    Ok(KeyAction::Down) => control::KeyAction::Down,
+                    Ok(KeyAction::Up) => control::KeyAction::Up,
+                    Ok(KeyAction::Unspecified) | Err(_) => {
+                        return Err(ControlCodecError::UnknownKeyAction(key.action))
+                    }
+                },
+                meta_state: key.meta_state,
+                timestamp_ms: key.timestamp_ms,
+            })),
+            control_frame::Payload::Text(input) => {
+                Ok(control::ControlFrame::Text { text: input.text })
+            }
+            control_frame::Payload::SystemKey(press) => {
+                let key = match SystemKey::try_from(press.key) {
+                    Ok(SystemKey::Back) => control::SystemKey::Back,
+                    Ok(SystemKey::Home) => control::SystemKey::Home,
+                    Ok(SystemKey::Recents) => control::SystemKey::Recents,
+                    Ok(SystemKey::VolumeUp) => control::SystemKey::VolumeUp,
+                    Ok(SystemKey::VolumeDown) => control::SystemKey::VolumeDown,
+                    Ok(SystemKey::VolumeMute) => control::SystemKey::VolumeMute,
+                    Ok(SystemKey::Unspecified) | Err(_) => {
+                        return Err(ControlCodecError::UnknownSystemKey(press.key))
+                    }
+                };
+                Ok(control::ControlFrame::SystemKey(key))
+            }
+            control_frame::Payload::Gamepad(state) => {
+                Ok(control::ControlFrame::Gamepad(control::GamepadState {
+                    buttons: state.buttons,
+                    left_trigger: state.left_trigger,
+                    right_trigger: state.right_trigger,
+                    left_stick: state.left_stick.unwrap_or_default().into(),
+                    right_stick: state.right_stick.unwrap_or_default().into(),
+                    timestamp_ms: state.timestamp_ms,
+                }))
+            }
+            control_frame::Payload::Mouse(mouse) => {
+                Ok(control::ControlFrame::Mouse(control::MouseEvent {
+                    motion: match required(mouse.motion, "mouse.motion")? {
+                        mouse_event::Motion::Absolute(position) => control::MouseMotion::Absolute {
+                            x: position.x,
+                            y: position.y,
+                        },
+                        mouse_event::Motion::Relative(motion) => control::MouseMotion::Relative {
+                            dx: motion.dx,
+                            dy: motion.dy,
+                        },
+                    },
+                    buttons: mouse.buttons,
+                    scroll_x: mouse.scroll_x,
+                    scroll_y: mouse.scroll_y,
+                    hover: mouse.hover,
+                    timestamp_ms: mouse.timestamp_ms,
+                }))
+            }
+            control_frame::Payload::DisplayInfo(info) => {
+                Ok(control::ControlFrame::DisplayInfo(display::DisplayInfo {
+                    width: info.width,
+                    height: info.height,
+                    rotation: rotation_from_wire(info.rotation)?,
+                    content: required(info.content, "display_info.content")?.into(),
+                }))
+            }
+            control_frame::Payload::OrientationChanged(changed) => {
+                Ok(control::ControlFrame::OrientationChanged {
+                    rotation: rotation_from_wire(changed.rotation)?,
+                    content: required(changed.content, "orientation_changed.content")?.into(),
+                })
+            }
+            control_frame::Payload::SessionProfileChanged(profile) => Ok(
+                control::ControlFrame::SessionProfileChanged(display::StreamProfile {
+                    codec: codec_from_wire(profile.codec)?,
+                    width: narrow(profile.width, "session_profile_changed.width")?,
+                    height: narrow(profile.height, "session_profile_changed.height")?,
+                    fps: narrow(profile.fps, "session_profile_changed.fps")?,
+                    target_bitrate_kbps: profile.target_bitrate_kbps,
+                    keyframe_interval_ms: profile.keyframe_interval_ms,
+                }),
+            ),
+            control_frame::Payload::KeyframeRequest(request) => {
+                Ok(control::ControlFrame::RequestKeyframe {
+                    reason: keyframe_reason_from_wire(request.reason)?,
+                    timestamp_ms: request.timestamp_ms,
+                })
+            }
+            control_frame::Payload::PauseStream(pause) => Ok(control::ControlFrame::PauseStream {
+                timestamp_ms: pause.timestamp_ms,
+            }),
+            control_frame::Payload::ResumeStream(resume) => {
+                Ok(control::ControlFrame::ResumeStream {
+                    timestamp_ms: resume.timestamp_ms,
+                })
+            }
+            control_frame::Payload::ProfileRequest(request) => Ok(
+                control::ControlFrame::RequestProfile(control::ProfileRequest {
+                    codec: request.codec.map(codec_from_wire).transpose()?,
+                    width: request
+                        .width
+                        .map(|width| narrow(width, "profile_request.width"))
+                        .transpose()?,
+                    height: request
+                        .height
+                        .map(|height| narrow(height, "profile_request.height"))
+                        .transpose()?,
+                    fps: request
+                        .fps
+                        .map(|fps| narrow(fps, "profile_request.fps"))
+                        .transpose()?,
+                    target_bitrate_kbps: request.target_bitrate_kbps,
+                    keyframe_interval_ms: request.keyframe_interval_ms,
+                }),
+            ),
+            control_frame::Payload::TouchKeyframeRequest(request) => {
+                Ok(control::ControlFrame::RequestTouchKeyframe {
+                    last_frame_id: request.last_frame_id,
+                })
+            }
+        }
+    }
+}
+
+fn keyframe_reason_from_wire(value: i32) -> Result<control::KeyframeReason, ControlCodecError> {
+    match KeyframeReason::try_from(value) {
+        Ok(KeyframeReason::DecodeError) => Ok(control::KeyframeReason::DecodeError),
+        Ok(KeyframeReason::PacketLoss) => Ok(control::KeyframeReason::PacketLoss),
+        Ok(KeyframeReason::StreamStart) => Ok(control::KeyframeReason::StreamStart),
+        Ok(KeyframeReason::Unspecified) | Err(_) => {
+            Err(ControlCodecError::UnknownKeyframeReason(value))
+        }
+    }
+}
+
+fn codec_from_wire(value: i32) -> Result<Codec, ControlCodecError> {
+    match VideoCodec::try_from(value) {
+        Ok(VideoCodec::H264) => Ok(Codec::H264),
+        Ok(VideoCodec::Hevc) => Ok(Codec::Hevc),
+        Ok(VideoCodec::Av1) => Ok(Codec::Av1),
+        Ok(VideoCodec::Unspecified) | Err(_) => Err(ControlCodecError::UnknownCodec(value)),
+    }
+}
+
+fn rotation_from_wire(value: i32) -> Result<Rotation, ControlCodecError> {
+    match DisplayRotation::try_from(value) {
+        Ok(DisplayRotation::DisplayRotation0) => Ok(Rotation::Deg0),
+        Ok(DisplayRotation::DisplayRotation90) => Ok(Rotation::Deg90),
+        Ok(DisplayRotation::DisplayRotation180) => Ok(Rotation::Deg180),
+        Ok(DisplayRotation::DisplayRotation270) => Ok(Rotation::Deg270),
+        Ok(DisplayRotation::Unspecified) | Err(_) => Err(ControlCodecError::UnknownRotation(value)),
+    }
+}
+
+fn required<T>(value: Option<T>, field: &'static str) -> Result<T, ControlCodecError> {
+    value.ok_or(ControlCodecError::MissingField(field))
+}
+
+fn narrow<T: TryFrom<u32>>(value: u32, field: &'static str) -> Result<T, ControlCodecError> {
+    T::try_from(value).map_err(|_| ControlCodecError::FieldOutOfRange(field))
+}
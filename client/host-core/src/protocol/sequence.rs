@@ -0,0 +1,233 @@
+use std::collections::{BTreeMap, VecDeque};
+
+use crate::protocol::control::TouchEnvelope;
+use crate::protocol::lifecycle::PointerValidator;
+
+pub const DEFAULT_REORDER_WINDOW: u64 = 8;
+pub const DEFAULT_REORDER_TIMEOUT_MS: u64 = 40;
+pub const DEFAULT_HEARTBEAT_TIMEOUT_MS: u64 = 3_000;
+
+const LOST_GAP_HISTORY: usize = 64;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SequenceConfig {
+    pub reorder_window: u64,
+    pub reorder_timeout_ms: u64,
+    pub heartbeat_timeout_ms: u64,
+}
+
+impl Default for SequenceConfig {
+    fn default() -> Self {
+        Self {
+            reorder_window: DEFAULT_REORDER_WINDOW,
+            reorder_timeout_ms: DEFAULT_REORDER_TIMEOUT_MS,
+            heartbeat_timeout_ms: DEFAULT_HEARTBEAT_TIMEOUT_MS,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SequenceStats {
+    pub received: u64,
+    pub delivered: u64,
+    pub duplicates: u64,
+    pub late: u64,
+    pub reordered: u64,
+    pub lost: u64,
+    pub orphaned_events: u64,
+    pub synthesized_releases: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FrameGap {
+    pub first: u64,
+    pub last: u64,
+}
+
+impl FrameGap {
+    pub fn count(&self) -> u64 {
+        self.last - self.first + 1
+    }
+
+    pub fn contains(&self, frame_id: u64) -> bool {
+        (self.first..=self.last).contains(&frame_id)
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SequenceOutput {
+    pub frames: Vec<TouchEnvelope>,
+    pub gaps: Vec<FrameGap>,
+}
+
+impl SequenceOutput {
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty() && self.gaps.is_empty()
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Pending {
+    envelope: TouchEnvelope,
+    arrived_ms: u64,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SequenceTracker {
+    config: SequenceConfig,
+    next_expected: Option<u64>,
+    highest_seen: Option<u64>,
+    pending: BTreeMap<u64, Pending>,
+    lost: VecDeque<FrameGap>,
+    pointers: PointerValidator,
+    release_before_next: bool,
+    last_frame_id: u64,
+    last_heartbeat_ms: u64,
+    stats: SequenceStats,
+}
+
+impl SequenceTracker {
+    pub fn new(config: SequenceConfig) -> Self {
+        Self {
+            config,
+            ..Self::default()
+        }
+    }
+
+    pub fn stats(&self) -> SequenceStats {
+        let pointers = self.pointers.stats();
+        SequenceStats {
+            orphaned_events: pointers.dropped_events,
+            synthesized_releases: pointers.synthesized_releases,
+            ..self.stats
+        }
+    }
+
+    pub fn active_pointers(&self) -> Vec<u8> {
+        self.pointers.active_pointers()
+    }
+
+    pub fn buffered(&self) -> usize {
+        self.pending.len()
+    }
+
+    pub fn push(&mut self, envelope: TouchEnvelope, now_ms: u64) -> SequenceOutput {
+        self.stats.received += 1;
+        let frame_id = envelope.frame_id;
+        let next = *self.next_expected.get_or_insert(frame_id);
+
+        if frame_id < next {
+            if self.lost.iter().any(|gap| gap.contains(frame_id)) {
+                self.stats.late += 1;
+            } else {
+                self.stats.duplicates += 1;
+            }
+            return SequenceOutput::default();
+        }
+        if self.pending.contains_key(&frame_id) {
+            self.stats.duplicates += 1;
+            return SequenceOutput::default();
+        }
+
+        if self.highest_seen.is_some_and(|highest| frame_id < highest) {
+            self.stats.reordered += 1;
+        }
+        self.highest_seen = Some(self.highest_seen.map_or(frame_id, |h| h.max(frame_id)));
+        self.last_heartbeat_ms = self.last_heartbeat_ms.max(now_ms);
+        self.pending.insert(
+            frame_id,
+            Pending {
+                envelope,
+                arrived_ms: now_ms,
+            },
+        );
+
+        let mut output = SequenceOutput::default();
+        self.drain(&mut output, now_ms);
+        output
+    }
+
+    pub fn heartbeat(&mut self, now_ms: u64) {
+        self.last_heartbeat_ms = self.last_heartbeat_ms.max(now_ms);
+    }
+
+    pub fn poll(&mut self, now_ms: u64) -> SequenceOutput {
+        let mut output = SequenceOutput::default();
+        self.drain(&mut output, now_ms);
+
+        if self.pending.is_empty()
+            && now_ms.saturating_sub(self.last_heartbeat_ms) >= self.config.heartbeat_timeout_ms
+        {
+            output.frames.extend(self.release_all());
+        }
+        output
+    }
+
+    pub fn release_all(&mut self) -> Option<TouchEnvelope> {
+        let events = self.pointers.release_all();
+        if events.is_empty() {
+            return None;
+        }
+
+        Some(TouchEnvelope {
+            frame_id: self.last_frame_id,
+            events,
+        })
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::new(self.config);
+    }
+
+    fn drain(&mut self, output: &mut SequenceOutput, now_ms: u64) {
+        while let Some(next) = self.next_expected {
+            if let Some(pending) = self.pending.remove(&next) {
+                self.deliver(pending.envelope, output);
+                self.next_expected = Some(next + 1);
+                continue;
+            }
+
+            let Some((&first, oldest)) = self.pending.iter().next() else {
+                break;
+            };
+            let newest = self.pending.keys().next_back().copied().unwrap_or(first);
+            let overflow = newest - next >= self.config.reorder_window;
+            let expired =
+                now_ms.saturating_sub(oldest.arrived_ms) >= self.config.reorder_timeout_ms;
+            if !overflow && !expired {
+                break;
+            }
+
+            let gap = FrameGap {
+                first: next,
+                last: first - 1,
+            };
+            if self.lost.len() == LOST_GAP_HISTORY {
+                self.lost.pop_front();
+            }
+            self.lost.push_back(gap);
+            self.stats.lost += gap.count();
+            output.gaps.push(gap);
+            self.release_before_next = true;
+            self.next_expected = Some(first);
+        }
+    }
+
+    fn deliver(&mut self, envelope: TouchEnvelope, output: &mut SequenceOutput) {
+        self.stats.delivered += 1;
+        self.last_frame_id = envelope.frame_id;
+
+        let mut events = Vec::with_capacity(envelope.events.len());
+        if std::mem::take(&mut self.release_before_next) {
+            events.extend(self.pointers.release_all());
+        }
+        events.extend(self.pointers.repair(envelope).events);
+
+        if !events.is_empty() {
+            output.frames.push(TouchEnvelope {
+                frame_id: self.last_frame_id,
+                events,
+            });
+        }
+    }
+}
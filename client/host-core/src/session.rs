@@ -1,24 +1,61 @@
 use thiserror::Error;
 
-use crate::config::profile::RuntimeProfile;
+use crate::config::profile::{ProfileError, ProfileUpdate, RuntimeProfile, StreamLevel};
 use crate::pipeline::{build_locked_pipeline, HostCapability, PipelineDescriptor, PipelineError};
+use crate::protocol::control::{ControlFrame, KeyframeReason};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub const KEYFRAME_REQUEST_COOLDOWN_MS: u64 = 250;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
 pub enum SessionState {
+    #[default]
     Idle,
     Starting,
     Running,
+    Paused,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StreamPressure {
+    Congested,
+    Healthy,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct SessionStarted {
+    pub profile: RuntimeProfile,
     pub pipeline: PipelineDescriptor,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReconfigureKind {
+    Unchanged,
+    Live,
+    Rebuild,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reconfigured {
+    pub kind: ReconfigureKind,
+    pub session: SessionStarted,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StreamAction {
+    ForceKeyframe(KeyframeReason),
+    Pause,
+    Resume,
+    Reconfigured(Reconfigured),
+}
+
 #[derive(Debug, Default)]
 pub struct SessionManager {
     state: SessionState,
     active: Option<SessionStarted>,
+    capability: Option<HostCapability>,
+    ladder_index: usize,
+    healthy_streak: u8,
+    last_keyframe_ms: Option<u64>,
 }
 
 impl SessionManager {
@@ -51,23 +88,221 @@ impl SessionManager {
                 return Err(SessionError::Pipeline(err));
             }
         };
-        let started = SessionStarted { pipeline };
+        let started = SessionStarted { profile, pipeline };
 
         self.active = Some(started.clone());
+        self.capability = Some(capability);
         self.state = SessionState::Running;
+        self.ladder_index = 0;
+        self.healthy_streak = 0;
+        self.last_keyframe_ms = None;
 
         Ok(started)
     }
 
+    pub fn reconfigure(&mut self, update: ProfileUpdate) -> Result<Reconfigured, SessionError> {
+        let profile = match (self.state, &self.active) {
+            (SessionState::Running | SessionState::Paused, Some(active)) => update
+                .apply_to(&active.profile)
+                .map_err(SessionError::Profile)?,
+            _ => return Err(SessionError::NotRunning(self.state)),
+        };
+
+        self.apply_profile(profile)
+    }
+
+    pub fn apply_profile(&mut self, profile: RuntimeProfile) -> Result<Reconfigured, SessionError> {
+        let (SessionState::Running | SessionState::Paused, Some(active), Some(capability)) =
+            (self.state, self.active.as_mut(), self.capability.as_ref())
+        else {
+            return Err(SessionError::NotRunning(self.state));
+        };
+
+        let kind = if profile == active.profile {
+            ReconfigureKind::Unchanged
+        } else if active.profile.requires_rebuild(&profile) {
+            let mut pipeline =
+                build_locked_pipeline(&profile, capability).map_err(SessionError::Pipeline)?;
+            pipeline.transport = active.pipeline.transport.clone();
+            active.pipeline = pipeline;
+            ReconfigureKind::Rebuild
+        } else {
+            active.pipeline.retune(&profile);
+            ReconfigureKind::Live
+        };
+
+        if kind != ReconfigureKind::Unchanged {
+            let bitrate_changed = profile.target_bitrate_kbps != active.profile.target_bitrate_kbps;
+            active.profile = profile;
+            self.healthy_streak = 0;
+            self.ladder_index = match &active.pipeline.ladder {
+                Some(_) if kind == ReconfigureKind::Rebuild || bitrate_changed => 0,
+                Some(ladder) => self
+                    .ladder_index
+                    .min(ladder.levels().len().saturating_sub(1)),
+                None => 0,
+            };
+            if let Some(level) = active
+                .pipeline
+                .ladder
+                .as_ref()
+                .and_then(|ladder| ladder.level(self.ladder_index))
+            {
+                active.pipeline.apply_level(level);
+            }
+        }
+
+        Ok(Reconfigured {
+            kind,
+            session: active.clone(),
+        })
+    }
+
+    pub fn profile(&self) -> Option<&RuntimeProfile> {
+        self.active.as_ref().map(|active| &active.profile)
+    }
+
     pub fn stop(&mut self) {
         self.active = None;
+        self.capability = None;
         self.state = SessionState::Idle;
+        self.ladder_index = 0;
+        self.healthy_streak = 0;
+        self.last_keyframe_ms = None;
     }
-}
 
-impl Default for SessionState {
-    fn default() -> Self {
-        SessionState::Idle
+    pub fn pause(&mut self) -> Result<bool, SessionError> {
+        match self.state {
+            SessionState::Running => {
+                self.state = SessionState::Paused;
+                Ok(true)
+            }
+            SessionState::Paused => Ok(false),
+            state => Err(SessionError::InvalidTransition(state, SessionState::Paused)),
+        }
+    }
+
+    pub fn resume(&mut self) -> Result<bool, SessionError> {
+        match self.state {
+            SessionState::Paused => {
+                self.state = SessionState::Running;
+                self.healthy_streak = 0;
+                Ok(true)
+            }
+            SessionState::Running => Ok(false),
+            state => Err(SessionError::InvalidTransition(
+                state,
+                SessionState::Running,
+            )),
+        }
+    }
+
+    pub fn request_keyframe(&mut self, now_ms: u64) -> Result<bool, SessionError> {
+        match self.state {
+            SessionState::Running => {}
+            SessionState::Paused => return Ok(false),
+            state => return Err(SessionError::NotRunning(state)),
+        }
+
+        let cooling_down = self
+            .last_keyframe_ms
+            .is_some_and(|last| (last..last + KEYFRAME_REQUEST_COOLDOWN_MS).contains(&now_ms));
+        if cooling_down {
+            return Ok(false);
+        }
+
+        self.last_keyframe_ms = Some(now_ms);
+        Ok(true)
+    }
+
+    pub fn handle_stream_control(
+        &mut self,
+        frame: &ControlFrame,
+        now_ms: u64,
+    ) -> Result<Vec<StreamAction>, SessionError> {
+        match frame {
+            ControlFrame::RequestKeyframe { reason, .. } => Ok(self
+                .request_keyframe(now_ms)?
+                .then_some(StreamAction::ForceKeyframe(*reason))
+                .into_iter()
+                .collect()),
+            ControlFrame::PauseStream { .. } => Ok(self
+                .pause()?
+                .then_some(StreamAction::Pause)
+                .into_iter()
+                .collect()),
+            ControlFrame::ResumeStream { .. } => {
+                if !self.resume()? {
+                    return Ok(Vec::new());
+                }
+                self.last_keyframe_ms = Some(now_ms);
+                Ok(vec![
+                    StreamAction::Resume,
+                    StreamAction::ForceKeyframe(KeyframeReason::StreamStart),
+                ])
+            }
+            ControlFrame::RequestProfile(request) => {
+                let reconfigured = self.reconfigure(ProfileUpdate::from(*request))?;
+                if reconfigured.kind == ReconfigureKind::Unchanged {
+                    return Ok(Vec::new());
+                }
+                if reconfigured.kind == ReconfigureKind::Rebuild {
+                    self.last_keyframe_ms = Some(now_ms);
+                }
+                Ok(vec![StreamAction::Reconfigured(reconfigured)])
+            }
+            _ => Err(SessionError::NotStreamControl),
+        }
+    }
+
+    pub fn current_level(&self) -> Option<StreamLevel> {
+        let pipeline = &self.active.as_ref()?.pipeline;
+        Some(StreamLevel {
+            width: pipeline.capture.width,
+            height: pipeline.capture.height,
+            fps: pipeline.capture.fps,
+            target_bitrate_kbps: pipeline.encoder.target_bitrate_kbps,
+        })
+    }
+
+    pub fn report_pressure(
+        &mut self,
+        pressure: StreamPressure,
+    ) -> Result<Option<StreamLevel>, SessionError> {
+        let active = match (&self.state, self.active.as_mut()) {
+            (SessionState::Running, Some(active)) => active,
+            _ => return Err(SessionError::NotRunning(self.state)),
+        };
+
+        let Some(ladder) = &active.pipeline.ladder else {
+            return Ok(None);
+        };
+
+        let next_index = match pressure {
+            StreamPressure::Congested => {
+                self.healthy_streak = 0;
+                if self.ladder_index + 1 >= ladder.levels().len() {
+                    return Ok(None);
+                }
+                self.ladder_index + 1
+            }
+            StreamPressure::Healthy => {
+                self.healthy_streak = self.healthy_streak.saturating_add(1);
+                if self.ladder_index == 0 || self.healthy_streak < ladder.recovery_samples() {
+                    return Ok(None);
+                }
+                self.healthy_streak = 0;
+                self.ladder_index - 1
+            }
+        };
+
+        let Some(level) = ladder.level(next_index) else {
+            return Ok(None);
+        };
+        self.ladder_index = next_index;
+        active.pipeline.apply_level(level);
+
+        Ok(Some(level))
     }
 }
 
@@ -77,4 +312,10 @@ pub enum SessionError {
     InvalidTransition(SessionState, SessionState),
     #[error("session pipeline validation failed: {0}")]
     Pipeline(PipelineError),
+    #[error("session is not running (current state {0:?})")]
+    NotRunning(SessionState),
+    #[error("session profile update is invalid: {0}")]
+    Profile(ProfileError),
+    #[error("control frame is not a stream control request")]
+    NotStreamControl,
 }
@@ -0,0 +1,269 @@
+use host_core::protocol::auth::{
+    AuthError, Direction, FrameAuthenticator, SessionKey, AUTH_COUNTER_LEN, AUTH_TAG_LEN,
+};
+use host_core::protocol::control::{
+    CompactTouchDecoder, CompactTouchEncoder, ControlCodecError, ControlFrame, KeyAction, KeyEvent,
+    PointerAction, PointerEvent, TouchEnvelope, WireCodec,
+};
+
+fn session_key() -> SessionKey {
+    SessionKey::derive(b"paired-device-secret", b"session-0001")
+}
+
+fn touch(frame_id: u64, timestamp_ms: u64) -> ControlFrame {
+    ControlFrame::Touch(TouchEnvelope {
+        frame_id,
+        events: vec![PointerEvent {
+            pointer_id: 0,
+            action: PointerAction::Move,
+            x: 0.5,
+            y: 0.5,
+            pressure: 0.5,
+            timestamp_ms,
+        }],
+    })
+}
+
+fn auth_error(err: ControlCodecError) -> AuthError {
+    match err {
+        ControlCodecError::Authentication(err) => err,
+        other => panic!("expected authentication failure, got {other}"),
+    }
+}
+
+#[test]
+fn sealed_frames_open_with_the_same_session_key() {
+    for codec in [WireCodec::Json, WireCodec::Protobuf] {
+        let mut sender = FrameAuthenticator::new(session_key(), codec);
+        let mut receiver = FrameAuthenticator::new(session_key(), codec);
+
+        let sealed = sender.seal(&touch(1, 100)).expect("seal");
+        assert_eq!(receiver.open(&sealed).expect("open"), touch(1, 100));
+    }
+}
+
+#[test]
+fn tampered_or_foreign_frames_are_rejected() {
+    let mut sender = FrameAuthenticator::new(session_key(), WireCodec::Json);
+    let mut receiver = FrameAuthenticator::new(session_key(), WireCodec::Json);
+    let sealed = sender.seal(&touch(1, 100)).expect("seal");
+
+    let mut tampered = sealed.clone();
+    tampered[10] ^= 0x01;
+    assert_eq!(
+        auth_error(receiver.open(&tampered).expect_err("tampered")),
+        AuthError::BadTag
+    );
+
+    let mut spoofer = FrameAuthenticator::new(
+        SessionKey::derive(b"paired-device-secret", b"session-0002"),
+        WireCodec::Json,
+    );
+    let forged = spoofer.seal(&touch(2, 110)).expect("seal");
+    assert_eq!(
+        auth_error(receiver.open(&forged).expect_err("foreign key")),
+        AuthError::BadTag
+    );
+
+    let unsigned = touch(3, 120).to_wire_bytes().expect("encode");
+    assert!(receiver
+        .open(&unsigned[..AUTH_TAG_LEN.min(unsigned.len())])
+        .is_err());
+    for truncated in [AUTH_TAG_LEN, AUTH_TAG_LEN + AUTH_COUNTER_LEN] {
+        assert_eq!(
+            auth_error(receiver.open(&vec![0_u8; truncated]).expect_err("no body")),
+            AuthError::MissingTag
+        );
+    }
+
+    assert_eq!(receiver.open(&sealed).expect("genuine"), touch(1, 100));
+}
+
+#[test]
+fn replay_window_rejects_repeated_and_stale_frames() {
+    let mut sender = FrameAuthenticator::new(session_key(), WireCodec::Protobuf);
+    let mut receiver = FrameAuthenticator::new(session_key(), WireCodec::Protobuf);
+
+    let first = sender.seal(&touch(10, 1_000)).expect("seal");
+    let second = sender.seal(&touch(11, 1_010)).expect("seal");
+    let third = sender.seal(&touch(12, 1_020)).expect("seal");
+    receiver.open(&first).expect("first");
+    receiver.open(&third).expect("skip ahead");
+    receiver.open(&second).expect("reordered inside window");
+    assert_eq!(
+        auth_error(receiver.open(&first).expect_err("replayed")),
+        AuthError::ReplayedFrame(0)
+    );
+
+    let resent = sender.seal(&touch(12, 1_020)).expect("seal");
+    assert_eq!(
+        receiver.open(&resent).expect("fresh counter"),
+        touch(12, 1_020)
+    );
+
+    let key = ControlFrame::Key(KeyEvent {
+        keycode: 66,
+        action: KeyAction::Up,
+        meta_state: 0,
+        timestamp_ms: 1_030,
+    });
+    let sealed_key = sender.seal(&key).expect("seal");
+    let held_back = sender.seal(&key).expect("seal");
+    receiver.open(&sealed_key).expect("key");
+    assert_eq!(
+        auth_error(receiver.open(&sealed_key).expect_err("replayed key")),
+        AuthError::ReplayedFrame(4)
+    );
+
+    for frame_id in 0..64 {
+        sender.seal(&touch(frame_id, 1_040)).expect("seal");
+    }
+    receiver
+        .open(&sender.seal(&touch(100, 5_000)).expect("seal"))
+        .expect("far ahead");
+    assert_eq!(
+        auth_error(receiver.open(&held_back).expect_err("outside window")),
+        AuthError::ReplayedFrame(5)
+    );
+
+    let old_key = ControlFrame::Key(KeyEvent {
+        timestamp_ms: 1_000,
+        ..match key {
+            ControlFrame::Key(event) => event,
+            _ => unreachable!(),
+        }
+    });
+    let err = auth_error(
+        receiver
+            .open(&sender.seal(&old_key).expect("seal"))
+            .expect_err("stale"),
+    );
+    assert!(matches!(err, AuthError::StaleTimestamp { .. }));
+}
+
+#[test]
+fn compact_touch_frames_are_sealed_and_replay_checked() {
+    let mut sender = FrameAuthenticator::new(session_key(), WireCodec::Protobuf);
+    let mut receiver = FrameAuthenticator::new(session_key(), WireCodec::Protobuf);
+    let mut encoder = CompactTouchEncoder::default();
+    let mut decoder = CompactTouchDecoder::default();
+
+    let envelope = |frame_id| match touch(frame_id, 1_000 + frame_id) {
+        ControlFrame::Touch(touch) => touch,
+        _ => unreachable!(),
+    };
+    let mut down = envelope(1);
+    down.events[0].action = PointerAction::Down;
+    let first = sender.seal_compact_touch(&encoder.encode(&down).expect("encode"));
+    let second = sender.seal_compact_touch(&encoder.encode(&envelope(2)).expect("encode"));
+
+    assert_eq!(
+        receiver
+            .open_compact_touch(&first, &mut decoder)
+            .expect("keyframe")
+            .frame_id,
+        1
+    );
+    assert_eq!(
+        auth_error(
+            receiver
+                .open_compact_touch(&first, &mut decoder)
+                .expect_err("replayed keyframe")
+        ),
+        AuthError::ReplayedFrame(0)
+    );
+    assert!(!decoder.needs_keyframe());
+
+    let mut tampered = second.clone();
+    tampered[2] ^= 0x01;
+    assert_eq!(
+        auth_error(
+            receiver
+                .open_compact_touch(&tampered, &mut decoder)
+                .expect_err("tampered")
+        ),
+        AuthError::BadTag
+    );
+    assert_eq!(
+        receiver
+            .open_compact_touch(&second, &mut decoder)
+            .expect("delta")
+            .frame_id,
+        2
+    );
+}
+
+#[test]
+fn session_key_parsing_requires_32_bytes() {
+    assert_eq!(
+        SessionKey::from_base64("c2hvcnQ="),
+        Err(AuthError::InvalidKeyLength(5))
+    );
+    assert_eq!(
+        SessionKey::from_base64("not base64!"),
+        Err(AuthError::InvalidKeyEncoding)
+    );
+    let encoded = "AAECAwQFBgcICQoLDA0ODxAREhMUFRYXGBkaGxwdHh8=";
+    assert_eq!(
+        SessionKey::from_base64(encoded).expect("valid"),
+        SessionKey::from_bytes(&(0..32).collect::<Vec<u8>>()).expect("valid")
+    );
+
+    let pairing = SessionKey::from_base64(encoded).expect("valid");
+    assert_eq!(
+        pairing.for_session(b"session-0001"),
+        SessionKey::derive(&(0..32).collect::<Vec<u8>>(), b"session-0001")
+    );
+    assert_ne!(
+        pairing.for_session(b"session-0001"),
+        pairing.for_session(b"session-0002")
+    );
+}
+
+#[test]
+fn direction_keys_reject_reflected_frames() {
+    let session = session_key();
+    let mut phone = FrameAuthenticator::new(
+        session.for_direction(Direction::PhoneToHost),
+        WireCodec::Json,
+    );
+    let mut host_inbound = FrameAuthenticator::new(
+        session.for_direction(Direction::PhoneToHost),
+        WireCodec::Json,
+    );
+    let mut host_outbound = FrameAuthenticator::new(
+        session.for_direction(Direction::HostToPhone),
+        WireCodec::Json,
+    );
+    assert_ne!(
+        session.for_direction(Direction::PhoneToHost),
+        session.for_direction(Direction::HostToPhone)
+    );
+
+    let reflected = host_outbound.seal(&touch(1, 100)).expect("seal");
+    assert_eq!(
+        auth_error(host_inbound.open(&reflected).expect_err("reflected")),
+        AuthError::BadTag
+    );
+
+    let sealed = phone.seal(&touch(1, 100)).expect("seal");
+    assert_eq!(host_inbound.open(&sealed).expect("open"), touch(1, 100));
+}
+
+#[test]
+fn direction_keys_match_the_phone_derivation() {
+    let pairing = SessionKey::from_bytes(&(0..32).collect::<Vec<u8>>()).expect("valid");
+    let key = pairing
+        .for_session(b"0123456789abcdef")
+        .for_direction(Direction::PhoneToHost);
+    let sealed = FrameAuthenticator::new(key, WireCodec::Json).seal_datagram(b"LMC_PING");
+
+    let tag = sealed[sealed.len() - AUTH_TAG_LEN..]
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect::<String>();
+    assert_eq!(
+        tag,
+        "f6f889bd8a6d1cbde029f084241c400c70e7541a2ebb15f0df6c08d48a283a14"
+    );
+}
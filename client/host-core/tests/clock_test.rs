@@ -0,0 +1,70 @@
+use host_core::protocol::clock::{ClockError, ClockEstimator, ClockSample};
+use host_core::protocol::control::{ControlFrame, Pong, WireCodec};
+
+fn pong(origin: u64, receive: u64, transmit: u64) -> Pong {
+    Pong {
+        origin_timestamp_ms: origin,
+        receive_timestamp_ms: receive,
+        transmit_timestamp_ms: transmit,
+    }
+}
+
+#[test]
+fn pong_frame_round_trips_on_both_codecs() {
+    let frame = ControlFrame::Pong(pong(1_000, 6_010, 6_012));
+
+    for codec in [WireCodec::Json, WireCodec::Protobuf] {
+        let bytes = codec.encode(&frame).expect("encode");
+        assert_eq!(codec.decode(&bytes).expect("decode"), frame);
+    }
+}
+
+#[test]
+fn sample_excludes_peer_processing_time() {
+    let sample = ClockSample::from_pong(&pong(1_000, 6_010, 6_012), 1_022).expect("sample");
+
+    assert_eq!(sample.rtt_ms, 20.0);
+    assert_eq!(sample.offset_ms, 5_000.0);
+}
+
+#[test]
+fn estimator_smooths_rtt_and_trusts_the_fastest_offset() {
+    let mut estimator = ClockEstimator::default();
+    assert_eq!(estimator.rtt_ms(), None);
+    assert_eq!(estimator.peer_to_host_ms(6_000), None);
+
+    estimator
+        .on_pong(&pong(1_000, 6_010, 6_012), 1_022)
+        .expect("symmetric sample");
+    let congested = estimator
+        .on_pong(&pong(2_000, 7_030, 7_032), 2_038)
+        .expect("asymmetric sample");
+    assert_eq!(congested.rtt_ms, 36.0);
+
+    assert_eq!(estimator.samples(), 2);
+    assert_eq!(estimator.rtt_ms(), Some(22.0));
+    assert_eq!(estimator.jitter_ms(), 1.0);
+    assert_eq!(estimator.offset_ms(), Some(5_000.0));
+
+    assert_eq!(estimator.peer_to_host_ms(6_100), Some(1_100));
+    assert_eq!(estimator.latency_ms(6_100, 1_130), Some(30));
+
+    estimator.reset();
+    assert_eq!(estimator.offset_ms(), None);
+}
+
+#[test]
+fn estimator_rejects_impossible_timestamps() {
+    let mut estimator = ClockEstimator::default();
+
+    let err = estimator
+        .on_pong(&pong(1_000, 6_010, 6_012), 999)
+        .expect_err("arrival before origin");
+    assert!(matches!(err, ClockError::ArrivalBeforeOrigin { .. }));
+
+    let err = estimator
+        .on_pong(&pong(1_000, 6_012, 6_010), 1_022)
+        .expect_err("transmit before receive");
+    assert!(err.to_string().contains("before it received the ping"));
+    assert_eq!(estimator.samples(), 0);
+}
@@ -0,0 +1,160 @@
+use host_core::config::loader::{ConfigError, ConfigFile, ProfileLayer, ProfileLoader};
+use host_core::config::profile::{BitDepth, ChromaSubsampling, Codec, ColorRange, LockPolicy};
+
+const SAMPLE_TOML: &str = r#"
+default_profile = "office"
+
+[profiles.office]
+fps = 90
+resolution = "1920x1080"
+bitrate_kbps = 40000
+codec = "h264"
+
+[profiles.desk]
+fps = 144
+resolution = "2460x1080"
+bitrate_kbps = 80000
+"#;
+
+#[test]
+fn named_profile_is_loaded_from_toml_file() {
+    let file = ConfigFile::from_toml_str(SAMPLE_TOML).expect("valid toml");
+
+    let profile = ProfileLoader::new()
+        .with_file(file)
+        .with_profile_name("desk")
+        .resolve()
+        .expect("profile resolves");
+
+    assert_eq!((profile.width, profile.height), (2460, 1080));
+    assert_eq!(profile.fps, 144);
+    assert_eq!(profile.codec, Codec::Hevc);
+    assert_eq!(profile.lock_policy, LockPolicy::TurboLock);
+}
+
+#[test]
+fn env_and_cli_layers_override_file_values_in_order() {
+    let file = ConfigFile::from_json_str(
+        r#"{"default_profile":"office","profiles":{"office":{"fps":90,"resolution":"1920x1080","bitrate_kbps":40000}}}"#,
+    )
+    .expect("valid json");
+    let env = ProfileLayer::from_env_vars([
+        ("LMC_FPS", "120"),
+        ("LMC_BITRATE", "50000"),
+        ("LMC_ADB_PATH", "C:\\adb.exe"),
+    ])
+    .expect("env layer");
+    let mut cli = ProfileLayer::default();
+    cli.set("bitrate", "60000").expect("cli value");
+
+    let profile = ProfileLoader::new()
+        .with_file(file)
+        .with_env(env)
+        .with_cli(cli)
+        .resolve()
+        .expect("profile resolves");
+
+    assert_eq!(profile.fps, 120);
+    assert_eq!(profile.target_bitrate_kbps, 60_000);
+    assert_eq!((profile.width, profile.height), (1920, 1080));
+}
+
+#[test]
+fn unknown_profile_name_is_rejected() {
+    let file = ConfigFile::from_toml_str(SAMPLE_TOML).expect("valid toml");
+
+    let err = ProfileLoader::new()
+        .with_file(file)
+        .with_profile_name("travel")
+        .resolve()
+        .expect_err("missing profile must fail");
+
+    assert!(matches!(err, ConfigError::UnknownProfile(name) if name == "travel"));
+}
+
+#[test]
+fn layered_result_is_still_validated_by_runtime_profile() {
+    let mut cli = ProfileLayer::default();
+    cli.set("fps", "75").expect("numeric value");
+
+    let err = ProfileLoader::new()
+        .with_cli(cli)
+        .resolve()
+        .expect_err("75fps must be rejected");
+
+    assert!(matches!(err, ConfigError::Profile(_)));
+}
+
+#[test]
+fn adaptive_settings_without_adaptive_policy_are_rejected() {
+    let file = ConfigFile::from_toml_str(
+        r#"
+[profiles.desk]
+fps = 144
+resolution = "2460x1080"
+
+[profiles.desk.adaptive]
+floor_fps = 90
+"#,
+    )
+    .expect("valid toml");
+
+    let err = ProfileLoader::new()
+        .with_file(file)
+        .with_profile_name("desk")
+        .resolve()
+        .expect_err("turbo_lock ignores [adaptive]");
+
+    assert!(matches!(err, ConfigError::AdaptiveWithoutPolicy));
+}
+
+#[test]
+fn custom_resolution_mode_can_be_selected_from_config() {
+    let file = ConfigFile::from_toml_str(
+        r#"
+[profiles.phone]
+fps = 120
+resolution = "2340x1080"
+resolution_mode = "custom"
+bitrate_kbps = 60000
+"#,
+    )
+    .expect("valid toml");
+
+    let profile = ProfileLoader::new()
+        .with_file(file)
+        .with_profile_name("phone")
+        .resolve()
+        .expect("custom profile resolves");
+
+    assert_eq!((profile.width, profile.height), (2340, 1080));
+}
+
+#[test]
+fn color_settings_are_layered_from_file_and_cli() {
+    let file = ConfigFile::from_toml_str(
+        r#"
+[profiles.studio]
+fps = 120
+resolution = "1920x1080"
+codec = "hevc"
+color_range = "full"
+bit_depth = 10
+"#,
+    )
+    .expect("valid toml");
+
+    let mut cli = ProfileLayer::default();
+    cli.set("chroma", "4:4:4").expect("valid chroma");
+
+    let profile = ProfileLoader::new()
+        .with_file(file)
+        .with_profile_name("studio")
+        .with_cli(cli)
+        .resolve()
+        .expect("color profile resolves");
+
+    assert_eq!(profile.color.range, ColorRange::Full);
+    assert_eq!(profile.color.chroma, ChromaSubsampling::Yuv444);
+    assert_eq!(profile.color.bit_depth, BitDepth::Ten);
+}
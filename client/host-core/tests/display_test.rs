@@ -0,0 +1,184 @@
+use host_core::config::profile::{Codec, LockPolicy, RuntimeProfile};
+use host_core::geometry::Rotation;
+use host_core::protocol::control::{ControlFrame, WireCodec};
+use host_core::protocol::display::{ContentRect, DisplayAnnouncer, DisplayInfo, StreamProfile};
+
+fn profile(width: u16, height: u16, bitrate_kbps: u32) -> RuntimeProfile {
+    RuntimeProfile::new(
+        width,
+        height,
+        120,
+        bitrate_kbps,
+        Codec::Hevc,
+        LockPolicy::TurboLock,
+    )
+    .expect("valid profile")
+}
+
+#[test]
+fn display_frames_round_trip_and_are_host_originated() {
+    let frames = [
+        ControlFrame::DisplayInfo(DisplayInfo {
+            width: 2460,
+            height: 1080,
+            rotation: Rotation::Deg90,
+            content: ContentRect {
+                x: 723,
+                y: 0,
+                width: 474,
+                height: 1080,
+                frame_width: 1920,
+                frame_height: 1080,
+            },
+        }),
+        ControlFrame::OrientationChanged {
+            rotation: Rotation::Deg270,
+            content: ContentRect::letterboxed(1080, 2460, 1920, 1080),
+        },
+        ControlFrame::SessionProfileChanged(StreamProfile::from(&profile(1920, 1080, 40_000))),
+    ];
+
+    for frame in frames {
+        assert!(frame.is_host_originated());
+        for codec in [WireCodec::Json, WireCodec::Protobuf] {
+            let bytes = codec.encode(&frame).expect("encode");
+            assert_eq!(codec.decode(&bytes).expect("decode"), frame);
+        }
+    }
+    assert!(!ControlFrame::Ping { timestamp_ms: 1 }.is_host_originated());
+}
+
+#[test]
+fn announcer_emits_only_what_changed() {
+    let mut announcer = DisplayAnnouncer::default();
+    let stream = profile(1920, 1080, 40_000);
+
+    assert_eq!(
+        announcer.update_profile(&stream),
+        [ControlFrame::SessionProfileChanged(StreamProfile::from(
+            &stream
+        ))]
+    );
+
+    let landscape = ContentRect {
+        x: 0,
+        y: 118,
+        width: 1920,
+        height: 843,
+        frame_width: 1920,
+        frame_height: 1080,
+    };
+    assert_eq!(
+        announcer.update_display(2460, 1080, Rotation::Deg0),
+        [ControlFrame::DisplayInfo(DisplayInfo {
+            width: 2460,
+            height: 1080,
+            rotation: Rotation::Deg0,
+            content: landscape,
+        })]
+    );
+    assert!(announcer
+        .update_display(2460, 1080, Rotation::Deg0)
+        .is_empty());
+
+    assert_eq!(
+        announcer.update_rotation(Rotation::Deg90),
+        [ControlFrame::OrientationChanged {
+            rotation: Rotation::Deg90,
+            content: ContentRect {
+                x: 723,
+                y: 0,
+                width: 474,
+                height: 1080,
+                frame_width: 1920,
+                frame_height: 1080,
+            },
+        }]
+    );
+
+    let retuned = announcer.update_profile(&profile(1920, 1080, 30_000));
+    assert!(matches!(
+        retuned.as_slice(),
+        [ControlFrame::SessionProfileChanged(StreamProfile {
+            target_bitrate_kbps: 30_000,
+            ..
+        })]
+    ));
+
+    let resized = announcer.update_profile(&profile(1280, 720, 20_000));
+    assert_eq!(resized.len(), 2);
+    assert!(matches!(
+        resized[1],
+        ControlFrame::DisplayInfo(DisplayInfo {
+            rotation: Rotation::Deg90,
+            content: ContentRect { height: 720, .. },
+            ..
+        })
+    ));
+    assert_eq!(announcer.snapshot(), resized);
+}
+
+#[test]
+fn content_rect_is_always_in_stream_frame_pixels() {
+    let mut announcer = DisplayAnnouncer::default();
+    assert_eq!(
+        announcer.update_display(2460, 1080, Rotation::Deg90),
+        [ControlFrame::DisplayInfo(DisplayInfo {
+            width: 2460,
+            height: 1080,
+            rotation: Rotation::Deg90,
+            content: ContentRect {
+                x: 0,
+                y: 0,
+                width: 1080,
+                height: 2460,
+                frame_width: 1080,
+                frame_height: 2460,
+            },
+        })]
+    );
+
+    let frames = announcer.update_profile(&profile(1920, 1080, 40_000));
+    let Some(ControlFrame::DisplayInfo(info)) = frames.last() else {
+        panic!("expected display info, got {frames:?}");
+    };
+    assert_eq!(
+        (info.content.frame_width, info.content.frame_height),
+        (1920, 1080)
+    );
+    assert!(info.content.is_within_frame());
+}
+
+#[test]
+fn display_frames_with_empty_or_overflowing_rects_are_rejected() {
+    let content = ContentRect::letterboxed(2460, 1080, 1920, 1080);
+    let invalid = [
+        ControlFrame::DisplayInfo(DisplayInfo {
+            width: 0,
+            height: 1080,
+            rotation: Rotation::Deg0,
+            content,
+        }),
+        ControlFrame::OrientationChanged {
+            rotation: Rotation::Deg0,
+            content: ContentRect {
+                frame_height: 0,
+                ..content
+            },
+        },
+        ControlFrame::OrientationChanged {
+            rotation: Rotation::Deg0,
+            content: ContentRect {
+                y: u32::MAX,
+                ..content
+            },
+        },
+    ];
+
+    for frame in invalid {
+        for codec in [WireCodec::Json, WireCodec::Protobuf] {
+            let bytes = codec.encode(&frame).expect("encode");
+            assert!(codec.decode(&bytes).is_err(), "{frame:?}");
+        }
+    }
+}
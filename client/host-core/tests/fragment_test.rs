@@ -0,0 +1,217 @@
+use host_core::protocol::auth::{FrameAuthenticator, SessionKey};
+use host_core::protocol::control::{
+    ControlFrame, PointerAction, PointerEvent, TouchEnvelope, WireCodec,
+};
+use host_core::protocol::fragment::{
+    is_fragment, FragmentError, FragmentHeader, Fragmenter, Reassembler, ReassemblyConfig,
+    FRAGMENT_HEADER_LEN,
+};
+use host_core::transport::webrtc::TransportDescriptor;
+
+fn full_touch_frame() -> Vec<u8> {
+    let events = (0..32)
+        .map(|index| PointerEvent {
+            pointer_id: index % 10,
+            action: PointerAction::Move,
+            x: f32::from(index) / 32.0,
+            y: 0.5,
+            pressure: 0.75,
+            timestamp_ms: 1_700_000_000_000 + u64::from(index),
+        })
+        .collect();
+    WireCodec::Json
+        .encode(&ControlFrame::Touch(TouchEnvelope {
+            frame_id: 9,
+            events,
+        }))
+        .expect("encode")
+}
+
+#[test]
+fn large_touch_frames_are_split_and_reassembled_out_of_order() {
+    let payload = full_touch_frame();
+    let transport = TransportDescriptor::lan_low_latency();
+    let mut fragmenter = Fragmenter::for_transport(&transport, false).expect("fragmenter");
+
+    let mut fragments = fragmenter.split(&payload).expect("split");
+    assert!(payload.len() > usize::from(transport.max_datagram_size));
+    assert!(fragments.len() > 1);
+    assert!(fragments.iter().all(|fragment| fragment.len()
+        <= usize::from(transport.max_datagram_size)
+        && is_fragment(fragment)));
+
+    fragments.reverse();
+    let mut reassembler = Reassembler::default();
+    let (last, rest) = fragments.split_last().expect("fragments");
+    for fragment in rest {
+        assert_eq!(reassembler.push(fragment, 0).expect("fragment"), None);
+    }
+    assert_eq!(
+        reassembler.push(&rest[0], 1).expect("duplicate fragment"),
+        None
+    );
+
+    let reassembled = reassembler
+        .push(last, 2)
+        .expect("fragment")
+        .expect("complete message");
+    assert_eq!(reassembled, payload);
+    assert!(matches!(
+        WireCodec::Json.decode(&reassembled),
+        Ok(ControlFrame::Touch(touch)) if touch.events.len() == 32
+    ));
+    assert_eq!(reassembler.pending(), 0);
+    assert_eq!(reassembler.buffered_bytes(), 0);
+
+    let stats = reassembler.stats();
+    assert_eq!(stats.completed, 1);
+    assert_eq!(stats.duplicates, 1);
+    assert_eq!(reassembler.push(&rest[1], 3).expect("straggler"), None);
+    assert_eq!(reassembler.stats().duplicates, 2);
+}
+
+#[test]
+fn sealed_fragments_are_verified_before_reassembly() {
+    let key = SessionKey::derive(b"paired-device-secret", b"session-0001");
+    let mut sender = FrameAuthenticator::new(key.clone(), WireCodec::Json);
+    let mut receiver = FrameAuthenticator::new(key, WireCodec::Json);
+    let mut spoofer = FrameAuthenticator::new(
+        SessionKey::derive(b"attacker", b"session-0001"),
+        WireCodec::Json,
+    );
+    let transport = TransportDescriptor::lan_low_latency();
+    let max_datagram_size = transport.max_datagram_size;
+    let mut fragmenter = Fragmenter::for_transport(&transport, true).expect("fragmenter");
+    let mut reassembler = Reassembler::default();
+
+    let payload = full_touch_frame();
+    let fragments = fragmenter.split(&payload).expect("split");
+    let sealed = fragments
+        .iter()
+        .map(|fragment| sender.seal_datagram(fragment))
+        .collect::<Vec<_>>();
+    assert!(sealed
+        .iter()
+        .all(|datagram| datagram.len() <= usize::from(max_datagram_size)));
+
+    let forged = spoofer.seal_datagram(&fragments[1]);
+    assert!(receiver.open_datagram(&forged).is_err());
+    assert!(receiver.open_datagram(&fragments[0]).is_err());
+    assert_eq!(reassembler.pending(), 0);
+
+    let mut reassembled = None;
+    for datagram in &sealed {
+        let verified = receiver.open_datagram(datagram).expect("verified");
+        reassembled = reassembler.push(verified, 0).expect("fragment");
+    }
+    let reassembled = reassembled.expect("complete message");
+    assert_eq!(reassembled, payload);
+    assert!(matches!(
+        receiver.accept(&reassembled),
+        Ok(ControlFrame::Touch(touch)) if touch.frame_id == 9
+    ));
+    assert!(receiver.open_datagram(&sealed[0]).is_err());
+}
+
+#[test]
+fn small_frames_pass_through_unfragmented() {
+    let mut fragmenter = Fragmenter::new(1200).expect("fragmenter");
+    let mut reassembler = Reassembler::default();
+    let ping = br#"{"kind":"ping","payload":{"timestamp_ms":1}}"#;
+
+    let fragments = fragmenter.split(ping).expect("split");
+    assert_eq!(fragments, [ping.to_vec()]);
+    assert_eq!(
+        reassembler.push(&fragments[0], 0).expect("passthrough"),
+        Some(ping.to_vec())
+    );
+
+    let lan = b"LMC_PING";
+    assert_eq!(reassembler.push(lan, 0).expect("lan"), Some(lan.to_vec()));
+}
+
+#[test]
+fn incomplete_sets_expire_and_memory_stays_bounded() {
+    let mut fragmenter = Fragmenter::new(64).expect("fragmenter");
+    let mut reassembler = Reassembler::new(ReassemblyConfig {
+        timeout_ms: 100,
+        max_pending_messages: 2,
+        max_buffered_bytes: 1_024,
+    });
+
+    let lost_tail = fragmenter.split(&[1; 300]).expect("split");
+    for fragment in &lost_tail[..lost_tail.len() - 1] {
+        assert_eq!(reassembler.push(fragment, 0).expect("fragment"), None);
+    }
+    assert_eq!(reassembler.pending(), 1);
+    assert_eq!(reassembler.expire(99), 0);
+    assert_eq!(reassembler.expire(100), 1);
+    assert_eq!(reassembler.pending(), 0);
+    assert_eq!(reassembler.buffered_bytes(), 0);
+    assert_eq!(reassembler.stats().expired, 1);
+
+    let sets = (0..3)
+        .map(|_| fragmenter.split(&[2; 200]).expect("split"))
+        .collect::<Vec<_>>();
+    for (offset, set) in (0..).zip(&sets) {
+        reassembler.push(&set[0], 200 + offset).expect("fragment");
+    }
+    assert_eq!(reassembler.pending(), 2);
+    assert_eq!(reassembler.stats().evicted, 1);
+
+    let evicted_rest = sets[0][1..]
+        .iter()
+        .map(|fragment| reassembler.push(fragment, 210).expect("fragment"))
+        .collect::<Vec<_>>();
+    assert!(evicted_rest.iter().all(Option::is_none));
+
+    let big = fragmenter.split(&[3; 1_500]).expect("split");
+    for fragment in &big {
+        reassembler.push(fragment, 220).expect("fragment");
+        assert!(reassembler.buffered_bytes() <= 1_024);
+    }
+}
+
+#[test]
+fn malformed_fragment_headers_are_rejected() {
+    let mut reassembler = Reassembler::default();
+    let mut fragmenter = Fragmenter::new(32).expect("fragmenter");
+    let fragments = fragmenter.split(&[7; 60]).expect("split");
+
+    assert_eq!(
+        reassembler.push(&fragments[0][..4], 0),
+        Err(FragmentError::TruncatedHeader(4))
+    );
+
+    let mut bad_index = fragments[0].clone();
+    bad_index[6..8].copy_from_slice(&9_u16.to_be_bytes());
+    assert_eq!(
+        reassembler.push(&bad_index, 0),
+        Err(FragmentError::InvalidFragmentIndex { index: 9, count: 3 })
+    );
+
+    let (header, body) = FragmentHeader::parse(&fragments[1])
+        .expect("fragment")
+        .expect("valid header");
+    assert_eq!(header.index, 1);
+    assert_eq!(body.len(), 32 - FRAGMENT_HEADER_LEN);
+
+    reassembler.push(&fragments[0], 0).expect("fragment");
+    let mut recounted = fragments[1].clone();
+    recounted[8..10].copy_from_slice(&4_u16.to_be_bytes());
+    assert_eq!(
+        reassembler.push(&recounted, 0),
+        Err(FragmentError::InconsistentFragmentCount(header.message_id))
+    );
+    assert_eq!(reassembler.pending(), 0);
+    assert_eq!(reassembler.stats().rejected, 3);
+
+    assert_eq!(
+        Fragmenter::new(10).err(),
+        Some(FragmentError::DatagramTooSmall(10))
+    );
+    assert!(matches!(
+        fragmenter.split(&[0; 2_000]),
+        Err(FragmentError::PayloadTooLarge { len: 2_000, .. })
+    ));
+}
@@ -0,0 +1,222 @@
+use host_core::config::profile::{Codec, LockPolicy, RuntimeProfile};
+use host_core::pipeline::HostCapability;
+use host_core::protocol::control::{ControlFrame, WireCodec};
+use host_core::protocol::handshake::{
+    negotiate_version, Downgrade, HandshakeError, Hello, HostOffer, InputCapabilities,
+    ProtocolVersion, FEATURE_PROTOBUF_CODEC, MAX_TOUCH_POINTERS, PROTOCOL_VERSION,
+};
+
+fn host_capability() -> HostCapability {
+    HostCapability {
+        max_width: 2560,
+        max_height: 1440,
+        max_fps: 144,
+        codecs: vec![Codec::Hevc, Codec::H264],
+    }
+}
+
+fn phone_hello() -> Hello {
+    Hello {
+        protocol_version: PROTOCOL_VERSION,
+        app_version: "0.1.0".to_string(),
+        codecs: vec![Codec::Hevc, Codec::H264],
+        max_decode_width: 3200,
+        max_decode_height: 1440,
+        max_refresh_hz: 144,
+        input: InputCapabilities {
+            touch: true,
+            max_pointers: 10,
+            ..InputCapabilities::default()
+        },
+        features: vec![FEATURE_PROTOBUF_CODEC.to_string(), "haptics".to_string()],
+    }
+}
+
+#[test]
+fn matching_peer_is_welcomed_without_downgrades() {
+    let profile = RuntimeProfile::new(2460, 1080, 144, 80_000, Codec::Hevc, LockPolicy::TurboLock)
+        .expect("profile");
+    let capability = host_capability();
+    let offer = HostOffer {
+        profile: &profile,
+        capability: &capability,
+        features: &[FEATURE_PROTOBUF_CODEC],
+        session_nonce: None,
+    };
+
+    let negotiated = offer.negotiate(&phone_hello()).expect("compatible peer");
+
+    assert!(negotiated.downgrades.is_empty());
+    assert_eq!(negotiated.profile, profile);
+    assert_eq!(negotiated.welcome.codec, Codec::Hevc);
+    assert_eq!(
+        (negotiated.welcome.width, negotiated.welcome.fps),
+        (2460, 144)
+    );
+    assert_eq!(negotiated.welcome.features, vec![FEATURE_PROTOBUF_CODEC]);
+    assert_eq!(negotiated.welcome.session_nonce, None);
+}
+
+#[test]
+fn limited_decoder_downgrades_codec_and_stream() {
+    let profile = RuntimeProfile::new(2460, 1080, 144, 80_000, Codec::Hevc, LockPolicy::TurboLock)
+        .expect("profile");
+    let capability = host_capability();
+    let offer = HostOffer {
+        profile: &profile,
+        capability: &capability,
+        features: &[],
+        session_nonce: None,
+    };
+    let hello = Hello {
+        codecs: vec![Codec::H264],
+        max_decode_width: 1080,
+        max_decode_height: 1920,
+        max_refresh_hz: 90,
+        input: InputCapabilities {
+            touch: true,
+            max_pointers: 16,
+            ..InputCapabilities::default()
+        },
+        ..phone_hello()
+    };
+
+    let negotiated = offer.negotiate(&hello).expect("downgraded peer");
+
+    assert_eq!(negotiated.profile.codec, Codec::H264);
+    assert_eq!(
+        (
+            negotiated.profile.width,
+            negotiated.profile.height,
+            negotiated.profile.fps
+        ),
+        (1920, 1080, 90)
+    );
+    assert_eq!(negotiated.welcome.input.max_pointers, MAX_TOUCH_POINTERS);
+    assert!(negotiated.downgrades.contains(&Downgrade::Codec {
+        requested: Codec::Hevc,
+        selected: Codec::H264,
+    }));
+    assert!(negotiated
+        .downgrades
+        .iter()
+        .any(|downgrade| matches!(downgrade, Downgrade::Stream { .. })));
+}
+
+#[test]
+fn incompatible_peers_are_rejected_with_clear_errors() {
+    let profile = RuntimeProfile::new(1920, 1080, 120, 40_000, Codec::Hevc, LockPolicy::TurboLock)
+        .expect("profile");
+    let capability = host_capability();
+    let offer = HostOffer {
+        profile: &profile,
+        capability: &capability,
+        features: &[],
+        session_nonce: None,
+    };
+
+    let future = Hello {
+        protocol_version: ProtocolVersion { major: 2, minor: 0 },
+        ..phone_hello()
+    };
+    let err = offer.negotiate(&future).expect_err("major mismatch");
+    assert!(matches!(err, HandshakeError::IncompatibleVersion { .. }));
+    assert!(err.to_string().contains("2.0"));
+
+    let av1_only = Hello {
+        codecs: vec![Codec::Av1],
+        ..phone_hello()
+    };
+    assert!(matches!(
+        offer.negotiate(&av1_only),
+        Err(HandshakeError::NoCommonCodec { .. })
+    ));
+
+    let tiny = Hello {
+        max_decode_width: 854,
+        max_decode_height: 480,
+        ..phone_hello()
+    };
+    assert!(matches!(
+        offer.negotiate(&tiny),
+        Err(HandshakeError::DecoderTooSmall { .. })
+    ));
+
+    assert_eq!(
+        negotiate_version(ProtocolVersion { major: 1, minor: 4 }),
+        Ok(PROTOCOL_VERSION)
+    );
+}
+
+#[test]
+fn newer_minor_version_is_reported_as_the_peer_request() {
+    let profile = RuntimeProfile::new(1920, 1080, 120, 40_000, Codec::Hevc, LockPolicy::TurboLock)
+        .expect("profile");
+    let capability = host_capability();
+    let requested = ProtocolVersion { major: 1, minor: 4 };
+    let negotiated = HostOffer {
+        profile: &profile,
+        capability: &capability,
+        features: &[],
+        session_nonce: None,
+    }
+    .negotiate(&Hello {
+        protocol_version: requested,
+        ..phone_hello()
+    })
+    .expect("compatible peer");
+
+    assert_eq!(negotiated.welcome.protocol_version, PROTOCOL_VERSION);
+    assert!(negotiated.downgrades.contains(&Downgrade::ProtocolVersion {
+        requested,
+        agreed: PROTOCOL_VERSION,
+    }));
+}
+
+#[test]
+fn unknown_codecs_are_ignored_on_both_codecs() {
+    let mut json = serde_json::to_value(ControlFrame::Hello(phone_hello())).expect("json");
+    json["payload"]["codecs"] = serde_json::json!(["vp9", "hevc", "h264"]);
+    let bytes = serde_json::to_vec(&json).expect("bytes");
+
+    assert_eq!(
+        WireCodec::Json.decode(&bytes).expect("decode"),
+        ControlFrame::Hello(phone_hello())
+    );
+
+    let mut protobuf = WireCodec::Protobuf
+        .encode(&ControlFrame::Hello(phone_hello()))
+        .expect("encode");
+    assert!(protobuf[1] < 0x7f);
+    protobuf[1] += 3;
+    protobuf.extend([0x1a, 0x01, 0x09]);
+    assert_eq!(
+        WireCodec::Protobuf.decode(&protobuf).expect("decode"),
+        ControlFrame::Hello(phone_hello())
+    );
+}
+
+#[test]
+fn hello_and_welcome_frames_round_trip_on_both_codecs() {
+    let profile = RuntimeProfile::new(1920, 1080, 120, 40_000, Codec::Hevc, LockPolicy::TurboLock)
+        .expect("profile");
+    let capability = host_capability();
+    let negotiated = HostOffer {
+        profile: &profile,
+        capability: &capability,
+        features: &[FEATURE_PROTOBUF_CODEC],
+        session_nonce: Some("0123456789abcdef"),
+    }
+    .negotiate(&phone_hello())
+    .expect("compatible peer");
+
+    for frame in [
+        ControlFrame::Hello(phone_hello()),
+        ControlFrame::Welcome(negotiated.welcome),
+    ] {
+        for codec in [WireCodec::Json, WireCodec::Protobuf] {
+            let bytes = codec.encode(&frame).expect("encode");
+            assert_eq!(codec.decode(&bytes).expect("decode"), frame);
+        }
+    }
+}
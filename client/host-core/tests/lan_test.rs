@@ -0,0 +1,155 @@
+use host_core::protocol::control::{PointerAction, PointerEvent, Pong};
+use host_core::protocol::lan::{LanCodecError, LanMessage, CONTROL_PORT, HOST_TOUCH_PORT};
+
+fn assert_round_trip(wire: &str, expected: LanMessage) {
+    let decoded = LanMessage::decode(wire).expect("decode");
+    assert_eq!(decoded, expected);
+    assert_eq!(decoded.encode().expect("encode"), wire);
+}
+
+#[test]
+fn handshake_messages_match_android_strings() {
+    assert_round_trip(
+        "LMC_DISCOVER|DESKTOP-7Q2|51034",
+        LanMessage::Discover {
+            desktop_name: "DESKTOP-7Q2".to_string(),
+            reply_port: 51034,
+        },
+    );
+    assert_round_trip(
+        "LMC_DEVICE|Pixel 8 Pro|0.1.0|42043",
+        LanMessage::Device {
+            model: "Pixel 8 Pro".to_string(),
+            version: "0.1.0".to_string(),
+            control_port: CONTROL_PORT,
+        },
+    );
+    assert_round_trip(
+        "LMC_CONNECT_REQUEST|DESKTOP-7Q2|42044",
+        LanMessage::ConnectRequest {
+            desktop_name: "DESKTOP-7Q2".to_string(),
+            touch_port: HOST_TOUCH_PORT,
+            session_nonce: None,
+        },
+    );
+    assert_round_trip(
+        "LMC_CONNECT_REQUEST|DESKTOP-7Q2|42044|9f86d081884c7d65",
+        LanMessage::ConnectRequest {
+            desktop_name: "DESKTOP-7Q2".to_string(),
+            touch_port: HOST_TOUCH_PORT,
+            session_nonce: Some("9f86d081884c7d65".to_string()),
+        },
+    );
+    assert_round_trip(
+        "LMC_CONNECT_ACCEPT|Pixel 8 Pro",
+        LanMessage::ConnectAccept {
+            model: "Pixel 8 Pro".to_string(),
+        },
+    );
+    assert_round_trip(
+        "LMC_CONNECT_REJECT|PHONE_REJECT",
+        LanMessage::ConnectReject {
+            reason: "PHONE_REJECT".to_string(),
+        },
+    );
+    assert_round_trip(
+        "LMC_DISCONNECT|HOST",
+        LanMessage::Disconnect {
+            origin: "HOST".to_string(),
+        },
+    );
+    assert_round_trip(
+        "LMC_DISCONNECTED|APP_EXIT",
+        LanMessage::Disconnected {
+            reason: "APP_EXIT".to_string(),
+        },
+    );
+    assert_round_trip("LMC_PING", LanMessage::Ping { timestamp_ms: None });
+    assert_round_trip("LMC_PONG", LanMessage::Pong { timestamps: None });
+    assert_round_trip(
+        "LMC_PING|1700000000000",
+        LanMessage::Ping {
+            timestamp_ms: Some(1_700_000_000_000),
+        },
+    );
+    assert_round_trip(
+        "LMC_PONG|1000|1510|1512",
+        LanMessage::Pong {
+            timestamps: Some(Pong {
+                origin_timestamp_ms: 1_000,
+                receive_timestamp_ms: 1_510,
+                transmit_timestamp_ms: 1_512,
+            }),
+        },
+    );
+}
+
+#[test]
+fn touch_packet_matches_android_format() {
+    assert_round_trip(
+        "LMC_TOUCH|812|1|MOVE|0.42000|0.87500|0.6500|1718000000123",
+        LanMessage::Touch {
+            frame_id: 812,
+            event: PointerEvent {
+                pointer_id: 1,
+                action: PointerAction::Move,
+                x: 0.42,
+                y: 0.875,
+                pressure: 0.65,
+                timestamp_ms: 1_718_000_000_123,
+            },
+        },
+    );
+
+    let cancel = LanMessage::decode("LMC_TOUCH|3|0|CANCEL|1.00000|0.00000|0.0000|9\n")
+        .expect("trailing newline is tolerated");
+    assert!(matches!(
+        cancel,
+        LanMessage::Touch {
+            event: PointerEvent {
+                action: PointerAction::Cancel,
+                ..
+            },
+            ..
+        }
+    ));
+}
+
+#[test]
+fn malformed_messages_report_typed_errors() {
+    assert_eq!(LanMessage::decode("  "), Err(LanCodecError::EmptyMessage));
+    assert_eq!(
+        LanMessage::decode("LMC_HELLO|1"),
+        Err(LanCodecError::UnknownTag("LMC_HELLO".to_string()))
+    );
+    assert!(matches!(
+        LanMessage::decode("LMC_DEVICE|Pixel|0.1.0"),
+        Err(LanCodecError::FieldCount {
+            expected: 3,
+            actual: 2,
+            ..
+        })
+    ));
+    assert_eq!(
+        LanMessage::decode("LMC_CONNECT_ACCEPT|Pixel|future"),
+        Ok(LanMessage::ConnectAccept {
+            model: "Pixel".to_string()
+        })
+    );
+    assert!(matches!(
+        LanMessage::decode("LMC_TOUCH|1|0|TAP|0.5|0.5|0.5|1"),
+        Err(LanCodecError::InvalidField {
+            field: "action",
+            ..
+        })
+    ));
+
+    let err = LanMessage::ConnectRequest {
+        desktop_name: "A|B".to_string(),
+        touch_port: HOST_TOUCH_PORT,
+        session_nonce: None,
+    }
+    .encode()
+    .expect_err("separator in name");
+    assert_eq!(err, LanCodecError::ReservedSeparator("desktop_name"));
+}
@@ -0,0 +1,114 @@
+use host_core::protocol::control::{PointerAction, PointerEvent, TouchEnvelope};
+use host_core::protocol::lifecycle::{LifecyclePolicy, PointerLifecycleError, PointerValidator};
+
+fn event(pointer_id: u8, action: PointerAction) -> PointerEvent {
+    PointerEvent {
+        pointer_id,
+        action,
+        x: 0.25,
+        y: 0.75,
+        pressure: 0.6,
+        timestamp_ms: u64::from(pointer_id),
+    }
+}
+
+fn frame(events: Vec<PointerEvent>) -> TouchEnvelope {
+    TouchEnvelope {
+        frame_id: 1,
+        events,
+    }
+}
+
+fn actions(envelope: &TouchEnvelope) -> Vec<(u8, PointerAction)> {
+    envelope
+        .events
+        .iter()
+        .map(|event| (event.pointer_id, event.action))
+        .collect()
+}
+
+#[test]
+fn reject_policy_refuses_broken_sequences_atomically() {
+    let mut validator = PointerValidator::default();
+    assert_eq!(validator.policy(), LifecyclePolicy::Reject);
+
+    validator
+        .apply(frame(vec![event(0, PointerAction::Down)]))
+        .expect("down");
+
+    let err = validator
+        .apply(frame(vec![
+            event(1, PointerAction::Down),
+            event(2, PointerAction::Move),
+        ]))
+        .expect_err("move without down");
+    assert_eq!(err, PointerLifecycleError::MoveWithoutDown(2));
+    assert_eq!(validator.active_pointers(), [0]);
+    assert!(validator.is_active(0) && !validator.is_active(2));
+
+    assert_eq!(
+        validator.apply(frame(vec![event(0, PointerAction::Down)])),
+        Err(PointerLifecycleError::DuplicateDown(0))
+    );
+    assert_eq!(
+        validator.apply(frame(vec![event(3, PointerAction::Up)])),
+        Err(PointerLifecycleError::ReleaseWithoutDown(3))
+    );
+    assert_eq!(validator.stats().rejected_frames, 3);
+}
+
+#[test]
+fn reject_policy_caps_simultaneous_pointers() {
+    let mut validator = PointerValidator::new(LifecyclePolicy::Reject, 10);
+    let downs = (0..10).map(|id| event(id, PointerAction::Down)).collect();
+    validator.apply(frame(downs)).expect("ten fingers");
+
+    let err = validator
+        .apply(frame(vec![event(10, PointerAction::Down)]))
+        .expect_err("eleventh finger");
+    assert!(err.to_string().contains("limit of 10 active pointers"));
+}
+
+#[test]
+fn repair_policy_produces_a_well_formed_stream() {
+    let mut validator = PointerValidator::new(LifecyclePolicy::Repair, 2);
+
+    let repaired = validator
+        .apply(frame(vec![
+            event(0, PointerAction::Down),
+            event(1, PointerAction::Move),
+            event(0, PointerAction::Down),
+            event(1, PointerAction::Down),
+            event(2, PointerAction::Down),
+            event(2, PointerAction::Move),
+        ]))
+        .expect("repair never fails");
+    assert_eq!(
+        actions(&repaired),
+        [
+            (0, PointerAction::Down),
+            (0, PointerAction::Cancel),
+            (0, PointerAction::Down),
+            (1, PointerAction::Down),
+        ]
+    );
+
+    let released = validator
+        .apply(frame(vec![
+            event(2, PointerAction::Up),
+            event(1, PointerAction::Up),
+            event(1, PointerAction::Up),
+        ]))
+        .expect("repair never fails");
+    assert_eq!(actions(&released), [(1, PointerAction::Up)]);
+
+    let leftovers = validator.release_all();
+    assert_eq!(leftovers.len(), 1);
+    assert_eq!(leftovers[0].action, PointerAction::Cancel);
+    assert_eq!(leftovers[0].pressure, 0.0);
+
+    let stats = validator.stats();
+    assert_eq!(stats.dropped_events, 5);
+    assert_eq!(stats.synthesized_releases, 2);
+    assert_eq!(stats.rejected_frames, 0);
+}
@@ -1,3 +1,4 @@
+use host_core::geometry::Rotation;
 use host_core::input::mapping::ViewportMapping;
 
 #[test]
@@ -35,3 +36,39 @@ fn normalized_input_must_be_in_range() {
         .to_string()
         .contains("normalized coordinates must be in range [0.0, 1.0]"));
 }
+
+#[test]
+fn normalized_to_emulator_rotates_into_natural_panel_space() {
+    let mapping = ViewportMapping::for_letterboxed(1080, 2400, 2460, 1080)
+        .expect("valid mapping")
+        .with_rotation(Rotation::Deg90);
+
+    let top_left = mapping.normalized_to_emulator(0.0, 0.0).expect("maps");
+    assert_eq!((top_left.x, top_left.y), (2459, 0));
+
+    let bottom_left = mapping.normalized_to_emulator(0.0, 1.0).expect("maps");
+    assert_eq!((bottom_left.x, bottom_left.y), (0, 0));
+
+    let flipped = ViewportMapping::for_letterboxed(2460, 1080, 2460, 1080)
+        .expect("valid mapping")
+        .with_rotation(Rotation::Deg180);
+    let corner = flipped.normalized_to_emulator(0.0, 0.0).expect("maps");
+    assert_eq!((corner.x, corner.y), (2459, 1079));
+}
+
+#[test]
+fn window_to_emulator_letterboxes_rotated_content() {
+    let mapping = ViewportMapping::for_letterboxed(2400, 1200, 1920, 1080)
+        .expect("valid mapping")
+        .with_rotation(Rotation::Deg270);
+
+    let err = mapping
+        .window_to_emulator(200, 600)
+        .expect_err("portrait content leaves wide side bars");
+    assert!(err.to_string().contains("outside active emulator viewport"));
+
+    let center = mapping
+        .window_to_emulator(1200, 600)
+        .expect("inside rotated content");
+    assert_eq!((center.x, center.y), (960, 539));
+}
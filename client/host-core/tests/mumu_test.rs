@@ -1,7 +1,18 @@
-use host_core::input::mumu::adb::{find_mumu_candidate, parse_adb_devices, AdbDeviceState};
-use host_core::input::mumu::bridge::MumuBridge;
+use host_core::geometry::Rotation;
+use host_core::input::mumu::adb::{
+    find_mumu_candidate, parse_adb_devices, parse_display_rotation, parse_display_size,
+    parse_foreground_package, AdbDeviceState,
+};
+use host_core::input::mumu::bridge::{MumuBridge, MumuBridgeError};
+use host_core::input::mumu::gamepad::{GamepadControl, GamepadLayout, GamepadMapper, Stick};
+use host_core::input::mumu::keyboard::{KeyInjector, ShellInput};
 use host_core::input::mumu::minitouch::{MinitouchBuilder, TouchPoint};
-use host_core::protocol::control::{PointerAction, PointerEvent};
+use host_core::input::mumu::mouse::{MouseBackend, MouseOutput, MouseTranslator};
+use host_core::input::mumu::slots::{SlotAllocator, SlotOwner};
+use host_core::protocol::control::{
+    AnalogStick, GamepadButton, GamepadState, KeyAction, KeyEvent, MouseButton, MouseEvent,
+    MouseMotion, PointerAction, PointerEvent, SystemKey,
+};
 
 #[test]
 fn parse_adb_devices_extracts_online_rows() {
@@ -73,3 +84,397 @@ fn bridge_translates_touch_events_into_minitouch_payload() {
 
     assert_eq!(payload, "d 0 1230 540 80\nu 0\nc\n");
 }
+
+#[test]
+fn parse_foreground_package_reads_current_focus() {
+    let sample = "  mCurrentFocus=Window{4a1f2c u0 com.tencent.tmgp.pubgmhd/com.epicgames.ue4.GameActivity}\n  mFocusedApp=ActivityRecord{9c2 u0 com.android.launcher3/.Launcher t12}\n";
+
+    assert_eq!(
+        parse_foreground_package(sample).as_deref(),
+        Some("com.tencent.tmgp.pubgmhd")
+    );
+}
+
+#[test]
+fn parse_foreground_package_falls_back_to_focused_app() {
+    let sample = "  mCurrentFocus=null\n  mFocusedApp=ActivityRecord{9c2 u0 com.miHoYo.GenshinImpact/.MainActivity t12}\n";
+
+    assert_eq!(
+        parse_foreground_package(sample).as_deref(),
+        Some("com.miHoYo.GenshinImpact")
+    );
+}
+
+#[test]
+fn parse_display_rotation_reads_surface_orientation() {
+    assert_eq!(
+        parse_display_rotation("  Viewport: ...\n    SurfaceOrientation: 1\n"),
+        Some(Rotation::Deg90)
+    );
+    assert_eq!(
+        parse_display_rotation("  mCurrentRotation=ROTATION_270\n"),
+        Some(Rotation::Deg270)
+    );
+    assert_eq!(parse_display_rotation("nothing here"), None);
+}
+
+#[test]
+fn parse_display_size_prefers_override_size() {
+    assert_eq!(
+        parse_display_size("Physical size: 1080x2460\n"),
+        Some((1080, 2460))
+    );
+    assert_eq!(
+        parse_display_size("Physical size: 1080x2460\nOverride size: 900x2050\n"),
+        Some((900, 2050))
+    );
+    assert_eq!(parse_display_size("Physical size: unknown"), None);
+}
+
+#[test]
+fn bridge_maps_touches_through_emulator_rotation() {
+    let bridge = MumuBridge::new(2460, 1080).with_rotation(Rotation::Deg90);
+    let payload = bridge
+        .build_minitouch_payload(&[PointerEvent {
+            pointer_id: 1,
+            action: PointerAction::Down,
+            x: 0.25,
+            y: 0.0,
+            pressure: 0.5,
+            timestamp_ms: 1,
+        }])
+        .expect("payload should build");
+
+    assert_eq!(payload, "d 0 2460 270 50\nc\n");
+}
+
+#[test]
+fn key_injector_emits_keyevents_on_release() {
+    let mut injector = KeyInjector::default();
+    let key = |keycode, action, meta_state, timestamp_ms| KeyEvent {
+        keycode,
+        action,
+        meta_state,
+        timestamp_ms,
+    };
+
+    assert_eq!(injector.on_key(&key(66, KeyAction::Down, 0, 100)), None);
+    let enter = injector
+        .on_key(&key(66, KeyAction::Up, 0, 140))
+        .expect("tap");
+    assert_eq!(enter.shell_args(), ["input", "keyevent", "66"]);
+
+    injector.on_key(&key(4, KeyAction::Down, 0, 0));
+    let held = injector
+        .on_key(&key(4, KeyAction::Up, 0, 800))
+        .expect("hold");
+    assert_eq!(held.shell_args(), ["input", "keyevent", "--longpress", "4"]);
+
+    injector.on_key(&key(113, KeyAction::Down, 0x3000, 0));
+    injector.on_key(&key(29, KeyAction::Down, 0x3000, 10));
+    let select_all = injector
+        .on_key(&key(29, KeyAction::Up, 0x3000, 20))
+        .expect("combination");
+    assert_eq!(select_all, ShellInput::KeyCombination(vec![113, 29]));
+    assert_eq!(injector.on_key(&key(113, KeyAction::Up, 0, 30)), None);
+
+    assert_eq!(injector.on_key(&key(66, KeyAction::Up, 0, 50)), None);
+}
+
+#[test]
+fn text_input_escapes_ascii_and_broadcasts_unicode() {
+    assert_eq!(
+        ShellInput::text("gg & gl").shell_args(),
+        ["input", "text", "gg%s\\&%sgl"]
+    );
+    assert_eq!(
+        ShellInput::text("好").shell_args(),
+        [
+            "am",
+            "broadcast",
+            "-a",
+            "ADB_INPUT_B64",
+            "--es",
+            "msg",
+            "5aW9"
+        ]
+    );
+    assert_eq!(
+        ShellInput::system_key(SystemKey::Recents).shell_args(),
+        ["input", "keyevent", "187"]
+    );
+}
+
+const MOBA_LAYOUT: &str = r#"
+package = "com.example.moba"
+
+[[buttons]]
+button = "a"
+x = 0.85
+y = 0.8
+
+[[triggers]]
+trigger = "right"
+x = 0.9
+y = 0.5
+
+[[sticks]]
+stick = "left"
+x = 0.2
+y = 0.7
+radius = 0.1
+"#;
+
+#[test]
+fn gamepad_mapper_taps_buttons_and_drags_sticks_on_stable_slots() {
+    let layout = GamepadLayout::from_toml_str(MOBA_LAYOUT).expect("layout");
+    let mut mapper = GamepadMapper::new(layout, MumuBridge::new(2460, 1080));
+    let mut slots = SlotAllocator::default();
+
+    let mut state = GamepadState {
+        left_stick: AnalogStick { x: 1.0, y: 0.0 },
+        ..GamepadState::default()
+    };
+    state.press(GamepadButton::A);
+    assert_eq!(
+        mapper.on_state(&mut slots, &state).expect("press"),
+        "d 0 2091 864 50\nd 1 492 756 50\nc\nm 1 600 756 50\nc\n"
+    );
+    assert_eq!(mapper.on_state(&mut slots, &state), None);
+
+    state.release(GamepadButton::A);
+    state.right_trigger = 0.8;
+    assert_eq!(
+        mapper.on_state(&mut slots, &state).expect("swap"),
+        "u 0\nd 0 2214 540 50\nc\n"
+    );
+    assert_eq!(mapper.slot(GamepadControl::Stick(Stick::Left)), Some(1));
+
+    state.left_stick = AnalogStick { x: 0.05, y: 0.0 };
+    assert_eq!(
+        mapper.on_state(&mut slots, &state).expect("dead zone"),
+        "u 1\nc\n"
+    );
+
+    assert_eq!(mapper.release_all(&mut slots).expect("release"), "u 0\nc\n");
+    assert_eq!(mapper.release_all(&mut slots), None);
+}
+
+#[test]
+fn gamepad_layout_rejects_duplicate_and_oversized_bindings() {
+    let duplicate = format!("{MOBA_LAYOUT}\n[[buttons]]\nbutton = \"a\"\nx = 0.1\ny = 0.1\n");
+    let err = GamepadLayout::from_toml_str(&duplicate).expect_err("duplicate must fail");
+    assert!(err.to_string().contains("bound more than once"));
+
+    let oversized = MOBA_LAYOUT.replace("radius = 0.1", "radius = 0.8");
+    let err = GamepadLayout::from_toml_str(&oversized).expect_err("radius must fail");
+    assert!(err.to_string().contains("stick radius"));
+}
+
+fn mouse(motion: MouseMotion, buttons: &[MouseButton], scroll_y: f32) -> MouseEvent {
+    MouseEvent {
+        motion,
+        buttons: buttons.iter().fold(0, |mask, button| mask | button.bit()),
+        scroll_x: 0.0,
+        scroll_y,
+        hover: buttons.is_empty(),
+        timestamp_ms: 0,
+    }
+}
+
+fn minitouch(payload: &str) -> MouseOutput {
+    MouseOutput::Minitouch(payload.to_string())
+}
+
+#[test]
+fn mouse_translator_drives_touches_on_an_allocated_slot() {
+    let mut translator = MouseTranslator::new(MumuBridge::new(2460, 1080), MouseBackend::Touch);
+    let mut slots = SlotAllocator::default();
+    let center = MouseMotion::Absolute { x: 0.5, y: 0.5 };
+    let nudge = MouseMotion::Relative { dx: 0.1, dy: 0.0 };
+    let still = MouseMotion::Relative { dx: 0.0, dy: 0.0 };
+
+    assert_eq!(
+        translator.on_event(&mut slots, &mouse(center, &[MouseButton::Primary], 0.0)),
+        [minitouch("d 0 1230 540 50\nc\n")]
+    );
+    assert_eq!(
+        translator.on_event(&mut slots, &mouse(nudge, &[MouseButton::Primary], 0.0)),
+        [minitouch("m 0 1476 540 50\nc\n")]
+    );
+    assert_eq!(
+        translator.on_event(&mut slots, &mouse(still, &[], 0.0)),
+        [minitouch("u 0\nc\n")]
+    );
+
+    assert_eq!(
+        translator.on_event(&mut slots, &mouse(still, &[], 1.0)),
+        [minitouch(
+            "d 0 1476 540 50\nc\nm 0 1476 626 50\nc\nu 0\nc\n"
+        )]
+    );
+    assert_eq!(
+        translator.on_event(&mut slots, &mouse(still, &[MouseButton::Secondary], 0.0)),
+        [MouseOutput::Shell(ShellInput::system_key(SystemKey::Back))]
+    );
+    assert!(translator.release_all(&mut slots).is_empty());
+}
+
+#[test]
+fn mouse_translator_uses_real_mouse_events_when_supported() {
+    let mut translator = MouseTranslator::new(MumuBridge::new(2460, 1080), MouseBackend::Mouse);
+    let mut slots = SlotAllocator::default();
+    let center = MouseMotion::Absolute { x: 0.5, y: 0.5 };
+
+    let pressed = translator.on_event(&mut slots, &mouse(center, &[MouseButton::Primary], 0.0));
+    let [MouseOutput::Shell(down)] = pressed.as_slice() else {
+        panic!("expected a shell command, got {pressed:?}");
+    };
+    assert_eq!(
+        down.shell_args(),
+        ["input", "mouse", "motionevent", "DOWN", "1230", "540"]
+    );
+
+    let released = translator.release_all(&mut slots);
+    let [MouseOutput::Shell(up)] = released.as_slice() else {
+        panic!("expected a shell command, got {released:?}");
+    };
+    assert_eq!(up.shell_args()[3], "UP");
+
+    let scrolled = translator.on_event(&mut slots, &mouse(center, &[], -2.0));
+    let [MouseOutput::Shell(scroll)] = scrolled.as_slice() else {
+        panic!("expected a shell command, got {scrolled:?}");
+    };
+    assert_eq!(
+        scroll.shell_args(),
+        [
+            "input",
+            "mouse",
+            "scroll",
+            "1230",
+            "540",
+            "--axis",
+            "VSCROLL,-2"
+        ]
+    );
+}
+
+#[test]
+fn mouse_backend_uses_rotated_display_coordinates_and_hovers() {
+    let bridge = MumuBridge::new(1080, 2460).with_rotation(Rotation::Deg90);
+    let mut translator = MouseTranslator::new(bridge, MouseBackend::Mouse);
+    let mut slots = SlotAllocator::default();
+
+    let hovered = translator.on_event(
+        &mut slots,
+        &mouse(MouseMotion::Absolute { x: 0.25, y: 0.5 }, &[], 0.0),
+    );
+    let [MouseOutput::Shell(hover)] = hovered.as_slice() else {
+        panic!("expected a hover move, got {hovered:?}");
+    };
+    assert_eq!(
+        hover.shell_args(),
+        ["input", "mouse", "motionevent", "HOVER_MOVE", "615", "540"]
+    );
+
+    let pressed = translator.on_event(
+        &mut slots,
+        &mouse(
+            MouseMotion::Relative { dx: 0.0, dy: 0.0 },
+            &[MouseButton::Primary],
+            0.0,
+        ),
+    );
+    let [MouseOutput::Shell(down)] = pressed.as_slice() else {
+        panic!("expected a shell command, got {pressed:?}");
+    };
+    assert_eq!(
+        down.shell_args(),
+        ["input", "mouse", "motionevent", "DOWN", "615", "540"]
+    );
+}
+
+#[test]
+fn touch_gamepad_and_mouse_share_minitouch_slots() {
+    let bridge = MumuBridge::new(2460, 1080);
+    let mut slots = SlotAllocator::default();
+    let touch = |pointer_id, action| PointerEvent {
+        pointer_id,
+        action,
+        x: 0.5,
+        y: 0.5,
+        pressure: 0.5,
+        timestamp_ms: 1,
+    };
+
+    let payload = bridge
+        .build_minitouch_payload_with_slots(&mut slots, &[touch(9, PointerAction::Down)])
+        .expect("touch");
+    assert_eq!(payload, "d 0 1230 540 50\nc\n");
+
+    let layout = GamepadLayout::from_toml_str(MOBA_LAYOUT).expect("layout");
+    let mut mapper = GamepadMapper::new(layout, bridge);
+    let mut state = GamepadState::default();
+    state.press(GamepadButton::A);
+    assert_eq!(
+        mapper.on_state(&mut slots, &state).expect("press"),
+        "d 1 2091 864 50\nc\n"
+    );
+
+    let mut translator = MouseTranslator::new(bridge, MouseBackend::Touch);
+    let center = MouseMotion::Absolute { x: 0.5, y: 0.5 };
+    assert_eq!(
+        translator.on_event(&mut slots, &mouse(center, &[MouseButton::Primary], 0.0)),
+        [minitouch("d 2 1230 540 50\nc\n")]
+    );
+    assert_eq!(slots.slot(SlotOwner::Touch(9)), Some(0));
+    assert_eq!(slots.slot(SlotOwner::Mouse), Some(2));
+
+    let payload = bridge
+        .build_minitouch_payload_with_slots(&mut slots, &[touch(9, PointerAction::Up)])
+        .expect("release");
+    assert_eq!(payload, "u 0\nc\n");
+    assert_eq!(mapper.release_all(&mut slots).expect("release"), "u 1\nc\n");
+    assert_eq!(translator.release_all(&mut slots), [minitouch("u 2\nc\n")]);
+    assert_eq!(slots.in_use(), 0);
+}
+
+#[test]
+fn bridge_drops_touches_once_every_slot_is_taken() {
+    let bridge = MumuBridge::new(2460, 1080);
+    let mut slots = SlotAllocator::default();
+    for control in 0..10 {
+        slots.acquire(SlotOwner::Touch(100 + control));
+    }
+
+    let err = bridge
+        .build_minitouch_payload_with_slots(
+            &mut slots,
+            &[PointerEvent {
+                pointer_id: 0,
+                action: PointerAction::Down,
+                x: 0.5,
+                y: 0.5,
+                pressure: 0.5,
+                timestamp_ms: 1,
+            }],
+        )
+        .expect_err("no slot should be free");
+    assert!(matches!(err, MumuBridgeError::NoFreeSlot));
+}
+
+#[test]
+fn bridge_reports_moves_for_pointers_without_a_slot() {
+    let bridge = MumuBridge::new(2460, 1080);
+    let err = bridge
+        .build_minitouch_payload(&[PointerEvent {
+            pointer_id: 4,
+            action: PointerAction::Move,
+            x: 0.5,
+            y: 0.5,
+            pressure: 0.5,
+            timestamp_ms: 1,
+        }])
+        .expect_err("pointer 4 was never pressed");
+    assert!(matches!(err, MumuBridgeError::UnknownPointer(4)));
+}
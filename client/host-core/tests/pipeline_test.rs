@@ -1,4 +1,7 @@
-use host_core::config::profile::{Codec, LockPolicy, RuntimeProfile};
+use host_core::config::profile::{
+    BitDepth, ChromaSubsampling, Codec, ColorFormat, LockPolicy, Orientation, RuntimeProfile,
+};
+use host_core::encode::nvenc::{codecs_for_compute_capability, parse_compute_capability};
 use host_core::pipeline::{build_locked_pipeline, HostCapability, PipelineError};
 
 #[test]
@@ -54,3 +57,132 @@ fn pipeline_rejects_profile_when_fps_exceeds_capability() {
         }
     ));
 }
+
+#[test]
+fn pipeline_builds_av1_encoder_when_host_supports_it() {
+    let profile = RuntimeProfile::new(2460, 1080, 144, 60_000, Codec::Av1, LockPolicy::TurboLock)
+        .expect("valid profile");
+    let capability = HostCapability {
+        max_width: 2560,
+        max_height: 1440,
+        max_fps: 144,
+        codecs: vec![Codec::Av1, Codec::Hevc, Codec::H264],
+    };
+
+    let pipeline = build_locked_pipeline(&profile, &capability).expect("pipeline");
+    assert_eq!(pipeline.encoder.codec, Codec::Av1);
+    assert_eq!(pipeline.encoder.preset, "p2_low_latency_av1");
+}
+
+#[test]
+fn codec_negotiation_picks_best_shared_codec() {
+    let capability = HostCapability {
+        max_width: 2560,
+        max_height: 1440,
+        max_fps: 144,
+        codecs: vec![Codec::Av1, Codec::Hevc, Codec::H264],
+    };
+
+    assert_eq!(
+        capability.negotiate_codec(&[Codec::H264, Codec::Hevc], None),
+        Ok(Codec::Hevc)
+    );
+    assert_eq!(
+        capability.negotiate_codec(&[Codec::H264, Codec::Av1], Some(Codec::H264)),
+        Ok(Codec::H264)
+    );
+    assert_eq!(
+        capability.negotiate_codec(&[Codec::Av1, Codec::H264], Some(Codec::Hevc)),
+        Ok(Codec::Av1)
+    );
+
+    let err = HostCapability {
+        codecs: vec![Codec::H264],
+        ..capability
+    }
+    .negotiate_codec(&[Codec::Av1], None)
+    .expect_err("no shared codec");
+    assert!(matches!(err, PipelineError::NoCommonCodec { .. }));
+}
+
+#[test]
+fn portrait_profile_captures_transposed_frames() {
+    let profile = RuntimeProfile::new(2460, 1080, 120, 60_000, Codec::Hevc, LockPolicy::TurboLock)
+        .expect("valid profile")
+        .with_orientation(Orientation::Portrait);
+    let capability = HostCapability {
+        max_width: 2560,
+        max_height: 1440,
+        max_fps: 144,
+        codecs: vec![Codec::Hevc],
+    };
+
+    let pipeline = build_locked_pipeline(&profile, &capability).expect("pipeline");
+
+    assert_eq!(profile.stream_dimensions(), (1080, 2460));
+    assert_eq!(pipeline.capture.frame_size(), (1080, 2460));
+}
+
+#[test]
+fn ten_bit_profile_selects_p010_capture_surfaces() {
+    let profile = RuntimeProfile::new(1920, 1080, 120, 40_000, Codec::Hevc, LockPolicy::TurboLock)
+        .expect("valid profile")
+        .with_color(ColorFormat {
+            bit_depth: BitDepth::Ten,
+            ..ColorFormat::default()
+        })
+        .expect("HEVC supports 10-bit");
+    let capability = HostCapability {
+        max_width: 2560,
+        max_height: 1440,
+        max_fps: 144,
+        codecs: vec![Codec::Hevc],
+    };
+
+    let pipeline = build_locked_pipeline(&profile, &capability).expect("pipeline");
+
+    assert_eq!(pipeline.capture.pixel_format, "p010");
+    assert_eq!(pipeline.encoder.color.bit_depth, BitDepth::Ten);
+}
+
+#[test]
+fn pipeline_rejects_color_format_the_codec_cannot_encode() {
+    let mut profile =
+        RuntimeProfile::new(1920, 1080, 120, 40_000, Codec::Av1, LockPolicy::TurboLock)
+            .expect("valid profile");
+    profile.color.chroma = ChromaSubsampling::Yuv444;
+    let capability = HostCapability {
+        max_width: 2560,
+        max_height: 1440,
+        max_fps: 144,
+        codecs: vec![Codec::Av1],
+    };
+
+    let err = build_locked_pipeline(&profile, &capability).expect_err("AV1 4:4:4 unsupported");
+    assert!(matches!(
+        err,
+        PipelineError::ColorFormatUnsupported {
+            codec: Codec::Av1,
+            ..
+        }
+    ));
+}
+
+#[test]
+fn av1_is_only_advertised_on_gpus_with_an_av1_encoder() {
+    assert_eq!(parse_compute_capability("8.6\n8.9\n"), Some((8, 9)));
+    assert_eq!(parse_compute_capability("No devices were found"), None);
+
+    assert_eq!(
+        codecs_for_compute_capability(Some((8, 9))),
+        [Codec::Av1, Codec::Hevc, Codec::H264]
+    );
+    assert_eq!(
+        codecs_for_compute_capability(Some((8, 6))),
+        [Codec::Hevc, Codec::H264]
+    );
+    assert_eq!(
+        codecs_for_compute_capability(None),
+        [Codec::Hevc, Codec::H264]
+    );
+}
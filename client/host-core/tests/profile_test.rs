@@ -1,4 +1,9 @@
-use host_core::config::profile::{Codec, LockPolicy, RuntimeProfile};
+use host_core::config::profile::{
+    nearest_presets, recommended_bitrate_kbps, AdaptiveConfig, BitDepth, BitrateWarning,
+    ChromaSubsampling, Codec, ColorFormat, ColorRange, DegradationLadder, LockPolicy,
+    PresetCandidate, ProfileError, ResolutionMode, RuntimeProfile,
+};
+use host_core::pipeline::HostCapability;
 
 #[test]
 fn turbo_lock_profile_uses_requested_caps() {
@@ -31,3 +36,266 @@ fn invalid_resolution_reports_fallbacks() {
         .to_string()
         .contains("supported resolutions: 1280x720, 1600x900, 1920x1080, 2460x1080"));
 }
+
+#[test]
+fn rejected_fps_carries_nearest_candidates() {
+    let err = RuntimeProfile::new(1920, 1080, 100, 60_000, Codec::H264, LockPolicy::TurboLock)
+        .expect_err("100fps must be rejected");
+
+    let first = err.candidates().first().expect("at least one candidate");
+    assert_eq!((first.width, first.height), (1920, 1080));
+    assert_eq!(first.fps, 90);
+    assert!(err.to_string().contains("did you mean 1920x1080@90"));
+}
+
+#[test]
+fn negotiation_only_offers_presets_within_host_capability() {
+    let capability = HostCapability {
+        max_width: 1920,
+        max_height: 1080,
+        max_fps: 120,
+        codecs: vec![Codec::H264],
+    };
+
+    let err = RuntimeProfile::negotiate(
+        2460,
+        1080,
+        144,
+        80_000,
+        Codec::H264,
+        LockPolicy::TurboLock,
+        &capability,
+    )
+    .expect_err("profile exceeds capability");
+
+    assert!(matches!(err, ProfileError::ExceedsCapability { .. }));
+    assert_eq!(
+        err.candidates()[0],
+        PresetCandidate {
+            width: 1920,
+            height: 1080,
+            fps: 120,
+            target_bitrate_kbps: 52_000,
+        }
+    );
+    assert!(err
+        .candidates()
+        .iter()
+        .all(|c| c.width <= 1920 && c.height <= 1080 && c.fps <= 120));
+}
+
+#[test]
+fn nearest_presets_scale_bitrate_with_pixel_rate() {
+    let candidates = nearest_presets(2340, 1080, 144, 80_000, Codec::Hevc, None);
+
+    assert_eq!(candidates[0].width, 2460);
+    assert_eq!(candidates[0].fps, 144);
+    assert!(candidates[0].target_bitrate_kbps > 80_000);
+}
+
+#[test]
+fn adaptive_ladder_steps_bitrate_then_fps_then_resolution() {
+    let profile = RuntimeProfile::new(1920, 1080, 120, 40_000, Codec::Hevc, LockPolicy::Adaptive)
+        .expect("profile")
+        .with_adaptive(AdaptiveConfig {
+            floor_width: 1600,
+            floor_height: 900,
+            floor_fps: 90,
+            floor_bitrate_kbps: 20_000,
+            bitrate_step_percent: 50,
+            recovery_samples: 3,
+        })
+        .expect("adaptive floors");
+
+    let ladder = DegradationLadder::for_profile(&profile).expect("adaptive ladder");
+    let summary = ladder
+        .levels()
+        .iter()
+        .map(|l| (l.width, l.fps, l.target_bitrate_kbps))
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        summary,
+        vec![
+            (1920, 120, 40_000),
+            (1920, 120, 20_000),
+            (1920, 90, 20_000),
+            (1600, 90, 20_000),
+        ]
+    );
+}
+
+#[test]
+fn ladder_and_candidate_bitrates_stay_within_codec_bounds() {
+    let profile = RuntimeProfile::new(1920, 1080, 120, 240_000, Codec::Hevc, LockPolicy::Adaptive)
+        .expect("profile")
+        .with_adaptive(AdaptiveConfig {
+            floor_bitrate_kbps: 200_000,
+            ..AdaptiveConfig::default()
+        })
+        .expect("adaptive floors");
+
+    let ladder = DegradationLadder::for_profile(&profile).expect("adaptive ladder");
+    assert!(ladder.levels().len() > 2);
+    for level in ladder.levels() {
+        RuntimeProfile::new(
+            level.width,
+            level.height,
+            level.fps,
+            level.target_bitrate_kbps,
+            Codec::Hevc,
+            LockPolicy::Adaptive,
+        )
+        .unwrap_or_else(|err| panic!("{level:?}: {err}"));
+    }
+
+    for candidate in nearest_presets(2340, 1080, 144, 1_000_000, Codec::H264, None) {
+        RuntimeProfile::new(
+            candidate.width,
+            candidate.height,
+            candidate.fps,
+            candidate.target_bitrate_kbps,
+            Codec::H264,
+            LockPolicy::TurboLock,
+        )
+        .unwrap_or_else(|err| panic!("{candidate}: {err}"));
+    }
+}
+
+#[test]
+fn adaptive_floors_above_profile_are_rejected() {
+    let err = RuntimeProfile::new(1280, 720, 60, 10_000, Codec::H264, LockPolicy::Adaptive)
+        .expect("profile")
+        .with_adaptive(AdaptiveConfig {
+            floor_fps: 90,
+            ..AdaptiveConfig::default()
+        })
+        .expect_err("floor above profile");
+
+    assert!(matches!(err, ProfileError::InvalidAdaptiveConfig(_)));
+}
+
+#[test]
+fn custom_resolution_accepts_native_phone_aspect() {
+    let profile =
+        RuntimeProfile::new_custom(2400, 1080, 120, 60_000, Codec::Hevc, LockPolicy::TurboLock)
+            .expect("custom resolution should be valid");
+
+    assert_eq!(profile.resolution_mode, ResolutionMode::Custom);
+    assert_eq!(profile.coded_dimensions(), (2400, 1088));
+}
+
+#[test]
+fn custom_resolution_rejects_odd_and_out_of_range_sizes() {
+    let odd =
+        RuntimeProfile::new_custom(2778, 1283, 120, 60_000, Codec::H264, LockPolicy::TurboLock)
+            .expect_err("odd height must be rejected");
+    assert_eq!(odd, ProfileError::OddResolution(2778, 1283));
+
+    let tiny = RuntimeProfile::new_custom(320, 240, 60, 2_000, Codec::H264, LockPolicy::TurboLock)
+        .expect_err("too few pixels");
+    assert!(matches!(tiny, ProfileError::PixelCountOutOfRange { .. }));
+}
+
+#[test]
+fn custom_resolution_checks_aligned_size_against_capability() {
+    let profile =
+        RuntimeProfile::new_custom(2778, 1284, 120, 60_000, Codec::Hevc, LockPolicy::TurboLock)
+            .expect("custom resolution");
+    let capability = HostCapability {
+        max_width: 2800,
+        max_height: 1284,
+        max_fps: 144,
+        codecs: vec![Codec::Hevc],
+    };
+
+    let err = profile
+        .check_capability(&capability)
+        .expect_err("CTU-aligned height 1312 exceeds 1284");
+    assert!(matches!(err, ProfileError::ExceedsCapability { .. }));
+}
+
+#[test]
+fn absurd_bitrates_are_rejected_per_pixel_rate() {
+    let starved = RuntimeProfile::new(2460, 1080, 144, 500, Codec::Hevc, LockPolicy::TurboLock)
+        .expect_err("500kbps at 2460x1080@144 must fail");
+    assert!(matches!(starved, ProfileError::BitrateTooLow { .. }));
+
+    let flood = RuntimeProfile::new(
+        2460,
+        1080,
+        144,
+        2_000_000,
+        Codec::Hevc,
+        LockPolicy::TurboLock,
+    )
+    .expect_err("2Gbps must fail");
+    assert!(matches!(flood, ProfileError::BitrateTooHigh { .. }));
+}
+
+#[test]
+fn questionable_bitrate_produces_structured_warning() {
+    let profile = RuntimeProfile::new(2460, 1080, 144, 12_000, Codec::H264, LockPolicy::TurboLock)
+        .expect("low but not absurd bitrate is accepted");
+
+    let warnings = profile.bitrate_warnings();
+    assert!(matches!(
+        warnings.as_slice(),
+        [BitrateWarning::BelowRecommended {
+            bitrate_kbps: 12_000,
+            recommended_kbps: 96_000,
+            ..
+        }]
+    ));
+
+    let healthy = RuntimeProfile::new(2460, 1080, 144, 80_000, Codec::Hevc, LockPolicy::TurboLock)
+        .expect("profile");
+    assert!(healthy.bitrate_warnings().is_empty());
+}
+
+#[test]
+fn recommended_bitrate_reflects_codec_efficiency() {
+    let h264 = recommended_bitrate_kbps(1920, 1080, 120, Codec::H264);
+    let hevc = recommended_bitrate_kbps(1920, 1080, 120, Codec::Hevc);
+    let av1 = recommended_bitrate_kbps(1920, 1080, 120, Codec::Av1);
+
+    assert!(h264 > hevc && hevc > av1);
+    assert_eq!(hevc % 1000, 0);
+}
+
+#[test]
+fn color_format_is_checked_against_codec() {
+    let ten_bit = ColorFormat {
+        bit_depth: BitDepth::Ten,
+        ..ColorFormat::default()
+    };
+    let err = RuntimeProfile::new(1920, 1080, 120, 40_000, Codec::H264, LockPolicy::TurboLock)
+        .expect("profile")
+        .with_color(ten_bit)
+        .expect_err("H.264 10-bit must be rejected");
+    assert!(matches!(
+        err,
+        ProfileError::UnsupportedColorFormat {
+            codec: Codec::H264,
+            ..
+        }
+    ));
+
+    let full_444 = ColorFormat {
+        range: ColorRange::Full,
+        chroma: ChromaSubsampling::Yuv444,
+        ..ColorFormat::default()
+    };
+    assert!(
+        RuntimeProfile::new(1920, 1080, 120, 40_000, Codec::Av1, LockPolicy::TurboLock)
+            .expect("profile")
+            .with_color(full_444)
+            .is_err()
+    );
+
+    let hevc = RuntimeProfile::new(1920, 1080, 120, 40_000, Codec::Hevc, LockPolicy::TurboLock)
+        .expect("profile")
+        .with_color(full_444)
+        .expect("HEVC supports full-range 4:4:4");
+    assert_eq!(hevc.color.pixel_format(), "yuv444");
+}
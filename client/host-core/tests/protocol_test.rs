@@ -1,4 +1,10 @@
-use host_core::protocol::control::{ControlFrame, PointerAction, PointerEvent, TouchEnvelope};
+use host_core::config::profile::Codec;
+use host_core::protocol::control::{
+    is_compact_touch, AnalogStick, CompactTouchDecoder, CompactTouchEncoder, ControlCodecError,
+    ControlFrame, GamepadButton, GamepadState, KeyAction, KeyEvent, KeyframeReason, MouseButton,
+    MouseEvent, MouseMotion, PointerAction, PointerEvent, ProfileRequest, SystemKey, TouchEnvelope,
+    WireCodec,
+};
 
 #[test]
 fn touch_envelope_roundtrip_keeps_pointer_lifecycle() {
@@ -67,3 +73,547 @@ fn decode_rejects_out_of_range_touch_coordinates() {
         .to_string()
         .contains("touch event x/y must be finite and within [0.0, 1.0]"));
 }
+
+fn multi_touch_frame() -> ControlFrame {
+    ControlFrame::Touch(TouchEnvelope {
+        frame_id: 4_096,
+        events: (0..5)
+            .map(|pointer_id| PointerEvent {
+                pointer_id,
+                action: PointerAction::Move,
+                x: 0.1 * f32::from(pointer_id + 1),
+                y: 0.25,
+                pressure: 0.5,
+                timestamp_ms: 1_700_000_000_000,
+            })
+            .collect(),
+    })
+}
+
+#[test]
+fn protobuf_roundtrip_matches_json_and_is_smaller() {
+    let frame = multi_touch_frame();
+
+    let json = WireCodec::Json.encode(&frame).expect("json");
+    let protobuf = WireCodec::Protobuf.encode(&frame).expect("protobuf");
+
+    assert_eq!(
+        WireCodec::Protobuf.decode(&protobuf).expect("decode"),
+        frame
+    );
+    assert_eq!(WireCodec::Json.decode(&json).expect("decode"), frame);
+    assert!(protobuf.len() * 3 < json.len());
+
+    assert_eq!(WireCodec::detect(&json), Some(WireCodec::Json));
+    assert_eq!(WireCodec::detect(&protobuf), Some(WireCodec::Protobuf));
+}
+
+#[test]
+fn detect_skips_leading_whitespace_before_json() {
+    let frame = multi_touch_frame();
+    let mut padded = b" \r\n\t".to_vec();
+    padded.extend(WireCodec::Json.encode(&frame).expect("json"));
+
+    assert_eq!(WireCodec::detect(&padded), Some(WireCodec::Json));
+    assert_eq!(WireCodec::Json.decode(&padded).expect("decode"), frame);
+
+    let mut protobuf = vec![0x0a, b'{'];
+    protobuf.extend([0x08, 0x01].repeat(61));
+    protobuf.push(0x08);
+    assert_eq!(WireCodec::detect(&protobuf), Some(WireCodec::Protobuf));
+}
+
+#[test]
+fn protobuf_decode_applies_the_same_validation() {
+    let invalid = ControlFrame::Touch(TouchEnvelope {
+        frame_id: 1,
+        events: vec![PointerEvent {
+            pointer_id: 0,
+            action: PointerAction::Down,
+            x: 0.5,
+            y: 0.5,
+            pressure: 1.5,
+            timestamp_ms: 11,
+        }],
+    });
+
+    let err = ControlFrame::from_protobuf_bytes(&invalid.to_protobuf_bytes())
+        .expect_err("pressure out of range");
+    assert!(matches!(err, ControlCodecError::InvalidPressureRange));
+
+    let empty = ControlFrame::Touch(TouchEnvelope {
+        frame_id: 2,
+        events: Vec::new(),
+    });
+    let err = ControlFrame::from_protobuf_bytes(&empty.to_protobuf_bytes())
+        .expect_err("empty touch frame");
+    assert!(matches!(err, ControlCodecError::EmptyTouchFrame));
+
+    let err = ControlFrame::from_protobuf_bytes(&[0x0a, 0x03, 0x12, 0x01, 0x00])
+        .expect_err("truncated protobuf");
+    assert!(matches!(err, ControlCodecError::ProtobufDecode(_)));
+}
+
+#[test]
+fn protobuf_decode_rejects_unspecified_pointer_action() {
+    // touch { frame_id: 1, events { pointer_id: 1, action: UNSPECIFIED } }
+    let payload = [0x0a, 0x06, 0x08, 0x01, 0x12, 0x02, 0x08, 0x01];
+
+    let err = ControlFrame::from_protobuf_bytes(&payload).expect_err("unspecified action");
+    assert!(matches!(err, ControlCodecError::UnknownPointerAction(0)));
+}
+
+#[test]
+fn key_text_and_system_frames_round_trip_on_both_codecs() {
+    let frames = [
+        ControlFrame::Key(KeyEvent {
+            keycode: 29,
+            action: KeyAction::Down,
+            meta_state: 0x1001,
+            timestamp_ms: 500,
+        }),
+        ControlFrame::Text {
+            text: "gg 好球!".to_string(),
+        },
+        ControlFrame::SystemKey(SystemKey::Back),
+    ];
+
+    for frame in frames {
+        for codec in [WireCodec::Json, WireCodec::Protobuf] {
+            let bytes = codec.encode(&frame).expect("encode");
+            assert_eq!(codec.decode(&bytes).expect("decode"), frame);
+        }
+    }
+}
+
+#[test]
+fn key_and_text_frames_are_validated() {
+    let unknown_key = ControlFrame::Key(KeyEvent {
+        keycode: 0,
+        action: KeyAction::Up,
+        meta_state: 0,
+        timestamp_ms: 1,
+    });
+    let err = ControlFrame::from_protobuf_bytes(&unknown_key.to_protobuf_bytes())
+        .expect_err("KEYCODE_UNKNOWN must fail");
+    assert!(matches!(err, ControlCodecError::InvalidKeycode(0)));
+
+    let bad_meta = ControlFrame::Key(KeyEvent {
+        keycode: 29,
+        action: KeyAction::Down,
+        meta_state: 0x8000_0000,
+        timestamp_ms: 1,
+    });
+    let err = ControlFrame::from_wire_bytes(&bad_meta.to_wire_bytes().expect("serialize"))
+        .expect_err("unknown meta bits must fail");
+    assert!(matches!(err, ControlCodecError::InvalidMetaState(_)));
+
+    for (text, expected) in [
+        ("", "text frame cannot be empty"),
+        ("line\nbreak", "control characters"),
+    ] {
+        let frame = ControlFrame::Text {
+            text: text.to_string(),
+        };
+        let err = ControlFrame::from_wire_bytes(&frame.to_wire_bytes().expect("serialize"))
+            .expect_err("invalid text must fail");
+        assert!(err.to_string().contains(expected));
+    }
+
+    let long = ControlFrame::Text {
+        text: "a".repeat(513),
+    };
+    assert!(matches!(
+        ControlFrame::from_protobuf_bytes(&long.to_protobuf_bytes()),
+        Err(ControlCodecError::TextTooLong(513))
+    ));
+}
+
+#[test]
+fn gamepad_frames_round_trip_and_reject_out_of_range_axes() {
+    let mut state = GamepadState {
+        left_trigger: 0.25,
+        right_trigger: 1.0,
+        left_stick: AnalogStick { x: -0.5, y: 1.0 },
+        right_stick: AnalogStick { x: 0.0, y: -0.75 },
+        timestamp_ms: 900,
+        ..GamepadState::default()
+    };
+    state.press(GamepadButton::A);
+    state.press(GamepadButton::DpadLeft);
+    assert!(state.is_pressed(GamepadButton::DpadLeft));
+    assert!(!state.is_pressed(GamepadButton::B));
+
+    let frame = ControlFrame::Gamepad(state.clone());
+    for codec in [WireCodec::Json, WireCodec::Protobuf] {
+        let bytes = codec.encode(&frame).expect("encode");
+        assert_eq!(codec.decode(&bytes).expect("decode"), frame);
+    }
+
+    let unknown_bits = ControlFrame::Gamepad(GamepadState {
+        buttons: 1 << 20,
+        ..state.clone()
+    });
+    assert!(matches!(
+        ControlFrame::from_protobuf_bytes(&unknown_bits.to_protobuf_bytes()),
+        Err(ControlCodecError::UnknownGamepadButtons(_))
+    ));
+
+    let overshoot = ControlFrame::Gamepad(GamepadState {
+        right_stick: AnalogStick { x: 1.5, y: 0.0 },
+        ..state
+    });
+    let err = ControlFrame::from_wire_bytes(&overshoot.to_wire_bytes().expect("serialize"))
+        .expect_err("stick overshoot must fail");
+    assert!(err.to_string().contains("stick axes"));
+}
+
+#[test]
+fn mouse_frames_round_trip_and_follow_coordinate_rules() {
+    let click = MouseEvent {
+        motion: MouseMotion::Absolute { x: 0.25, y: 1.0 },
+        buttons: MouseButton::Primary.bit() | MouseButton::Middle.bit(),
+        scroll_x: 0.0,
+        scroll_y: -1.5,
+        hover: false,
+        timestamp_ms: 42,
+    };
+    let hover = MouseEvent {
+        motion: MouseMotion::Relative { dx: -0.02, dy: 0.5 },
+        buttons: 0,
+        hover: true,
+        ..click.clone()
+    };
+    assert!(click.is_pressed(MouseButton::Middle));
+
+    for event in [click.clone(), hover.clone()] {
+        let frame = ControlFrame::Mouse(event);
+        for codec in [WireCodec::Json, WireCodec::Protobuf] {
+            let bytes = codec.encode(&frame).expect("encode");
+            assert_eq!(codec.decode(&bytes).expect("decode"), frame);
+        }
+    }
+
+    let rejected = [
+        MouseEvent {
+            motion: MouseMotion::Absolute { x: 1.2, y: 0.5 },
+            ..click.clone()
+        },
+        MouseEvent {
+            motion: MouseMotion::Relative {
+                dx: f32::NAN,
+                dy: 0.0,
+            },
+            ..click.clone()
+        },
+        MouseEvent {
+            scroll_y: 64.0,
+            ..click.clone()
+        },
+        MouseEvent {
+            buttons: 1 << 9,
+            ..click.clone()
+        },
+        MouseEvent {
+            hover: true,
+            ..click
+        },
+    ];
+    let expected = [
+        "x/y must be finite",
+        "x/y must be finite",
+        "scroll deltas",
+        "unknown button bits",
+        "cannot have buttons pressed",
+    ];
+    for (event, expected) in rejected.into_iter().zip(expected) {
+        let frame = ControlFrame::Mouse(event);
+        let err = ControlFrame::from_protobuf_bytes(&frame.to_protobuf_bytes())
+            .expect_err("invalid mouse frame");
+        assert!(err.to_string().contains(expected), "{err}");
+    }
+}
+
+#[test]
+fn stream_control_frames_round_trip_and_reject_incomplete_profile_requests() {
+    let frames = [
+        ControlFrame::RequestKeyframe {
+            reason: KeyframeReason::DecodeError,
+            timestamp_ms: 10,
+        },
+        ControlFrame::PauseStream { timestamp_ms: 20 },
+        ControlFrame::ResumeStream { timestamp_ms: 30 },
+        ControlFrame::RequestProfile(ProfileRequest {
+            codec: Some(Codec::H264),
+            width: Some(1280),
+            height: Some(720),
+            ..ProfileRequest::default()
+        }),
+        ControlFrame::RequestProfile(ProfileRequest {
+            target_bitrate_kbps: Some(12_000),
+            ..ProfileRequest::default()
+        }),
+    ];
+    for frame in frames {
+        assert!(frame.is_stream_control());
+        assert!(!frame.is_host_originated());
+        for codec in [WireCodec::Json, WireCodec::Protobuf] {
+            let bytes = codec.encode(&frame).expect("encode");
+            assert_eq!(codec.decode(&bytes).expect("decode"), frame);
+        }
+    }
+
+    let json = br#"{"kind":"request_profile","payload":{"fps":90}}"#;
+    assert_eq!(
+        ControlFrame::from_wire_bytes(json).expect("sparse profile request"),
+        ControlFrame::RequestProfile(ProfileRequest {
+            fps: Some(90),
+            ..ProfileRequest::default()
+        })
+    );
+
+    let rejected = [
+        (ProfileRequest::default(), "at least one field"),
+        (
+            ProfileRequest {
+                width: Some(1280),
+                ..ProfileRequest::default()
+            },
+            "width and height together",
+        ),
+        (
+            ProfileRequest {
+                fps: Some(0),
+                ..ProfileRequest::default()
+            },
+            "field fps is out of range",
+        ),
+    ];
+    for (request, expected) in rejected {
+        let frame = ControlFrame::RequestProfile(request);
+        let err = ControlFrame::from_protobuf_bytes(&frame.to_protobuf_bytes())
+            .expect_err("invalid profile request");
+        assert!(err.to_string().contains(expected), "{err}");
+    }
+}
+
+fn touch_frame(frame_id: u64, events: &[(u8, PointerAction, f32, f32)]) -> TouchEnvelope {
+    TouchEnvelope {
+        frame_id,
+        events: events
+            .iter()
+            .zip(0..)
+            .map(|(&(pointer_id, action, x, y), offset)| PointerEvent {
+                pointer_id,
+                action,
+                x,
+                y,
+                pressure: 0.6,
+                timestamp_ms: 1_700_000_000_000 + frame_id * 4 + offset,
+            })
+            .collect(),
+    }
+}
+
+#[test]
+fn compact_touch_frames_use_deltas_and_decode_to_quantized_events() {
+    let frames = [
+        touch_frame(
+            40,
+            &[
+                (0, PointerAction::Down, 0.25, 0.5),
+                (1, PointerAction::Down, 0.75, 0.5),
+            ],
+        ),
+        touch_frame(
+            41,
+            &[
+                (0, PointerAction::Move, 0.2502, 0.5001),
+                (1, PointerAction::Move, 0.7497, 0.5),
+            ],
+        ),
+        touch_frame(
+            42,
+            &[
+                (0, PointerAction::Up, 0.2502, 0.5001),
+                (1, PointerAction::Move, 0.7495, 0.4998),
+            ],
+        ),
+    ];
+    let mut encoder = CompactTouchEncoder::default();
+    let mut decoder = CompactTouchDecoder::default();
+    assert!(decoder.needs_keyframe());
+
+    for (index, frame) in frames.iter().enumerate() {
+        let compact = encoder.encode(frame).expect("encode");
+        assert!(is_compact_touch(&compact));
+        let json = WireCodec::Json
+            .encode(&ControlFrame::Touch(frame.clone()))
+            .expect("json");
+        if index > 0 {
+            assert!(compact.len() < 20, "delta frame is {} bytes", compact.len());
+        }
+        assert!(compact.len() * 8 < json.len());
+
+        let decoded = decoder.decode(&compact).expect("decode");
+        assert_eq!(decoded.frame_id, frame.frame_id);
+        for (decoded, original) in decoded.events.iter().zip(&frame.events) {
+            assert_eq!(
+                (decoded.pointer_id, decoded.action, decoded.timestamp_ms),
+                (original.pointer_id, original.action, original.timestamp_ms)
+            );
+            assert!((decoded.x - original.x).abs() <= 1.0 / 65_535.0);
+            assert!((decoded.y - original.y).abs() <= 1.0 / 65_535.0);
+            assert!((decoded.pressure - original.pressure).abs() <= 1.0 / 255.0);
+        }
+
+        let reencoded = ControlFrame::Touch(decoded.clone());
+        let bytes = WireCodec::Protobuf.encode(&reencoded).expect("protobuf");
+        assert_eq!(
+            WireCodec::Protobuf.decode(&bytes).expect("valid"),
+            reencoded
+        );
+    }
+    assert!(!decoder.needs_keyframe());
+}
+
+#[test]
+fn compact_touch_decoder_waits_for_a_keyframe_after_loss() {
+    let mut encoder = CompactTouchEncoder::new(4);
+    let mut decoder = CompactTouchDecoder::default();
+    let down = touch_frame(1, &[(3, PointerAction::Down, 0.1, 0.1)]);
+    let moves = (2..=6)
+        .map(|frame_id| touch_frame(frame_id, &[(3, PointerAction::Move, 0.1, 0.2)]))
+        .collect::<Vec<_>>();
+
+    decoder
+        .decode(&encoder.encode(&down).expect("encode"))
+        .expect("keyframe");
+    let _lost = encoder.encode(&moves[0]).expect("encode");
+    let after_loss = encoder.encode(&moves[1]).expect("encode");
+    assert!(matches!(
+        decoder.decode(&after_loss),
+        Err(ControlCodecError::CompactTouchGap {
+            expected: 2,
+            frame_id: 3
+        })
+    ));
+    assert!(decoder.needs_keyframe());
+
+    let still_delta = encoder.encode(&moves[2]).expect("encode");
+    assert!(matches!(
+        decoder.decode(&still_delta),
+        Err(ControlCodecError::MissingTouchKeyframe)
+    ));
+
+    let keyframe = encoder.encode(&moves[3]).expect("encode");
+    let decoded = decoder.decode(&keyframe).expect("periodic keyframe");
+    assert_eq!(decoded.frame_id, 5);
+    assert!((decoded.events[0].y - 0.2).abs() <= 1.0 / 65_535.0);
+
+    encoder.force_keyframe();
+    let forced = encoder.encode(&moves[4]).expect("encode");
+    assert!(matches!(
+        decoder.decode(&forced[..forced.len() - 1]),
+        Err(ControlCodecError::TruncatedCompactTouch)
+    ));
+    assert_eq!(
+        decoder.decode(&forced).expect("forced keyframe").frame_id,
+        6
+    );
+    assert!(matches!(
+        encoder.encode(&touch_frame(7, &[(3, PointerAction::Move, 1.5, 0.2)])),
+        Err(ControlCodecError::InvalidCoordinateRange)
+    ));
+}
+
+#[test]
+fn compact_touch_decoder_ignores_stale_deltas_and_requests_keyframes_once() {
+    let mut encoder = CompactTouchEncoder::new(60);
+    let mut decoder = CompactTouchDecoder::default();
+    let frames = (1..=5)
+        .map(|frame_id| {
+            let action = if frame_id == 1 {
+                PointerAction::Down
+            } else {
+                PointerAction::Move
+            };
+            encoder
+                .encode(&touch_frame(frame_id, &[(0, action, 0.1, 0.1)]))
+                .expect("encode")
+        })
+        .collect::<Vec<_>>();
+
+    decoder.decode(&frames[0]).expect("keyframe");
+    decoder.decode(&frames[1]).expect("delta");
+    decoder.decode(&frames[2]).expect("delta");
+    assert!(matches!(
+        decoder.decode(&frames[1]),
+        Err(ControlCodecError::StaleCompactTouch {
+            frame_id: 2,
+            applied: 3
+        })
+    ));
+    assert!(!decoder.needs_keyframe());
+    assert_eq!(decoder.take_keyframe_request(), None);
+
+    assert!(matches!(
+        decoder.decode(&frames[4]),
+        Err(ControlCodecError::CompactTouchGap { .. })
+    ));
+    let request = decoder.take_keyframe_request().expect("request");
+    assert_eq!(
+        request,
+        ControlFrame::RequestTouchKeyframe { last_frame_id: 3 }
+    );
+    assert!(request.is_host_originated());
+    assert_eq!(decoder.take_keyframe_request(), None);
+    for codec in [WireCodec::Json, WireCodec::Protobuf] {
+        let bytes = codec.encode(&request).expect("encode");
+        assert_eq!(codec.decode(&bytes).expect("decode"), request);
+    }
+
+    encoder.force_keyframe();
+    let keyframe = encoder
+        .encode(&touch_frame(6, &[(0, PointerAction::Move, 0.2, 0.1)]))
+        .expect("encode");
+    decoder.decode(&keyframe).expect("requested keyframe");
+    assert_eq!(decoder.take_keyframe_request(), None);
+}
+
+#[test]
+fn compact_touch_decoder_rejects_old_keyframes_and_follows_frame_id_wraparound() {
+    let mut encoder = CompactTouchEncoder::new(60);
+    let mut decoder = CompactTouchDecoder::default();
+    let wrapped = |offset: u64, action| TouchEnvelope {
+        frame_id: (u64::MAX - 1).wrapping_add(offset),
+        ..touch_frame(offset, &[(0, action, 0.1, 0.1 + offset as f32 / 10.0)])
+    };
+    let keyframe = encoder
+        .encode(&wrapped(0, PointerAction::Down))
+        .expect("encode");
+    let frames = (1..=3)
+        .map(|offset| {
+            encoder
+                .encode(&wrapped(offset, PointerAction::Move))
+                .expect("encode")
+        })
+        .collect::<Vec<_>>();
+
+    decoder.decode(&keyframe).expect("keyframe");
+    for (frame, frame_id) in frames.iter().zip([u64::MAX, 0, 1]) {
+        assert_eq!(decoder.decode(frame).expect("delta").frame_id, frame_id);
+    }
+
+    assert!(matches!(
+        decoder.decode(&keyframe),
+        Err(ControlCodecError::StaleCompactTouch {
+            frame_id,
+            applied: 1
+        }) if frame_id == u64::MAX - 1
+    ));
+    assert!(!decoder.needs_keyframe());
+
+    decoder.reset();
+    decoder.decode(&keyframe).expect("keyframe after reset");
+}
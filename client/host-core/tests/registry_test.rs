@@ -0,0 +1,106 @@
+use host_core::config::loader::{ConfigFile, ProfileLayer, ProfileLoader};
+use host_core::config::profile::{Codec, LockPolicy, RuntimeProfile};
+use host_core::config::registry::ProfileRegistry;
+
+fn default_profile() -> RuntimeProfile {
+    RuntimeProfile::new(1920, 1080, 60, 30_000, Codec::Hevc, LockPolicy::TurboLock)
+        .expect("default profile")
+}
+
+#[test]
+fn registry_maps_packages_to_named_profiles() {
+    let file = ConfigFile::from_toml_str(
+        r#"
+[profiles.shooter]
+fps = 144
+resolution = "2460x1080"
+bitrate_kbps = 80000
+
+[packages]
+"com.tencent.tmgp.pubgmhd" = "shooter"
+"#,
+    )
+    .expect("valid toml");
+
+    let registry =
+        ProfileRegistry::from_config(&file, default_profile(), None).expect("registry builds");
+
+    assert_eq!(
+        registry.profile_for(Some("com.tencent.tmgp.pubgmhd")).fps,
+        144
+    );
+    assert_eq!(
+        registry.profile_for(Some("com.miHoYo.GenshinImpact")).fps,
+        60
+    );
+    assert_eq!(registry.profile_for(None).fps, 60);
+}
+
+#[test]
+fn foreground_change_reports_only_effective_profile_switches() {
+    let shooter = RuntimeProfile::new(2460, 1080, 144, 80_000, Codec::Hevc, LockPolicy::TurboLock)
+        .expect("shooter profile");
+    let mut registry = ProfileRegistry::new(default_profile());
+    registry.insert("com.tencent.tmgp.pubgmhd", shooter.clone());
+
+    assert_eq!(
+        registry.on_foreground_changed(Some("com.tencent.tmgp.pubgmhd")),
+        Some(&shooter)
+    );
+    assert_eq!(
+        registry.on_foreground_changed(Some("com.tencent.tmgp.pubgmhd")),
+        None
+    );
+    assert_eq!(
+        registry.on_foreground_changed(Some("com.miHoYo.GenshinImpact")),
+        Some(&default_profile())
+    );
+    assert_eq!(
+        registry.on_foreground_changed(Some("com.android.launcher3")),
+        None
+    );
+    assert_eq!(registry.foreground(), Some("com.android.launcher3"));
+}
+
+#[test]
+fn package_pointing_at_missing_profile_is_rejected() {
+    let file = ConfigFile::from_toml_str(
+        r#"
+[packages]
+"com.example.game" = "missing"
+"#,
+    )
+    .expect("valid toml");
+
+    assert!(ProfileRegistry::from_config(&file, default_profile(), None).is_err());
+}
+
+#[test]
+fn package_profiles_keep_env_and_cli_overrides() {
+    let file = ConfigFile::from_toml_str(
+        r#"
+[profiles.shooter]
+fps = 144
+resolution = "2460x1080"
+bitrate_kbps = 80000
+
+[packages]
+"com.tencent.tmgp.pubgmhd" = "shooter"
+"#,
+    )
+    .expect("valid toml");
+    let env = ProfileLayer::from_env_vars([("LMC_BITRATE", "70000")]).expect("env layer");
+    let mut cli = ProfileLayer::default();
+    cli.set("codec", "h264").expect("cli value");
+    let loader = ProfileLoader::new()
+        .with_file(file)
+        .with_env(env)
+        .with_cli(cli);
+
+    let registry = ProfileRegistry::from_loader(&loader, default_profile()).expect("registry");
+    let shooter = registry.profile_for(Some("com.tencent.tmgp.pubgmhd"));
+
+    assert_eq!(shooter.fps, 144);
+    assert_eq!(shooter.target_bitrate_kbps, 70_000);
+    assert_eq!(shooter.codec, Codec::H264);
+}
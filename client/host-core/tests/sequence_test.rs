@@ -0,0 +1,124 @@
+use host_core::protocol::control::{PointerAction, PointerEvent, TouchEnvelope};
+use host_core::protocol::sequence::{FrameGap, SequenceConfig, SequenceTracker};
+
+fn event(pointer_id: u8, action: PointerAction, timestamp_ms: u64) -> PointerEvent {
+    PointerEvent {
+        pointer_id,
+        action,
+        x: 0.5,
+        y: 0.5,
+        pressure: 0.8,
+        timestamp_ms,
+    }
+}
+
+fn frame(frame_id: u64, events: Vec<PointerEvent>) -> TouchEnvelope {
+    TouchEnvelope { frame_id, events }
+}
+
+fn ids(frames: &[TouchEnvelope]) -> Vec<u64> {
+    frames.iter().map(|frame| frame.frame_id).collect()
+}
+
+#[test]
+fn tracker_drops_duplicates_and_restores_order_within_window() {
+    let mut tracker = SequenceTracker::default();
+
+    let first = tracker.push(frame(1, vec![event(0, PointerAction::Down, 10)]), 0);
+    assert_eq!(ids(&first.frames), [1]);
+
+    let early = tracker.push(frame(3, vec![event(0, PointerAction::Move, 30)]), 1);
+    assert!(early.is_empty());
+    assert_eq!(tracker.buffered(), 1);
+
+    let filled = tracker.push(frame(2, vec![event(0, PointerAction::Move, 20)]), 2);
+    assert_eq!(ids(&filled.frames), [2, 3]);
+
+    assert!(tracker
+        .push(frame(2, vec![event(0, PointerAction::Move, 20)]), 3)
+        .is_empty());
+
+    let stats = tracker.stats();
+    assert_eq!(stats.received, 4);
+    assert_eq!(stats.delivered, 3);
+    assert_eq!(stats.reordered, 1);
+    assert_eq!(stats.duplicates, 1);
+    assert_eq!(stats.lost, 0);
+}
+
+#[test]
+fn tracker_declares_gap_after_timeout_and_releases_broken_pointers() {
+    let mut tracker = SequenceTracker::default();
+    tracker.push(frame(1, vec![event(0, PointerAction::Down, 10)]), 0);
+
+    let held = tracker.push(frame(3, vec![event(1, PointerAction::Down, 30)]), 5);
+    assert!(held.is_empty());
+
+    let flushed = tracker.poll(45);
+    assert_eq!(flushed.gaps, [FrameGap { first: 2, last: 2 }]);
+    assert_eq!(ids(&flushed.frames), [3]);
+    let events = &flushed.frames[0].events;
+    assert_eq!(events[0].pointer_id, 0);
+    assert_eq!(events[0].action, PointerAction::Cancel);
+    assert_eq!(events[1].pointer_id, 1);
+    assert_eq!(events[1].action, PointerAction::Down);
+    assert_eq!(tracker.active_pointers(), [1]);
+
+    assert!(tracker
+        .push(frame(2, vec![event(0, PointerAction::Up, 20)]), 50)
+        .is_empty());
+
+    let stats = tracker.stats();
+    assert_eq!(stats.lost, 1);
+    assert_eq!(stats.late, 1);
+    assert_eq!(stats.synthesized_releases, 1);
+}
+
+#[test]
+fn tracker_overflowing_reorder_window_skips_ahead() {
+    let mut tracker = SequenceTracker::new(SequenceConfig {
+        reorder_window: 2,
+        ..SequenceConfig::default()
+    });
+    tracker.push(frame(10, vec![event(0, PointerAction::Down, 0)]), 0);
+    tracker.push(frame(12, vec![event(0, PointerAction::Move, 2)]), 0);
+
+    let output = tracker.push(frame(13, vec![event(0, PointerAction::Move, 3)]), 0);
+    assert_eq!(
+        output.gaps,
+        [FrameGap {
+            first: 11,
+            last: 11
+        }]
+    );
+    assert_eq!(ids(&output.frames), [12]);
+    assert_eq!(output.frames[0].events[0].action, PointerAction::Cancel);
+    assert_eq!(tracker.stats().orphaned_events, 2);
+    assert!(tracker.active_pointers().is_empty());
+}
+
+#[test]
+fn tracker_repairs_lifecycle_and_releases_pointers_when_heartbeats_stop() {
+    let mut tracker = SequenceTracker::default();
+    tracker.push(frame(1, vec![event(0, PointerAction::Down, 0)]), 0);
+
+    let repeated = tracker.push(frame(2, vec![event(0, PointerAction::Down, 5)]), 5);
+    let actions: Vec<_> = repeated.frames[0]
+        .events
+        .iter()
+        .map(|event| event.action)
+        .collect();
+    assert_eq!(actions, [PointerAction::Cancel, PointerAction::Down]);
+
+    assert!(tracker.poll(1_000).frames.is_empty());
+    tracker.heartbeat(2_000);
+    tracker.heartbeat(4_000);
+    assert!(tracker.poll(6_000).frames.is_empty());
+    assert_eq!(tracker.active_pointers(), [0]);
+
+    let idle = tracker.poll(7_000);
+    assert_eq!(ids(&idle.frames), [2]);
+    assert_eq!(idle.frames[0].events[0].action, PointerAction::Cancel);
+    assert!(tracker.release_all().is_none());
+    assert_eq!(tracker.stats().synthesized_releases, 2);
+}
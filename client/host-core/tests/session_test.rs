@@ -1,6 +1,11 @@
-use host_core::config::profile::{Codec, LockPolicy, RuntimeProfile};
+use host_core::config::profile::{
+    AdaptiveConfig, Codec, LockPolicy, ProfileUpdate, RuntimeProfile,
+};
 use host_core::pipeline::HostCapability;
-use host_core::session::{SessionError, SessionManager, SessionState};
+use host_core::protocol::control::{ControlFrame, KeyframeReason, ProfileRequest};
+use host_core::session::{
+    ReconfigureKind, SessionError, SessionManager, SessionState, StreamAction, StreamPressure,
+};
 
 #[test]
 fn start_session_moves_state_to_running_when_capability_matches() {
@@ -57,3 +62,431 @@ fn stop_session_moves_running_state_back_to_idle() {
 
     assert_eq!(manager.state(), SessionState::Idle);
 }
+
+#[test]
+fn adaptive_session_steps_down_under_pressure_and_recovers() {
+    let profile = RuntimeProfile::new(2460, 1080, 144, 80_000, Codec::Hevc, LockPolicy::Adaptive)
+        .expect("profile")
+        .with_adaptive(AdaptiveConfig {
+            floor_bitrate_kbps: 40_000,
+            bitrate_step_percent: 50,
+            recovery_samples: 2,
+            ..AdaptiveConfig::default()
+        })
+        .expect("adaptive floors");
+    let capability = HostCapability {
+        max_width: 2560,
+        max_height: 1440,
+        max_fps: 144,
+        codecs: vec![Codec::Hevc],
+    };
+    let mut manager = SessionManager::new();
+    manager.start(profile, capability).expect("start succeeds");
+
+    let first = manager
+        .report_pressure(StreamPressure::Congested)
+        .expect("running")
+        .expect("bitrate step");
+    assert_eq!(first.target_bitrate_kbps, 40_000);
+    assert_eq!(first.fps, 144);
+
+    let second = manager
+        .report_pressure(StreamPressure::Congested)
+        .expect("running")
+        .expect("fps step");
+    assert_eq!(second.fps, 120);
+    assert_eq!(second.target_bitrate_kbps, 40_000);
+
+    assert_eq!(
+        manager
+            .report_pressure(StreamPressure::Healthy)
+            .expect("running"),
+        None
+    );
+    let recovered = manager
+        .report_pressure(StreamPressure::Healthy)
+        .expect("running")
+        .expect("step back up");
+    assert_eq!(recovered, first);
+    assert_eq!(manager.current_level(), Some(first));
+}
+
+#[test]
+fn live_reconfigure_keeps_the_degradation_step_until_bitrate_or_rebuild() {
+    let profile = RuntimeProfile::new(2460, 1080, 144, 80_000, Codec::Hevc, LockPolicy::Adaptive)
+        .expect("profile")
+        .with_adaptive(AdaptiveConfig {
+            floor_bitrate_kbps: 40_000,
+            bitrate_step_percent: 50,
+            ..AdaptiveConfig::default()
+        })
+        .expect("adaptive floors");
+    let capability = HostCapability {
+        max_width: 2560,
+        max_height: 1440,
+        max_fps: 144,
+        codecs: vec![Codec::Hevc],
+    };
+    let mut manager = SessionManager::new();
+    manager.start(profile, capability).expect("start succeeds");
+    for _ in 0..2 {
+        manager
+            .report_pressure(StreamPressure::Congested)
+            .expect("running")
+            .expect("step down");
+    }
+    let degraded = manager.current_level().expect("level");
+    assert_eq!(degraded.fps, 120);
+
+    let live = manager
+        .reconfigure(ProfileUpdate {
+            keyframe_interval_ms: Some(1_000),
+            ..ProfileUpdate::default()
+        })
+        .expect("live update");
+    assert_eq!(live.kind, ReconfigureKind::Live);
+    assert_eq!(manager.current_level(), Some(degraded));
+
+    manager
+        .reconfigure(ProfileUpdate {
+            target_bitrate_kbps: Some(70_000),
+            ..ProfileUpdate::default()
+        })
+        .expect("bitrate tweak");
+    let restored = manager.current_level().expect("level");
+    assert_eq!((restored.fps, restored.target_bitrate_kbps), (144, 70_000));
+
+    for _ in 0..2 {
+        manager
+            .report_pressure(StreamPressure::Congested)
+            .expect("running")
+            .expect("step down");
+    }
+    let rebuilt = manager
+        .reconfigure(ProfileUpdate {
+            fps: Some(120),
+            ..ProfileUpdate::default()
+        })
+        .expect("rebuild");
+    assert_eq!(rebuilt.kind, ReconfigureKind::Rebuild);
+    let level = manager.current_level().expect("level");
+    assert_eq!((level.fps, level.target_bitrate_kbps), (120, 70_000));
+}
+
+#[test]
+fn turbo_lock_session_ignores_pressure_reports() {
+    let profile = RuntimeProfile::new(1920, 1080, 120, 60_000, Codec::H264, LockPolicy::TurboLock)
+        .expect("profile");
+    let capability = HostCapability {
+        max_width: 2560,
+        max_height: 1440,
+        max_fps: 144,
+        codecs: vec![Codec::H264],
+    };
+    let mut manager = SessionManager::new();
+    manager.start(profile, capability).expect("start succeeds");
+
+    let changed = manager
+        .report_pressure(StreamPressure::Congested)
+        .expect("running");
+
+    assert_eq!(changed, None);
+    assert_eq!(
+        manager
+            .current_level()
+            .map(|level| level.target_bitrate_kbps),
+        Some(60_000)
+    );
+}
+
+#[test]
+fn apply_profile_rebuilds_pipeline_while_running() {
+    let capability = HostCapability {
+        max_width: 2560,
+        max_height: 1440,
+        max_fps: 144,
+        codecs: vec![Codec::Hevc],
+    };
+    let casual = RuntimeProfile::new(1920, 1080, 60, 30_000, Codec::Hevc, LockPolicy::TurboLock)
+        .expect("profile");
+    let shooter = RuntimeProfile::new(2460, 1080, 144, 80_000, Codec::Hevc, LockPolicy::TurboLock)
+        .expect("profile");
+    let mut manager = SessionManager::new();
+
+    assert!(matches!(
+        manager.apply_profile(shooter.clone()),
+        Err(SessionError::NotRunning(SessionState::Idle))
+    ));
+
+    let started = manager.start(casual, capability).expect("start succeeds");
+    let applied = manager.apply_profile(shooter.clone()).expect("apply");
+
+    assert_eq!(applied.kind, ReconfigureKind::Rebuild);
+    assert_eq!(applied.session.pipeline.capture.fps, 144);
+    assert_eq!(
+        applied.session.pipeline.transport,
+        started.pipeline.transport
+    );
+    assert_eq!(manager.profile(), Some(&shooter));
+    assert_eq!(manager.state(), SessionState::Running);
+
+    let tuned = shooter
+        .clone()
+        .with_keyframe_interval_ms(1_000)
+        .expect("interval");
+    let live = manager.apply_profile(tuned).expect("apply");
+    assert_eq!(live.kind, ReconfigureKind::Live);
+    assert_eq!(
+        manager
+            .apply_profile(live.session.profile)
+            .expect("apply")
+            .kind,
+        ReconfigureKind::Unchanged
+    );
+}
+
+#[test]
+fn reconfigure_retunes_bitrate_and_keyframe_interval_live() {
+    let capability = HostCapability {
+        max_width: 2560,
+        max_height: 1440,
+        max_fps: 144,
+        codecs: vec![Codec::Hevc, Codec::H264],
+    };
+    let profile = RuntimeProfile::new(1920, 1080, 120, 40_000, Codec::Hevc, LockPolicy::TurboLock)
+        .expect("profile");
+    let mut manager = SessionManager::new();
+    let started = manager.start(profile, capability).expect("start succeeds");
+
+    let live = manager
+        .reconfigure(ProfileUpdate {
+            target_bitrate_kbps: Some(30_000),
+            keyframe_interval_ms: Some(1_000),
+            ..ProfileUpdate::default()
+        })
+        .expect("live update");
+
+    assert_eq!(live.kind, ReconfigureKind::Live);
+    assert_eq!(live.session.pipeline.encoder.target_bitrate_kbps, 30_000);
+    assert_eq!(live.session.pipeline.encoder.gop_frames, 120);
+    assert_eq!(live.session.pipeline.capture, started.pipeline.capture);
+    assert_eq!(manager.state(), SessionState::Running);
+
+    let rebuilt = manager
+        .reconfigure(ProfileUpdate {
+            codec: Some(Codec::H264),
+            resolution: Some((1600, 900)),
+            ..ProfileUpdate::default()
+        })
+        .expect("rebuild update");
+
+    assert_eq!(rebuilt.kind, ReconfigureKind::Rebuild);
+    assert_eq!(rebuilt.session.pipeline.encoder.codec, Codec::H264);
+    assert_eq!(rebuilt.session.pipeline.capture.width, 1600);
+    assert_eq!(rebuilt.session.profile.target_bitrate_kbps, 30_000);
+    assert_eq!(rebuilt.session.profile.keyframe_interval_ms, 1_000);
+    assert_eq!(manager.state(), SessionState::Running);
+
+    let unchanged = manager
+        .reconfigure(ProfileUpdate {
+            fps: Some(120),
+            ..ProfileUpdate::default()
+        })
+        .expect("no-op update");
+    assert_eq!(unchanged.kind, ReconfigureKind::Unchanged);
+}
+
+#[test]
+fn rejected_reconfigure_keeps_running_session_intact() {
+    let capability = HostCapability {
+        max_width: 1920,
+        max_height: 1080,
+        max_fps: 144,
+        codecs: vec![Codec::Hevc],
+    };
+    let profile = RuntimeProfile::new(1920, 1080, 120, 40_000, Codec::Hevc, LockPolicy::TurboLock)
+        .expect("profile");
+    let mut manager = SessionManager::new();
+    manager
+        .start(profile.clone(), capability)
+        .expect("start succeeds");
+
+    let unsupported = manager
+        .reconfigure(ProfileUpdate {
+            codec: Some(Codec::Av1),
+            ..ProfileUpdate::default()
+        })
+        .expect_err("host cannot encode AV1");
+    assert!(matches!(unsupported, SessionError::Pipeline(_)));
+
+    let invalid = manager
+        .reconfigure(ProfileUpdate {
+            keyframe_interval_ms: Some(50),
+            ..ProfileUpdate::default()
+        })
+        .expect_err("keyframe interval too short");
+    assert!(matches!(invalid, SessionError::Profile(_)));
+
+    assert_eq!(manager.state(), SessionState::Running);
+    assert_eq!(manager.profile(), Some(&profile));
+}
+
+#[test]
+fn stream_control_frames_drive_keyframes_and_pause_state() {
+    let capability = HostCapability {
+        max_width: 2560,
+        max_height: 1440,
+        max_fps: 144,
+        codecs: vec![Codec::Hevc, Codec::H264],
+    };
+    let profile = RuntimeProfile::new(1920, 1080, 120, 40_000, Codec::Hevc, LockPolicy::TurboLock)
+        .expect("profile");
+    let mut manager = SessionManager::new();
+    let keyframe = ControlFrame::RequestKeyframe {
+        reason: KeyframeReason::DecodeError,
+        timestamp_ms: 0,
+    };
+
+    assert!(matches!(
+        manager.handle_stream_control(&keyframe, 0),
+        Err(SessionError::NotRunning(SessionState::Idle))
+    ));
+    manager.start(profile, capability).expect("start succeeds");
+
+    assert_eq!(
+        manager
+            .handle_stream_control(&keyframe, 1_000)
+            .expect("keyframe"),
+        [StreamAction::ForceKeyframe(KeyframeReason::DecodeError)]
+    );
+    assert!(manager
+        .handle_stream_control(&keyframe, 1_100)
+        .expect("coalesced")
+        .is_empty());
+    assert_eq!(
+        manager
+            .handle_stream_control(&keyframe, 1_250)
+            .expect("keyframe")
+            .len(),
+        1
+    );
+
+    let pause = ControlFrame::PauseStream {
+        timestamp_ms: 2_000,
+    };
+    assert_eq!(
+        manager.handle_stream_control(&pause, 2_000).expect("pause"),
+        [StreamAction::Pause]
+    );
+    assert_eq!(manager.state(), SessionState::Paused);
+    assert!(manager
+        .handle_stream_control(&pause, 2_010)
+        .expect("pause")
+        .is_empty());
+    assert!(manager
+        .handle_stream_control(&keyframe, 3_000)
+        .expect("paused")
+        .is_empty());
+    assert!(matches!(
+        manager.report_pressure(StreamPressure::Congested),
+        Err(SessionError::NotRunning(SessionState::Paused))
+    ));
+
+    let resume = ControlFrame::ResumeStream {
+        timestamp_ms: 4_000,
+    };
+    assert_eq!(
+        manager
+            .handle_stream_control(&resume, 4_000)
+            .expect("resume"),
+        [
+            StreamAction::Resume,
+            StreamAction::ForceKeyframe(KeyframeReason::StreamStart)
+        ]
+    );
+    assert_eq!(manager.state(), SessionState::Running);
+    assert!(manager
+        .handle_stream_control(&keyframe, 4_100)
+        .expect("coalesced")
+        .is_empty());
+
+    assert!(matches!(
+        manager.handle_stream_control(&ControlFrame::Ping { timestamp_ms: 0 }, 5_000),
+        Err(SessionError::NotStreamControl)
+    ));
+    manager.stop();
+    assert!(matches!(
+        manager.handle_stream_control(&pause, 6_000),
+        Err(SessionError::InvalidTransition(
+            SessionState::Idle,
+            SessionState::Paused
+        ))
+    ));
+}
+
+#[test]
+fn profile_request_frames_reconfigure_the_running_session() {
+    let capability = HostCapability {
+        max_width: 2560,
+        max_height: 1440,
+        max_fps: 144,
+        codecs: vec![Codec::Hevc, Codec::H264],
+    };
+    let profile = RuntimeProfile::new(1920, 1080, 120, 40_000, Codec::Hevc, LockPolicy::TurboLock)
+        .expect("profile");
+    let mut manager = SessionManager::new();
+    manager.start(profile, capability).expect("start succeeds");
+
+    let request = |request: ProfileRequest| ControlFrame::RequestProfile(request);
+    let actions = manager
+        .handle_stream_control(
+            &request(ProfileRequest {
+                width: Some(1600),
+                height: Some(900),
+                fps: Some(60),
+                ..ProfileRequest::default()
+            }),
+            1_000,
+        )
+        .expect("rebuild");
+    let [StreamAction::Reconfigured(rebuilt)] = actions.as_slice() else {
+        panic!("expected a reconfiguration, got {actions:?}");
+    };
+    assert_eq!(rebuilt.kind, ReconfigureKind::Rebuild);
+    assert_eq!(rebuilt.session.pipeline.capture.width, 1600);
+    assert_eq!(rebuilt.session.profile.fps, 60);
+
+    let same = request(ProfileRequest {
+        fps: Some(60),
+        ..ProfileRequest::default()
+    });
+    assert!(manager
+        .handle_stream_control(&same, 2_000)
+        .expect("no-op")
+        .is_empty());
+
+    let invalid = request(ProfileRequest {
+        target_bitrate_kbps: Some(1),
+        ..ProfileRequest::default()
+    });
+    assert!(matches!(
+        manager.handle_stream_control(&invalid, 3_000),
+        Err(SessionError::Profile(_))
+    ));
+
+    manager.pause().expect("pause");
+    let paused = manager
+        .handle_stream_control(
+            &request(ProfileRequest {
+                fps: Some(90),
+                ..ProfileRequest::default()
+            }),
+            4_000,
+        )
+        .expect("reconfigure while paused");
+    assert!(matches!(
+        paused.as_slice(),
+        [StreamAction::Reconfigured(reconfigured)] if reconfigured.session.profile.fps == 90
+    ));
+    assert_eq!(manager.state(), SessionState::Paused);
+}
@@ -0,0 +1,234 @@
+use std::collections::HashSet;
+
+use host_core::protocol::control::{ControlCodecError, ControlFrame, PointerAction};
+use host_core::protocol::lan::{LanCodecError, LanMessage};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+const CONTROL_FRAME_VECTORS: &str = include_str!(concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/../../shared/vectors/control_frames.json"
+));
+const LAN_MESSAGE_VECTORS: &str = include_str!(concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/../../shared/vectors/lan_messages.json"
+));
+const SUPPORTED_VERSION: u32 = 1;
+
+#[derive(Debug, Deserialize)]
+struct VectorFile<T> {
+    version: u32,
+    vectors: Vec<T>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ControlVector {
+    name: String,
+    wire: String,
+    expect: ControlExpectation,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum ControlExpectation {
+    Frame(Value),
+    Error(String),
+}
+
+#[derive(Debug, Deserialize)]
+struct LanVector {
+    name: String,
+    wire: String,
+    #[serde(default)]
+    canonical: bool,
+    expect: LanExpectation,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum LanExpectation {
+    Message {
+        message: Value,
+    },
+    Error {
+        error: String,
+        #[serde(default)]
+        field: Option<String>,
+    },
+}
+
+fn load<T: for<'de> Deserialize<'de>>(source: &str) -> Vec<T> {
+    let file: VectorFile<T> = serde_json::from_str(source).expect("vector file parses");
+    assert_eq!(
+        file.version, SUPPORTED_VERSION,
+        "unsupported vector version"
+    );
+    assert!(!file.vectors.is_empty());
+    file.vectors
+}
+
+fn control_error_code(err: &ControlCodecError) -> &'static str {
+    match err {
+        ControlCodecError::EmptyPayload => "empty_payload",
+        ControlCodecError::Deserialize(_) => "deserialize",
+        ControlCodecError::EmptyTouchFrame => "empty_touch_frame",
+        ControlCodecError::TooManyEvents(_) => "too_many_events",
+        ControlCodecError::InvalidCoordinateRange => "invalid_coordinate_range",
+        ControlCodecError::InvalidPressureRange => "invalid_pressure_range",
+        ControlCodecError::InvalidKeycode(_) => "invalid_keycode",
+        ControlCodecError::InvalidMetaState(_) => "invalid_meta_state",
+        ControlCodecError::EmptyText => "empty_text",
+        ControlCodecError::TextTooLong(_) => "text_too_long",
+        ControlCodecError::TextControlCharacter => "text_control_character",
+        ControlCodecError::UnknownGamepadButtons(_) => "unknown_gamepad_buttons",
+        ControlCodecError::InvalidTriggerRange => "invalid_trigger_range",
+        ControlCodecError::InvalidStickRange => "invalid_stick_range",
+        ControlCodecError::UnknownMouseButtons(_) => "unknown_mouse_buttons",
+        ControlCodecError::InvalidScrollDelta => "invalid_scroll_delta",
+        ControlCodecError::HoverWithButtons => "hover_with_buttons",
+        ControlCodecError::EmptyProfileRequest => "empty_profile_request",
+        ControlCodecError::PartialResolution => "partial_resolution",
+        ControlCodecError::FieldOutOfRange(_) => "field_out_of_range",
+        ControlCodecError::EmptyDisplay => "empty_display",
+        ControlCodecError::EmptyContentRect => "empty_content_rect",
+        ControlCodecError::ContentOutsideFrame => "content_outside_frame",
+        _ => "other",
+    }
+}
+
+fn lan_error_code(err: &LanCodecError) -> (&'static str, Option<&'static str>) {
+    match err {
+        LanCodecError::EmptyMessage => ("empty_message", None),
+        LanCodecError::UnknownTag(_) => ("unknown_tag", None),
+        LanCodecError::FieldCount { .. } => ("field_count", None),
+        LanCodecError::InvalidField { field, .. } => ("invalid_field", Some(field)),
+        LanCodecError::ReservedSeparator(field) => ("reserved_separator", Some(field)),
+    }
+}
+
+fn action_name(action: PointerAction) -> &'static str {
+    match action {
+        PointerAction::Down => "DOWN",
+        PointerAction::Move => "MOVE",
+        PointerAction::Up => "UP",
+        PointerAction::Cancel => "CANCEL",
+    }
+}
+
+fn lan_fields(message: &LanMessage) -> Value {
+    let mut fields = match message {
+        LanMessage::Discover {
+            desktop_name,
+            reply_port,
+        } => json!({ "desktop_name": desktop_name, "reply_port": reply_port }),
+        LanMessage::Device {
+            model,
+            version,
+            control_port,
+        } => json!({ "model": model, "version": version, "control_port": control_port }),
+        LanMessage::ConnectRequest {
+            desktop_name,
+            touch_port,
+            session_nonce,
+        } => json!({
+            "desktop_name": desktop_name,
+            "touch_port": touch_port,
+            "session_nonce": session_nonce,
+        }),
+        LanMessage::ConnectAccept { model } => json!({ "model": model }),
+        LanMessage::ConnectReject { reason } | LanMessage::Disconnected { reason } => {
+            json!({ "reason": reason })
+        }
+        LanMessage::Disconnect { origin } => json!({ "origin": origin }),
+        LanMessage::Ping { timestamp_ms } => json!({ "timestamp_ms": timestamp_ms }),
+        LanMessage::Pong { timestamps } => json!({ "timestamps": timestamps }),
+        LanMessage::Touch { frame_id, event } => json!({
+            "frame_id": frame_id,
+            "pointer_id": event.pointer_id,
+            "action": action_name(event.action),
+            "x": event.x,
+            "y": event.y,
+            "pressure": event.pressure,
+            "timestamp_ms": event.timestamp_ms,
+        }),
+    };
+    fields["tag"] = json!(message.tag());
+    fields
+}
+
+#[test]
+fn shared_control_frame_vectors_decode_as_specified() {
+    let vectors = load::<ControlVector>(CONTROL_FRAME_VECTORS);
+    let mut names = HashSet::new();
+
+    for vector in vectors {
+        assert!(
+            names.insert(vector.name.clone()),
+            "duplicate {}",
+            vector.name
+        );
+        let decoded = ControlFrame::from_wire_bytes(vector.wire.as_bytes());
+
+        match (&vector.expect, decoded) {
+            (ControlExpectation::Frame(expected), Ok(frame)) => {
+                let actual = serde_json::to_value(&frame).expect("serialize");
+                assert_eq!(&actual, expected, "{}", vector.name);
+
+                let reencoded = frame.to_wire_bytes().expect("encode");
+                let again = ControlFrame::from_wire_bytes(&reencoded).expect("re-decode");
+                assert_eq!(again, frame, "{}", vector.name);
+                let protobuf = ControlFrame::from_protobuf_bytes(&frame.to_protobuf_bytes())
+                    .expect("protobuf");
+                assert_eq!(protobuf, frame, "{}", vector.name);
+            }
+            (ControlExpectation::Error(expected), Err(err)) => {
+                assert_eq!(control_error_code(&err), expected, "{}: {err}", vector.name);
+            }
+            (expected, actual) => {
+                panic!("{}: expected {expected:?}, got {actual:?}", vector.name)
+            }
+        }
+    }
+}
+
+#[test]
+fn shared_lan_message_vectors_decode_as_specified() {
+    let vectors = load::<LanVector>(LAN_MESSAGE_VECTORS);
+    let mut names = HashSet::new();
+
+    for vector in vectors {
+        assert!(
+            names.insert(vector.name.clone()),
+            "duplicate {}",
+            vector.name
+        );
+        let decoded = LanMessage::decode(&vector.wire);
+
+        match (&vector.expect, decoded) {
+            (LanExpectation::Message { message: expected }, Ok(message)) => {
+                assert_eq!(&lan_fields(&message), expected, "{}", vector.name);
+
+                let encoded = message.encode().expect("encode");
+                if vector.canonical {
+                    assert_eq!(encoded, vector.wire, "{}", vector.name);
+                }
+                assert_eq!(
+                    LanMessage::decode(&encoded).expect("re-decode"),
+                    message,
+                    "{}",
+                    vector.name
+                );
+            }
+            (LanExpectation::Error { error, field }, Err(err)) => {
+                let (code, actual_field) = lan_error_code(&err);
+                assert_eq!(code, error, "{}: {err}", vector.name);
+                if let Some(field) = field {
+                    assert_eq!(actual_field, Some(field.as_str()), "{}", vector.name);
+                }
+            }
+            (expected, actual) => {
+                panic!("{}: expected {expected:?}, got {actual:?}", vector.name)
+            }
+        }
+    }
+}
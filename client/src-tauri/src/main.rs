@@ -1,24 +1,81 @@
+use std::collections::hash_map::RandomState;
 use std::collections::{HashMap, HashSet};
+use std::hash::{BuildHasher, Hasher};
 use std::net::{SocketAddr, UdpSocket};
 use std::path::{Path, PathBuf};
 use std::process::Command;
 use std::sync::{Arc, Mutex};
 use std::thread;
-use std::time::{Duration, Instant};
-
-use host_core::config::profile::{Codec, LockPolicy, RuntimeProfile};
-use host_core::input::mumu::bridge::MumuBridge;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use host_core::config::loader::{
+    parse_lock_policy, parse_resolution, ConfigFile, ProfileLayer, ProfileLoader, ENV_CONFIG_PATH,
+};
+use host_core::config::profile::{Codec, ProfileUpdate, RuntimeProfile};
+use host_core::config::registry::ProfileRegistry;
+use host_core::encode::nvenc;
+use host_core::geometry::Rotation;
+use host_core::input::mumu::bridge::{MinitouchProcess, MumuBridge};
+use host_core::input::mumu::gamepad::{GamepadLayout, GamepadMapper};
+use host_core::input::mumu::keyboard::{KeyInjector, ShellInput};
+use host_core::input::mumu::mouse::{MouseBackend, MouseOutput, MouseTranslator};
+use host_core::input::mumu::slots::SlotAllocator;
 use host_core::pipeline::HostCapability;
-use host_core::protocol::control::PointerAction;
-use host_core::session::{SessionManager, SessionState};
+use host_core::protocol::auth::{Direction, FrameAuthenticator, SessionKey};
+use host_core::protocol::clock::ClockEstimator;
+use host_core::protocol::control::{
+    is_compact_touch, CompactTouchDecoder, ControlFrame, PointerAction, PointerEvent,
+    TouchEnvelope, WireCodec,
+};
+use host_core::protocol::display::DisplayAnnouncer;
+use host_core::protocol::fragment::Reassembler;
+use host_core::protocol::handshake::{Hello, HostOffer, FEATURE_PROTOBUF_CODEC};
+use host_core::protocol::lan::{LanMessage, DISCOVERY_PORT, HOST_TOUCH_PORT};
+use host_core::protocol::sequence::{SequenceTracker, DEFAULT_REORDER_TIMEOUT_MS};
+use host_core::session::{ReconfigureKind, Reconfigured, SessionManager, SessionState};
 use serde::{Deserialize, Serialize};
 
 struct HostState {
     session: Mutex<SessionManager>,
+    registry: Mutex<Option<ProfileRegistry>>,
     connection: Mutex<Option<LanDevice>>,
+    peer: Mutex<Option<Hello>>,
+    pairing: Mutex<PairingSettings>,
+    clock: Mutex<ClockEstimator>,
     touch_runtime: Arc<Mutex<TouchRuntime>>,
 }
 
+#[derive(Debug, Clone, Default)]
+struct PairingSettings {
+    key: Option<SessionKey>,
+    allow_unauthenticated: bool,
+}
+
+impl PairingSettings {
+    fn from_env() -> Self {
+        Self {
+            key: std::env::var(ENV_PAIRING_KEY)
+                .ok()
+                .and_then(|key| SessionKey::from_base64(&key).ok()),
+            allow_unauthenticated: std::env::var(ENV_ALLOW_UNAUTHENTICATED)
+                .is_ok_and(|value| matches!(value.trim(), "1" | "true")),
+        }
+    }
+
+    fn authenticator(
+        &self,
+        session_nonce: Option<&str>,
+        direction: Direction,
+    ) -> Option<FrameAuthenticator> {
+        let key = self
+            .key
+            .as_ref()?
+            .for_session(session_nonce?.as_bytes())
+            .for_direction(direction);
+        Some(FrameAuthenticator::new(key, WireCodec::default()))
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct SessionPayload {
@@ -26,6 +83,33 @@ struct SessionPayload {
     resolution: String,
     bitrate_kbps: u32,
     lock_policy: String,
+    #[serde(default)]
+    profile: Option<String>,
+    #[serde(default)]
+    warnings: Vec<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ReconfigurePayload {
+    #[serde(default)]
+    fps: Option<u16>,
+    #[serde(default)]
+    resolution: Option<String>,
+    #[serde(default)]
+    bitrate_kbps: Option<u32>,
+    #[serde(default)]
+    keyframe_interval_ms: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ReconfigureResponse {
+    rebuilt: bool,
+    fps: u16,
+    resolution: String,
+    bitrate_kbps: u32,
+    keyframe_interval_ms: u32,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -58,29 +142,63 @@ enum SessionStateValue {
     Idle,
     Starting,
     Running,
+    Paused,
 }
 
 #[derive(Debug)]
 struct TouchRuntime {
     connected_device_ip: Option<String>,
+    control_port: Option<u16>,
     mumu_serial: Option<String>,
     adb_path: Option<String>,
     down_points: HashMap<u8, (u32, u32)>,
     last_points: HashMap<u8, (u32, u32)>,
     target_width: u32,
     target_height: u32,
+    rotation: Rotation,
+    authenticator: Option<FrameAuthenticator>,
+    outbound_authenticator: Option<FrameAuthenticator>,
+    allow_unauthenticated: bool,
+    sequence: SequenceTracker,
+    reassembler: Reassembler,
+    compact_touch: CompactTouchDecoder,
+    display: DisplayAnnouncer,
+    slots: SlotAllocator,
+    keys: KeyInjector,
+    mouse: MouseTranslator,
+    gamepad: Option<GamepadMapper>,
+    minitouch: Option<MinitouchProcess>,
 }
 
 impl Default for TouchRuntime {
     fn default() -> Self {
+        let bridge = MumuBridge::new(2460, 1080);
+        let gamepad = std::env::var(ENV_GAMEPAD_LAYOUT)
+            .ok()
+            .and_then(|path| GamepadLayout::load(Path::new(&path)).ok())
+            .map(|layout| GamepadMapper::new(layout, bridge));
         Self {
             connected_device_ip: None,
+            control_port: None,
             mumu_serial: None,
             adb_path: None,
             down_points: HashMap::new(),
             last_points: HashMap::new(),
             target_width: 2460,
             target_height: 1080,
+            rotation: Rotation::Deg0,
+            authenticator: None,
+            outbound_authenticator: None,
+            allow_unauthenticated: false,
+            sequence: SequenceTracker::default(),
+            reassembler: Reassembler::default(),
+            compact_touch: CompactTouchDecoder::default(),
+            display: DisplayAnnouncer::default(),
+            slots: SlotAllocator::default(),
+            keys: KeyInjector::default(),
+            mouse: MouseTranslator::new(bridge, MouseBackend::Mouse),
+            gamepad,
+            minitouch: None,
         }
     }
 }
@@ -88,9 +206,90 @@ impl Default for TouchRuntime {
 impl TouchRuntime {
     fn clear_connection(&mut self) {
         self.connected_device_ip = None;
+        self.control_port = None;
         self.mumu_serial = None;
         self.down_points.clear();
         self.last_points.clear();
+        self.authenticator = None;
+        self.outbound_authenticator = None;
+        self.allow_unauthenticated = false;
+        self.sequence.reset();
+        self.reassembler.reset();
+        self.compact_touch.reset();
+        self.release_inputs();
+    }
+
+    fn release_inputs(&mut self) {
+        let released = self
+            .gamepad
+            .as_mut()
+            .and_then(|gamepad| gamepad.release_all(&mut self.slots));
+        if let (Some(payload), Some(minitouch)) = (released, self.minitouch.as_mut()) {
+            let _ = minitouch.send(&payload);
+        }
+        self.mouse.release_all(&mut self.slots);
+        self.keys.release_all();
+        self.slots.reset();
+        self.minitouch = None;
+    }
+
+    fn send_minitouch(&mut self, adb_path: &str, serial: &str, payload: &str) {
+        if self.minitouch.is_none() {
+            self.minitouch = self.bridge().spawn_minitouch(adb_path, serial).ok();
+        }
+        let Some(minitouch) = self.minitouch.as_mut() else {
+            return;
+        };
+        if minitouch.send(payload).is_err() {
+            self.minitouch = None;
+        }
+    }
+
+    fn bridge(&self) -> MumuBridge {
+        MumuBridge::new(self.target_width.max(1), self.target_height.max(1))
+            .with_rotation(self.rotation)
+    }
+
+    fn displayed_size(&self) -> (u32, u32) {
+        if self.rotation.swaps_axes() {
+            (self.target_height, self.target_width)
+        } else {
+            (self.target_width, self.target_height)
+        }
+    }
+
+    fn rotation_route(&self) -> Option<(MumuBridge, String, String)> {
+        self.connected_device_ip.as_ref()?;
+        Some((
+            self.bridge(),
+            self.adb_path.clone()?,
+            self.mumu_serial.clone()?,
+        ))
+    }
+
+    fn apply_display(&mut self, size: Option<(u32, u32)>, rotation: Rotation) -> Vec<ControlFrame> {
+        if let Some((width, height)) = size {
+            self.target_width = width;
+            self.target_height = height;
+        }
+        self.rotation = rotation;
+        let bridge = self.bridge();
+        self.mouse.set_bridge(bridge);
+        if let Some(gamepad) = self.gamepad.as_mut() {
+            gamepad.set_bridge(bridge);
+        }
+        self.display
+            .update_display(self.target_width, self.target_height, self.rotation)
+    }
+
+    fn retarget(&mut self, profile: &RuntimeProfile) -> Vec<ControlFrame> {
+        self.display.update_profile(profile)
+    }
+
+    fn announce(&mut self, frames: &[ControlFrame]) {
+        if let (Some(ip), Some(port)) = (self.connected_device_ip.as_deref(), self.control_port) {
+            let _ = send_control_frames(ip, port, frames, self.outbound_authenticator.as_mut());
+        }
     }
 }
 
@@ -103,8 +302,20 @@ struct TouchEventPacket {
     pressure: f32,
 }
 
+impl From<PointerEvent> for TouchEventPacket {
+    fn from(event: PointerEvent) -> Self {
+        Self {
+            pointer_id: event.pointer_id,
+            action: event.action,
+            x: event.x,
+            y: event.y,
+            pressure: event.pressure,
+        }
+    }
+}
+
 #[derive(Debug)]
-enum AdbTouchCommand {
+enum AdbInputCommand {
     Tap {
         x: u32,
         y: u32,
@@ -116,55 +327,215 @@ enum AdbTouchCommand {
         end_y: u32,
         duration_ms: u32,
     },
+    Shell(ShellInput),
 }
 
-const DISCOVERY_PORT: u16 = 42042;
-const HOST_TOUCH_PORT: u16 = 42044;
 const DISCOVERY_TIMEOUT_MS: u64 = 1_300;
 const REQUEST_TIMEOUT_MS: u64 = 5_000;
+const HELLO_TIMEOUT_MS: u64 = 1_000;
 const PING_TIMEOUT_MS: u64 = 900;
+const ROTATION_REFRESH_MS: u64 = 2_000;
+const TOUCH_DATAGRAM_BUFFER: usize = 2_048;
+const ENV_PAIRING_KEY: &str = "LMC_PAIRING_KEY";
+const ENV_ALLOW_UNAUTHENTICATED: &str = "LMC_ALLOW_UNAUTHENTICATED";
+const ENV_GAMEPAD_LAYOUT: &str = "LMC_GAMEPAD_LAYOUT";
+
+fn host_capability() -> HostCapability {
+    HostCapability {
+        max_width: 2560,
+        max_height: 1440,
+        max_fps: 144,
+        codecs: nvenc::detect_codecs(nvenc::NVIDIA_SMI),
+    }
+}
 
 #[tauri::command]
 fn start_locked_session(
     payload: SessionPayload,
     state: tauri::State<HostState>,
 ) -> Result<SessionPayload, String> {
-    if payload.lock_policy != "turbo_lock" {
-        return Err("锁定策略必须为 turbo_lock".to_string());
-    }
-
-    let (width, height) = parse_resolution(&payload.resolution)?;
-    let profile = RuntimeProfile::new(
-        width,
-        height,
-        payload.fps,
-        payload.bitrate_kbps,
-        Codec::Hevc,
-        LockPolicy::TurboLock,
-    )
-    .map_err(|err| err.to_string())?;
+    let lock_policy = parse_lock_policy(&payload.lock_policy)
+        .map_err(|_| "锁定策略必须为 turbo_lock 或 adaptive".to_string())?;
+
+    let capability = host_capability();
+
+    let env_layer = ProfileLayer::from_env_vars(std::env::vars())
+        .map_err(|err| format!("环境变量配置无效: {err}"))?;
+    let mut loader = ProfileLoader::new()
+        .with_env(env_layer)
+        .with_capability(capability.clone());
+    let config_file = match std::env::var(ENV_CONFIG_PATH) {
+        Ok(path) => {
+            Some(ConfigFile::load(Path::new(&path)).map_err(|err| format!("配置文件无效: {err}"))?)
+        }
+        Err(_) => None,
+    };
+    if let Some(file) = &config_file {
+        loader = loader.with_file(file.clone());
+    }
 
-    let capability = HostCapability {
-        max_width: 2560,
-        max_height: 1440,
-        max_fps: 144,
-        codecs: vec![Codec::Hevc, Codec::H264],
+    loader = match &payload.profile {
+        Some(name) => loader.with_profile_name(name.clone()),
+        None => {
+            let mut cli = ProfileLayer::default();
+            cli.fps = Some(payload.fps);
+            cli.resolution = Some(payload.resolution.clone());
+            cli.bitrate_kbps = Some(payload.bitrate_kbps);
+            cli.lock_policy = Some(lock_policy);
+            loader.with_cli(cli)
+        }
+    };
+    let mut profile = loader.resolve().map_err(|err| err.to_string())?;
+
+    let mut registry = match &config_file {
+        Some(file) if !file.packages.is_empty() => Some(
+            ProfileRegistry::from_loader(&loader, profile.clone())
+                .map_err(|err| format!("游戏档位配置无效: {err}"))?,
+        ),
+        _ => None,
+    };
+    if let Some(registry) = registry.as_mut() {
+        let package = query_foreground_package(&state.touch_runtime);
+        registry.on_foreground_changed(package.as_deref());
+        profile = registry.profile_for(package.as_deref()).clone();
+    }
+
+    let peer_codecs = state
+        .peer
+        .lock()
+        .map_err(|_| "手机能力加锁失败".to_string())?
+        .as_ref()
+        .map_or_else(|| vec![Codec::H264], |hello| hello.codecs.clone());
+    let codec = capability
+        .negotiate_codec(&peer_codecs, Some(profile.codec))
+        .map_err(|err| err.to_string())?;
+    if codec != profile.codec {
+        profile = ProfileUpdate {
+            codec: Some(codec),
+            ..ProfileUpdate::default()
+        }
+        .apply_to(&profile)
+        .map_err(|err| format!("编码格式协商失败: {err}"))?;
+    }
+
+    let (width, height) = (profile.width, profile.height);
+    let resolved = SessionPayload {
+        fps: profile.fps,
+        resolution: format!("{width}x{height}"),
+        bitrate_kbps: profile.target_bitrate_kbps,
+        lock_policy: payload.lock_policy.clone(),
+        profile: payload.profile.clone(),
+        warnings: profile
+            .bitrate_warnings()
+            .iter()
+            .map(ToString::to_string)
+            .collect(),
     };
 
     let mut manager = state
         .session
         .lock()
         .map_err(|_| "会话管理器加锁失败".to_string())?;
-    manager
+    let started = manager
         .start(profile, capability)
         .map_err(|err| err.to_string())?;
 
+    if let Ok(mut current) = state.registry.lock() {
+        *current = registry;
+    }
+
+    if let Ok(mut runtime) = state.touch_runtime.lock() {
+        let frames = runtime.retarget(&started.profile);
+        runtime.announce(&frames);
+    }
+
+    Ok(resolved)
+}
+
+#[tauri::command]
+async fn sync_foreground_profile(
+    state: tauri::State<'_, HostState>,
+) -> Result<Option<ReconfigureResponse>, String> {
+    let package = query_foreground_package(&state.touch_runtime);
+
+    let profile = {
+        let mut registry = state
+            .registry
+            .lock()
+            .map_err(|_| "游戏档位表加锁失败".to_string())?;
+        match registry.as_mut() {
+            Some(registry) => registry.on_foreground_changed(package.as_deref()).cloned(),
+            None => None,
+        }
+    };
+    let Some(profile) = profile else {
+        return Ok(None);
+    };
+
+    let reconfigured = state
+        .session
+        .lock()
+        .map_err(|_| "会话管理器加锁失败".to_string())?
+        .apply_profile(profile)
+        .map_err(|err| err.to_string())?;
+    Ok(Some(apply_reconfigured(&state, &reconfigured)))
+}
+
+#[tauri::command]
+fn reconfigure_session(
+    payload: ReconfigurePayload,
+    state: tauri::State<HostState>,
+) -> Result<ReconfigureResponse, String> {
+    let resolution = match payload.resolution.as_deref() {
+        Some(value) => {
+            Some(parse_resolution(value).map_err(|_| format!("分辨率格式无效: {value}"))?)
+        }
+        None => None,
+    };
+    let update = ProfileUpdate {
+        resolution,
+        fps: payload.fps,
+        codec: None,
+        target_bitrate_kbps: payload.bitrate_kbps,
+        keyframe_interval_ms: payload.keyframe_interval_ms,
+    };
+
+    let reconfigured = state
+        .session
+        .lock()
+        .map_err(|_| "会话管理器加锁失败".to_string())?
+        .reconfigure(update)
+        .map_err(|err| err.to_string())?;
+    Ok(apply_reconfigured(&state, &reconfigured))
+}
+
+fn apply_reconfigured(state: &HostState, reconfigured: &Reconfigured) -> ReconfigureResponse {
+    let profile = &reconfigured.session.profile;
+    let (width, height) = (profile.width, profile.height);
     if let Ok(mut runtime) = state.touch_runtime.lock() {
-        runtime.target_width = width as u32;
-        runtime.target_height = height as u32;
+        let frames = runtime.retarget(profile);
+        runtime.announce(&frames);
     }
 
-    Ok(payload)
+    ReconfigureResponse {
+        rebuilt: reconfigured.kind == ReconfigureKind::Rebuild,
+        fps: profile.fps,
+        resolution: format!("{width}x{height}"),
+        bitrate_kbps: profile.target_bitrate_kbps,
+        keyframe_interval_ms: profile.keyframe_interval_ms,
+    }
+}
+
+fn query_foreground_package(runtime: &Arc<Mutex<TouchRuntime>>) -> Option<String> {
+    let mut guard = runtime.lock().ok()?;
+    let (adb_path, serial) = ensure_mumu_serial(&mut guard).ok()?;
+    let bridge = MumuBridge::new(guard.target_width.max(1), guard.target_height.max(1));
+    drop(guard);
+
+    bridge
+        .query_foreground_package(&adb_path, &serial)
+        .ok()
+        .flatten()
 }
 
 #[tauri::command]
@@ -188,6 +559,7 @@ fn session_status(state: tauri::State<HostState>) -> Result<SessionStatusPayload
         SessionState::Idle => SessionStateValue::Idle,
         SessionState::Starting => SessionStateValue::Starting,
         SessionState::Running => SessionStateValue::Running,
+        SessionState::Paused => SessionStateValue::Paused,
     };
 
     Ok(SessionStatusPayload { state: value })
@@ -208,7 +580,12 @@ fn scan_lan_devices() -> Result<Vec<LanDevice>, String> {
         .map_err(|err| format!("读取本地端口失败: {err}"))?
         .port();
 
-    let packet = format!("LMC_DISCOVER|{}|{}", desktop_name(), local_port);
+    let packet = LanMessage::Discover {
+        desktop_name: desktop_name(),
+        reply_port: local_port,
+    }
+    .encode()
+    .map_err(|err| format!("生成扫描广播失败: {err}"))?;
     socket
         .send_to(packet.as_bytes(), ("255.255.255.255", DISCOVERY_PORT))
         .map_err(|err| format!("发送扫描广播失败: {err}"))?;
@@ -247,7 +624,18 @@ fn request_device_connection(
         .set_read_timeout(Some(Duration::from_millis(450)))
         .map_err(|err| format!("设置连接超时失败: {err}"))?;
 
-    let message = format!("LMC_CONNECT_REQUEST|{}|{}", desktop_name(), HOST_TOUCH_PORT);
+    let pairing = state
+        .pairing
+        .lock()
+        .map_err(|_| "配对设置加锁失败".to_string())?
+        .clone();
+    let message = LanMessage::ConnectRequest {
+        desktop_name: desktop_name(),
+        touch_port: HOST_TOUCH_PORT,
+        session_nonce: None,
+    }
+    .encode()
+    .map_err(|err| format!("生成连接请求失败: {err}"))?;
     let target = format!("{}:{}", device.ip, device.control_port);
     let begin = Instant::now();
     let mut last_send = Instant::now() - Duration::from_millis(600);
@@ -267,10 +655,33 @@ fn request_device_connection(
                     continue;
                 }
 
-                let text = String::from_utf8_lossy(&buffer[..length])
-                    .trim()
-                    .to_string();
-                if text.starts_with("LMC_CONNECT_ACCEPT") {
+                let text = String::from_utf8_lossy(&buffer[..length]);
+                let reply = LanMessage::decode(&text);
+                if let Ok(LanMessage::ConnectAccept { .. }) = reply {
+                    let hello = receive_hello(&socket, &device.ip);
+                    let nonce = hello
+                        .as_ref()
+                        .and(pairing.key.as_ref())
+                        .map(|_| session_nonce());
+                    if let Some(hello) = &hello {
+                        if let Err(err) = welcome_peer(&state, &device, hello, nonce.as_deref()) {
+                            let _ = send_lan_message(
+                                &device.ip,
+                                device.control_port,
+                                &LanMessage::Disconnect {
+                                    origin: "HOST".to_string(),
+                                },
+                            );
+                            return Ok(LanConnectionStatusPayload {
+                                connected: false,
+                                device: None,
+                                message: format!("与手机协商失败：{err}"),
+                            });
+                        }
+                    }
+                    if let Ok(mut peer) = state.peer.lock() {
+                        *peer = hello;
+                    }
                     {
                         let mut current = state
                             .connection
@@ -278,17 +689,40 @@ fn request_device_connection(
                             .map_err(|_| "连接状态加锁失败".to_string())?;
                         *current = Some(device.clone());
                     }
+                    if let Ok(mut clock) = state.clock.lock() {
+                        clock.reset();
+                    }
 
                     let mut runtime = state
                         .touch_runtime
                         .lock()
                         .map_err(|_| "触控运行态加锁失败".to_string())?;
                     runtime.connected_device_ip = Some(device.ip.clone());
+                    runtime.control_port = Some(device.control_port);
                     runtime.mumu_serial = None;
                     runtime.down_points.clear();
                     runtime.last_points.clear();
+                    runtime.sequence.reset();
+                    runtime.reassembler.reset();
+                    runtime.compact_touch.reset();
+                    runtime.release_inputs();
+                    runtime.authenticator =
+                        pairing.authenticator(nonce.as_deref(), Direction::PhoneToHost);
+                    runtime.outbound_authenticator =
+                        pairing.authenticator(nonce.as_deref(), Direction::HostToPhone);
+                    runtime.allow_unauthenticated = runtime.authenticator.is_none()
+                        && (pairing.key.is_none() || pairing.allow_unauthenticated);
+                    let snapshot = runtime.display.snapshot();
+                    runtime.announce(&snapshot);
 
                     let bridge_status = match ensure_mumu_serial(&mut runtime) {
+                        Ok(_)
+                            if runtime.authenticator.is_none()
+                                && !runtime.allow_unauthenticated =>
+                        {
+                            "手机已确认连接，但未完成认证握手且未允许未认证输入，触控将被忽略。"
+                                .to_string()
+                        }
                         Ok((adb_path, serial)) => {
                             format!("手机已确认连接，可直接控制（MuMu: {serial}，ADB: {adb_path}）")
                         }
@@ -304,7 +738,7 @@ fn request_device_connection(
                     });
                 }
 
-                if text.starts_with("LMC_CONNECT_REJECT") {
+                if let Ok(LanMessage::ConnectReject { .. }) = reply {
                     return Ok(LanConnectionStatusPayload {
                         connected: false,
                         device: None,
@@ -335,13 +769,25 @@ fn disconnect_device(state: tauri::State<HostState>) -> Result<LanConnectionStat
             .map_err(|_| "连接状态加锁失败".to_string())?;
         current.take()
     };
+    if let Ok(mut peer) = state.peer.lock() {
+        *peer = None;
+    }
 
     if let Ok(mut runtime) = state.touch_runtime.lock() {
         runtime.clear_connection();
     }
+    if let Ok(mut clock) = state.clock.lock() {
+        clock.reset();
+    }
 
     if let Some(device) = device {
-        let _ = send_udp_message(&device.ip, device.control_port, "LMC_DISCONNECT|HOST");
+        let _ = send_lan_message(
+            &device.ip,
+            device.control_port,
+            &LanMessage::Disconnect {
+                origin: "HOST".to_string(),
+            },
+        );
         return Ok(LanConnectionStatusPayload {
             connected: false,
             device: None,
@@ -356,6 +802,30 @@ fn disconnect_device(state: tauri::State<HostState>) -> Result<LanConnectionStat
     })
 }
 
+#[tauri::command]
+fn set_pairing_key(key: Option<String>, state: tauri::State<HostState>) -> Result<(), String> {
+    let key = key
+        .map(|key| SessionKey::from_base64(&key))
+        .transpose()
+        .map_err(|err| format!("配对密钥无效: {err}"))?;
+    state
+        .pairing
+        .lock()
+        .map_err(|_| "配对设置加锁失败".to_string())?
+        .key = key;
+    Ok(())
+}
+
+#[tauri::command]
+fn set_allow_unauthenticated(allow: bool, state: tauri::State<HostState>) -> Result<(), String> {
+    state
+        .pairing
+        .lock()
+        .map_err(|_| "配对设置加锁失败".to_string())?
+        .allow_unauthenticated = allow;
+    Ok(())
+}
+
 #[tauri::command]
 fn connection_status(state: tauri::State<HostState>) -> Result<LanConnectionStatusPayload, String> {
     let device = {
@@ -367,12 +837,15 @@ fn connection_status(state: tauri::State<HostState>) -> Result<LanConnectionStat
     };
 
     if let Some(device) = device {
-        if ping_device(&device).is_ok() {
+        if let Ok(rtt_ms) = ping_device(&device, &state.clock) {
+            let latency = rtt_ms
+                .map(|rtt| format!("，往返延迟约 {rtt:.0} ms"))
+                .unwrap_or_default();
             return Ok(LanConnectionStatusPayload {
                 connected: true,
                 device: Some(device.clone()),
                 message: format!(
-                    "已连接手机：{}（{}），可在手机控制区直接操作",
+                    "已连接手机：{}（{}）{latency}，可在手机控制区直接操作",
                     device.name, device.ip
                 ),
             });
@@ -385,6 +858,9 @@ fn connection_status(state: tauri::State<HostState>) -> Result<LanConnectionStat
                 .map_err(|_| "连接状态加锁失败".to_string())?;
             *current = None;
         }
+        if let Ok(mut peer) = state.peer.lock() {
+            *peer = None;
+        }
 
         if let Ok(mut runtime) = state.touch_runtime.lock() {
             runtime.clear_connection();
@@ -404,40 +880,40 @@ fn connection_status(state: tauri::State<HostState>) -> Result<LanConnectionStat
     })
 }
 
-fn parse_resolution(value: &str) -> Result<(u16, u16), String> {
-    let (w, h) = value.split_once('x').ok_or("分辨率格式必须为 宽x高")?;
-    let width = w.parse::<u16>().map_err(|err| format!("宽度无效: {err}"))?;
-    let height = h.parse::<u16>().map_err(|err| format!("高度无效: {err}"))?;
-    Ok((width, height))
-}
-
 fn parse_discovery_response(message: &str, from: SocketAddr) -> Option<LanDevice> {
-    let parts = message.trim().split('|').collect::<Vec<_>>();
-    if parts.len() < 4 || parts[0] != "LMC_DEVICE" {
+    let LanMessage::Device {
+        model,
+        version,
+        control_port,
+    } = LanMessage::decode(message).ok()?
+    else {
         return None;
-    }
-
-    let control_port = parts[3].parse::<u16>().ok()?;
+    };
     let ip = from.ip().to_string();
 
     Some(LanDevice {
         id: format!("{}:{}", ip, control_port),
-        name: parts[1].to_string(),
+        name: model,
         ip,
         control_port,
-        version: parts[2].to_string(),
+        version,
     })
 }
 
-fn ping_device(device: &LanDevice) -> Result<(), String> {
+fn ping_device(device: &LanDevice, clock: &Mutex<ClockEstimator>) -> Result<Option<f64>, String> {
     let socket = UdpSocket::bind("0.0.0.0:0").map_err(|err| format!("创建心跳通道失败: {err}"))?;
     socket
         .set_read_timeout(Some(Duration::from_millis(250)))
         .map_err(|err| format!("设置心跳超时失败: {err}"))?;
 
+    let ping = LanMessage::Ping {
+        timestamp_ms: Some(epoch_ms()),
+    }
+    .encode()
+    .map_err(|err| format!("生成心跳失败: {err}"))?;
     socket
         .send_to(
-            "LMC_PING".as_bytes(),
+            ping.as_bytes(),
             format!("{}:{}", device.ip, device.control_port),
         )
         .map_err(|err| format!("发送心跳失败: {err}"))?;
@@ -451,12 +927,15 @@ fn ping_device(device: &LanDevice) -> Result<(), String> {
                 if from.ip().to_string() != device.ip {
                     continue;
                 }
-                let text = String::from_utf8_lossy(&buffer[..length])
-                    .trim()
-                    .to_string();
-                if text == "LMC_PONG" {
-                    return Ok(());
+                let text = String::from_utf8_lossy(&buffer[..length]);
+                let Ok(LanMessage::Pong { timestamps }) = LanMessage::decode(&text) else {
+                    continue;
+                };
+                let mut clock = clock.lock().map_err(|_| "时钟估计加锁失败".to_string())?;
+                if let Some(pong) = timestamps {
+                    let _ = clock.on_pong(&pong, epoch_ms());
                 }
+                return Ok(clock.rtt_ms());
             }
             Err(err)
                 if err.kind() == std::io::ErrorKind::WouldBlock
@@ -468,7 +947,10 @@ fn ping_device(device: &LanDevice) -> Result<(), String> {
     Err("手机心跳超时".to_string())
 }
 
-fn send_udp_message(ip: &str, port: u16, message: &str) -> Result<(), String> {
+fn send_lan_message(ip: &str, port: u16, message: &LanMessage) -> Result<(), String> {
+    let message = message
+        .encode()
+        .map_err(|err| format!("生成消息失败: {err}"))?;
     let socket = UdpSocket::bind("0.0.0.0:0").map_err(|err| format!("创建发送通道失败: {err}"))?;
     socket
         .send_to(message.as_bytes(), format!("{}:{}", ip, port))
@@ -476,6 +958,106 @@ fn send_udp_message(ip: &str, port: u16, message: &str) -> Result<(), String> {
     Ok(())
 }
 
+fn receive_hello(socket: &UdpSocket, ip: &str) -> Option<Hello> {
+    let begin = Instant::now();
+    let mut buffer = [0_u8; TOUCH_DATAGRAM_BUFFER];
+
+    while begin.elapsed() < Duration::from_millis(HELLO_TIMEOUT_MS) {
+        match socket.recv_from(&mut buffer) {
+            Ok((length, from)) if from.ip().to_string() == ip => {
+                if let Ok(ControlFrame::Hello(hello)) = WireCodec::Json.decode(&buffer[..length]) {
+                    return Some(hello);
+                }
+            }
+            Ok(_) => {}
+            Err(err)
+                if err.kind() == std::io::ErrorKind::WouldBlock
+                    || err.kind() == std::io::ErrorKind::TimedOut => {}
+            Err(_) => return None,
+        }
+    }
+    None
+}
+
+fn welcome_peer(
+    state: &HostState,
+    device: &LanDevice,
+    hello: &Hello,
+    session_nonce: Option<&str>,
+) -> Result<(), String> {
+    let capability = host_capability();
+    let running = state
+        .session
+        .lock()
+        .map_err(|_| "会话管理器加锁失败".to_string())?
+        .profile()
+        .cloned();
+    let profile = match running {
+        Some(profile) => profile,
+        None => ProfileLoader::new()
+            .with_capability(capability.clone())
+            .resolve()
+            .map_err(|err| err.to_string())?,
+    };
+
+    let negotiated = HostOffer {
+        profile: &profile,
+        capability: &capability,
+        features: &[FEATURE_PROTOBUF_CODEC],
+        session_nonce,
+    }
+    .negotiate(hello)
+    .map_err(|err| err.to_string())?;
+
+    send_control_frames(
+        &device.ip,
+        device.control_port,
+        &[ControlFrame::Welcome(negotiated.welcome)],
+        None,
+    )
+}
+
+fn send_control_frames(
+    ip: &str,
+    port: u16,
+    frames: &[ControlFrame],
+    mut authenticator: Option<&mut FrameAuthenticator>,
+) -> Result<(), String> {
+    if frames.is_empty() {
+        return Ok(());
+    }
+
+    let socket = UdpSocket::bind("0.0.0.0:0").map_err(|err| format!("创建发送通道失败: {err}"))?;
+    for frame in frames {
+        let bytes = match authenticator.as_deref_mut() {
+            Some(authenticator) => authenticator.seal(frame),
+            None => WireCodec::default().encode(frame),
+        }
+        .map_err(|err| format!("生成控制帧失败: {err}"))?;
+        socket
+            .send_to(&bytes, format!("{}:{}", ip, port))
+            .map_err(|err| format!("发送控制帧失败: {err}"))?;
+    }
+    Ok(())
+}
+
+fn epoch_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+fn session_nonce() -> String {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos();
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u128(nanos);
+    format!("{:016x}", hasher.finish())
+}
+
 fn desktop_name() -> String {
     std::env::var("COMPUTERNAME").unwrap_or_else(|_| "LinkMyComputer-PC".to_string())
 }
@@ -490,16 +1072,19 @@ fn start_touch_listener(runtime: Arc<Mutex<TouchRuntime>>) {
             }
         };
 
-        if let Err(err) = socket.set_read_timeout(Some(Duration::from_millis(250))) {
+        if let Err(err) =
+            socket.set_read_timeout(Some(Duration::from_millis(DEFAULT_REORDER_TIMEOUT_MS)))
+        {
             eprintln!("设置触控监听超时失败: {err}");
         }
 
-        let mut buffer = [0_u8; 1024];
+        let epoch = Instant::now();
+        let mut buffer = [0_u8; TOUCH_DATAGRAM_BUFFER];
         loop {
             match socket.recv_from(&mut buffer) {
                 Ok((length, from)) => {
-                    let payload = String::from_utf8_lossy(&buffer[..length]).to_string();
-                    handle_touch_datagram(&runtime, from, &payload);
+                    let now_ms = epoch.elapsed().as_millis() as u64;
+                    handle_touch_datagram(&runtime, from, &buffer[..length], now_ms);
                 }
                 Err(err)
                     if err.kind() == std::io::ErrorKind::WouldBlock
@@ -508,17 +1093,43 @@ fn start_touch_listener(runtime: Arc<Mutex<TouchRuntime>>) {
                     eprintln!("触控监听异常: {err}");
                 }
             }
+            poll_touch_sequence(&runtime, epoch.elapsed().as_millis() as u64);
         }
     });
 }
 
-fn handle_touch_datagram(runtime: &Arc<Mutex<TouchRuntime>>, from: SocketAddr, payload: &str) {
-    let event = match parse_touch_packet(payload) {
-        Some(event) => event,
-        None => return,
-    };
+fn start_rotation_poller(runtime: Arc<Mutex<TouchRuntime>>) {
+    thread::spawn(move || loop {
+        thread::sleep(Duration::from_millis(ROTATION_REFRESH_MS));
+
+        let route = match runtime.lock() {
+            Ok(guard) => guard.rotation_route(),
+            Err(_) => return,
+        };
+        let Some((bridge, adb_path, serial)) = route else {
+            continue;
+        };
+        let Ok(rotation) = bridge.query_rotation(&adb_path, &serial) else {
+            continue;
+        };
+        let size = bridge.query_display_size(&adb_path, &serial).ok();
+
+        if let Ok(mut guard) = runtime.lock() {
+            if guard.mumu_serial.as_deref() == Some(serial.as_str()) {
+                let frames = guard.apply_display(size, rotation);
+                guard.announce(&frames);
+            }
+        }
+    });
+}
 
-    let (adb_path, serial, command) = {
+fn handle_touch_datagram(
+    runtime: &Arc<Mutex<TouchRuntime>>,
+    from: SocketAddr,
+    datagram: &[u8],
+    now_ms: u64,
+) {
+    let (adb_path, serial, commands) = {
         let mut guard = match runtime.lock() {
             Ok(guard) => guard,
             Err(_) => return,
@@ -529,44 +1140,178 @@ fn handle_touch_datagram(runtime: &Arc<Mutex<TouchRuntime>>, from: SocketAddr, p
             return;
         }
 
-        let (adb_path, serial) = match ensure_mumu_serial(&mut guard) {
-            Ok(route) => route,
+        let runtime = &mut *guard;
+        let verified = match runtime.authenticator.as_mut() {
+            Some(authenticator) => match authenticator.open_datagram(datagram) {
+                Ok(verified) => verified,
+                Err(_) => return,
+            },
+            None if !runtime.allow_unauthenticated => return,
+            None => datagram,
+        };
+        runtime.sequence.heartbeat(now_ms);
+        let payload = match runtime.reassembler.push(verified, now_ms) {
+            Ok(Some(payload)) => payload,
+            Ok(None) | Err(_) => return,
+        };
+        let compact = is_compact_touch(&payload);
+        let decoded = match runtime.authenticator.as_mut() {
+            Some(authenticator) if compact => authenticator
+                .accept_compact_touch(&payload, &mut runtime.compact_touch)
+                .map(ControlFrame::Touch),
+            Some(authenticator) => authenticator.accept(&payload),
+            None if compact => runtime
+                .compact_touch
+                .decode(&payload)
+                .map(ControlFrame::Touch),
+            None => match parse_touch_packet(&String::from_utf8_lossy(&payload)) {
+                Some(envelope) => Ok(ControlFrame::Touch(tap_orphaned_release(
+                    envelope,
+                    &runtime.sequence,
+                ))),
+                None => match WireCodec::detect(&payload) {
+                    Some(codec) => codec.decode(&payload),
+                    None => return,
+                },
+            },
+        };
+        let frame = match decoded {
+            Ok(frame) => frame,
+            Err(_) => {
+                if let Some(request) = runtime.compact_touch.take_keyframe_request() {
+                    runtime.announce(&[request]);
+                }
+                return;
+            }
+        };
+        let planned = match frame {
+            ControlFrame::Touch(envelope) => {
+                let output = runtime.sequence.push(envelope, now_ms);
+                plan_touch_frames(runtime, output.frames)
+            }
+            frame => plan_input_frame(runtime, &frame),
+        };
+        match planned {
+            Some(planned) => planned,
+            None => return,
+        }
+    };
+
+    for command in commands {
+        let _ = execute_adb_input(&adb_path, &serial, command);
+    }
+}
+
+fn poll_touch_sequence(runtime: &Arc<Mutex<TouchRuntime>>, now_ms: u64) {
+    let (adb_path, serial, commands) = {
+        let mut guard = match runtime.lock() {
+            Ok(guard) => guard,
             Err(_) => return,
         };
+        if guard.connected_device_ip.is_none() {
+            return;
+        }
 
-        let command = plan_touch_command(&mut guard, &event);
-        (adb_path, serial, command)
+        let output = guard.sequence.poll(now_ms);
+        match plan_touch_frames(&mut guard, output.frames) {
+            Some(planned) => planned,
+            None => return,
+        }
     };
 
-    if let Some(command) = command {
-        let _ = execute_adb_touch(&adb_path, &serial, command);
+    for command in commands {
+        let _ = execute_adb_input(&adb_path, &serial, command);
     }
 }
 
-fn parse_touch_packet(payload: &str) -> Option<TouchEventPacket> {
-    let parts = payload.trim().split('|').collect::<Vec<_>>();
-    if parts.len() < 8 || parts[0] != "LMC_TOUCH" {
+fn plan_input_frame(
+    runtime: &mut TouchRuntime,
+    frame: &ControlFrame,
+) -> Option<(String, String, Vec<AdbInputCommand>)> {
+    let (adb_path, serial) = ensure_mumu_serial(runtime).ok()?;
+    let shell: Vec<ShellInput> = match frame {
+        ControlFrame::Key(event) => runtime.keys.on_key(event).into_iter().collect(),
+        ControlFrame::Text { text } => vec![ShellInput::text(text)],
+        ControlFrame::SystemKey(key) => vec![ShellInput::system_key(*key)],
+        ControlFrame::Mouse(event) => runtime
+            .mouse
+            .on_event(&mut runtime.slots, event)
+            .into_iter()
+            .filter_map(|output| match output {
+                MouseOutput::Shell(input) => Some(input),
+                MouseOutput::Minitouch(payload) => {
+                    runtime.send_minitouch(&adb_path, &serial, &payload);
+                    None
+                }
+            })
+            .collect(),
+        ControlFrame::Gamepad(state) => {
+            let payload = runtime
+                .gamepad
+                .as_mut()
+                .and_then(|gamepad| gamepad.on_state(&mut runtime.slots, state));
+            if let Some(payload) = payload {
+                runtime.send_minitouch(&adb_path, &serial, &payload);
+            }
+            Vec::new()
+        }
+        _ => return None,
+    };
+
+    let commands = shell
+        .into_iter()
+        .map(AdbInputCommand::Shell)
+        .collect::<Vec<_>>();
+    Some((adb_path, serial, commands))
+}
+
+fn plan_touch_frames(
+    runtime: &mut TouchRuntime,
+    frames: Vec<TouchEnvelope>,
+) -> Option<(String, String, Vec<AdbInputCommand>)> {
+    let events = frames
+        .into_iter()
+        .flat_map(|frame| frame.events)
+        .map(TouchEventPacket::from)
+        .collect::<Vec<_>>();
+    if events.is_empty() {
         return None;
     }
 
-    let pointer_id = parts[2].parse::<u8>().ok()?;
-    let action = match parts[3] {
-        "DOWN" => PointerAction::Down,
-        "MOVE" => PointerAction::Move,
-        "UP" => PointerAction::Up,
-        "CANCEL" => PointerAction::Cancel,
-        _ => return None,
+    let (adb_path, serial) = ensure_mumu_serial(runtime).ok()?;
+    let commands = events
+        .iter()
+        .filter_map(|event| plan_touch_command(runtime, event))
+        .collect::<Vec<_>>();
+    Some((adb_path, serial, commands))
+}
+
+fn parse_touch_packet(payload: &str) -> Option<TouchEnvelope> {
+    let LanMessage::Touch { frame_id, event } = LanMessage::decode(payload).ok()? else {
+        return None;
     };
 
-    Some(TouchEventPacket {
-        pointer_id,
-        action,
-        x: parts[4].parse::<f32>().ok()?,
-        y: parts[5].parse::<f32>().ok()?,
-        pressure: parts[6].parse::<f32>().ok()?,
+    Some(TouchEnvelope {
+        frame_id,
+        events: vec![event],
     })
 }
 
+fn tap_orphaned_release(mut envelope: TouchEnvelope, sequence: &SequenceTracker) -> TouchEnvelope {
+    if let [event] = envelope.events.as_slice() {
+        if event.action == PointerAction::Up
+            && !sequence.active_pointers().contains(&event.pointer_id)
+        {
+            let down = PointerEvent {
+                action: PointerAction::Down,
+                ..event.clone()
+            };
+            envelope.events.insert(0, down);
+        }
+    }
+    envelope
+}
+
 fn ensure_mumu_serial(runtime: &mut TouchRuntime) -> Result<(String, String), String> {
     if let (Some(adb_path), Some(serial)) = (runtime.adb_path.clone(), runtime.mumu_serial.clone())
     {
@@ -608,14 +1353,15 @@ fn ensure_mumu_serial(runtime: &mut TouchRuntime) -> Result<(String, String), St
 fn plan_touch_command(
     runtime: &mut TouchRuntime,
     event: &TouchEventPacket,
-) -> Option<AdbTouchCommand> {
+) -> Option<AdbInputCommand> {
     let _ = event.pressure;
     if event.pointer_id != 0 {
         return None;
     }
 
-    let x = to_pixel(event.x, runtime.target_width);
-    let y = to_pixel(event.y, runtime.target_height);
+    let (width, height) = runtime.displayed_size();
+    let x = to_pixel(event.x, width);
+    let y = to_pixel(event.y, height);
 
     match event.action {
         PointerAction::Down => {
@@ -639,9 +1385,9 @@ fn plan_touch_command(
 
             let distance = start.0.abs_diff(end.0) + start.1.abs_diff(end.1);
             if distance <= 24 {
-                Some(AdbTouchCommand::Tap { x: end.0, y: end.1 })
+                Some(AdbInputCommand::Tap { x: end.0, y: end.1 })
             } else {
-                Some(AdbTouchCommand::Swipe {
+                Some(AdbInputCommand::Swipe {
                     start_x: start.0,
                     start_y: start.1,
                     end_x: end.0,
@@ -664,15 +1410,19 @@ fn to_pixel(normalized: f32, max: u32) -> u32 {
     value.round() as u32
 }
 
-fn execute_adb_touch(adb_path: &str, serial: &str, command: AdbTouchCommand) -> Result<(), String> {
+fn execute_adb_input(adb_path: &str, serial: &str, command: AdbInputCommand) -> Result<(), String> {
     let mut process = Command::new(adb_path);
-    process.arg("-s").arg(serial).arg("shell").arg("input");
+    process.arg("-s").arg(serial).arg("shell");
 
     match command {
-        AdbTouchCommand::Tap { x, y } => {
-            process.arg("tap").arg(x.to_string()).arg(y.to_string());
+        AdbInputCommand::Tap { x, y } => {
+            process
+                .arg("input")
+                .arg("tap")
+                .arg(x.to_string())
+                .arg(y.to_string());
         }
-        AdbTouchCommand::Swipe {
+        AdbInputCommand::Swipe {
             start_x,
             start_y,
             end_x,
@@ -680,6 +1430,7 @@ fn execute_adb_touch(adb_path: &str, serial: &str, command: AdbTouchCommand) ->
             duration_ms,
         } => {
             process
+                .arg("input")
                 .arg("swipe")
                 .arg(start_x.to_string())
                 .arg(start_y.to_string())
@@ -687,11 +1438,14 @@ fn execute_adb_touch(adb_path: &str, serial: &str, command: AdbTouchCommand) ->
                 .arg(end_y.to_string())
                 .arg(duration_ms.to_string());
         }
+        AdbInputCommand::Shell(input) => {
+            process.args(input.shell_args());
+        }
     }
 
     let output = process
         .output()
-        .map_err(|err| format!("执行 adb 触控失败（{}）: {err}", adb_path))?;
+        .map_err(|err| format!("执行 adb 输入失败（{}）: {err}", adb_path))?;
 
     if output.status.success() {
         return Ok(());
@@ -699,9 +1453,9 @@ fn execute_adb_touch(adb_path: &str, serial: &str, command: AdbTouchCommand) ->
 
     let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
     if stderr.is_empty() {
-        return Err("adb 触控命令执行失败（无错误输出）".to_string());
+        return Err("adb 输入命令执行失败（无错误输出）".to_string());
     }
-    Err(format!("adb 触控命令执行失败: {stderr}"))
+    Err(format!("adb 输入命令执行失败: {stderr}"))
 }
 
 fn resolve_adb_candidates(current: Option<&str>) -> Vec<String> {
@@ -816,20 +1570,29 @@ fn collect_adb_under(dir: &Path, depth: usize, out: &mut Vec<String>, seen: &mut
 fn main() {
     let touch_runtime = Arc::new(Mutex::new(TouchRuntime::default()));
     start_touch_listener(touch_runtime.clone());
+    start_rotation_poller(touch_runtime.clone());
 
     tauri::Builder::default()
         .manage(HostState {
             session: Mutex::new(SessionManager::new()),
+            registry: Mutex::new(None),
             connection: Mutex::new(None),
+            peer: Mutex::new(None),
+            pairing: Mutex::new(PairingSettings::from_env()),
+            clock: Mutex::new(ClockEstimator::default()),
             touch_runtime,
         })
         .invoke_handler(tauri::generate_handler![
             start_locked_session,
+            sync_foreground_profile,
+            reconfigure_session,
             stop_session,
             session_status,
             scan_lan_devices,
             request_device_connection,
             disconnect_device,
+            set_pairing_key,
+            set_allow_unauthenticated,
             connection_status
         ])
         .run(tauri::generate_context!())
@@ -1,15 +1,31 @@
 import { invoke } from "@tauri-apps/api/core";
 
-export type LockPolicy = "turbo_lock";
+export type LockPolicy = "turbo_lock" | "adaptive";
 
 export type SessionPayload = {
   fps: 60 | 90 | 120 | 144;
   resolution: "1280x720" | "1600x900" | "1920x1080" | "2460x1080";
   bitrateKbps: number;
   lockPolicy: LockPolicy;
+  warnings?: string[];
 };
 
-export type SessionState = "idle" | "starting" | "running";
+export type ReconfigurePayload = {
+  fps?: SessionPayload["fps"];
+  resolution?: SessionPayload["resolution"];
+  bitrateKbps?: number;
+  keyframeIntervalMs?: number;
+};
+
+export type ReconfigureResponse = {
+  rebuilt: boolean;
+  fps: number;
+  resolution: string;
+  bitrateKbps: number;
+  keyframeIntervalMs: number;
+};
+
+export type SessionState = "idle" | "starting" | "running" | "paused";
 
 export type SessionStatusResponse = {
   state: SessionState;
@@ -37,6 +53,24 @@ export async function startLockedSession(payload: SessionPayload): Promise<Sessi
   return payload;
 }
 
+export async function syncForegroundProfile(): Promise<ReconfigureResponse | null> {
+  if (hasTauriRuntime()) {
+    return invoke<ReconfigureResponse | null>("sync_foreground_profile");
+  }
+
+  return null;
+}
+
+export async function reconfigureSession(
+  payload: ReconfigurePayload
+): Promise<ReconfigureResponse | null> {
+  if (hasTauriRuntime()) {
+    return invoke<ReconfigureResponse>("reconfigure_session", { payload });
+  }
+
+  return null;
+}
+
 export async function stopSession(): Promise<void> {
   if (hasTauriRuntime()) {
     await invoke("stop_session");
@@ -81,6 +115,18 @@ export async function disconnectDevice(): Promise<LanConnectionStatus> {
   };
 }
 
+export async function setPairingKey(key: string | null): Promise<void> {
+  if (hasTauriRuntime()) {
+    await invoke("set_pairing_key", { key });
+  }
+}
+
+export async function setAllowUnauthenticated(allow: boolean): Promise<void> {
+  if (hasTauriRuntime()) {
+    await invoke("set_allow_unauthenticated", { allow });
+  }
+}
+
 export async function getConnectionStatus(): Promise<LanConnectionStatus> {
   if (hasTauriRuntime()) {
     return invoke<LanConnectionStatus>("connection_status");
@@ -7,15 +7,21 @@ import {
   getSessionStatus,
   LanConnectionStatus,
   LanDevice,
+  reconfigureSession,
+  ReconfigurePayload,
   requestDeviceConnection,
   scanLanDevices,
   SessionPayload,
+  SessionState,
   startLockedSession,
-  stopSession
+  stopSession,
+  syncForegroundProfile
 } from "./lib/api";
 import { SessionPage, UiSessionStatus } from "./pages/Session";
 import "./styles.css";
 
+const FOREGROUND_POLL_MS = 4000;
+
 function HostConsoleApp() {
   const [status, setStatus] = useState<UiSessionStatus>("idle");
   const [lastMessage, setLastMessage] = useState<string>("准备就绪，可随时启动会话。");
@@ -39,6 +45,30 @@ function HostConsoleApp() {
     };
   }, []);
 
+  useEffect(() => {
+    if (status !== "running" && status !== "paused") {
+      return;
+    }
+
+    const timer = window.setInterval(() => {
+      void syncForegroundProfile()
+        .then((response) => {
+          if (response) {
+            setLastMessage(
+              `已切换游戏档位：${response.resolution}@${response.fps}Hz · ${response.bitrateKbps.toLocaleString()} kbps`
+            );
+          }
+        })
+        .catch((error) => {
+          setLastMessage(`切换游戏档位失败：${formatError(error)}`);
+        });
+    }, FOREGROUND_POLL_MS);
+
+    return () => {
+      window.clearInterval(timer);
+    };
+  }, [status]);
+
   const refreshStatus = async () => {
     try {
       const response = await getSessionStatus();
@@ -115,6 +145,21 @@ function HostConsoleApp() {
     }
   };
 
+  const handleReconfigure = async (payload: ReconfigurePayload) => {
+    try {
+      const response = await reconfigureSession(payload);
+      if (!response) {
+        return;
+      }
+      const mode = response.rebuilt ? "已重建管线" : "已在线调整";
+      setLastMessage(
+        `${mode}：${response.resolution}@${response.fps}Hz · ${response.bitrateKbps.toLocaleString()} kbps`
+      );
+    } catch (error) {
+      setLastMessage(`调整失败：${formatError(error)}`);
+    }
+  };
+
   const handleStop = async () => {
     try {
       await stopSession();
@@ -137,12 +182,13 @@ function HostConsoleApp() {
       onConnectDevice={handleConnectDevice}
       onDisconnectDevice={handleDisconnectDevice}
       onStartSession={handleStart}
+      onReconfigureSession={handleReconfigure}
       onStopSession={handleStop}
     />
   );
 }
 
-function mapBackendState(value: "idle" | "starting" | "running"): UiSessionStatus {
+function mapBackendState(value: SessionState): UiSessionStatus {
   return value;
 }
 
@@ -12,7 +12,8 @@ const baseProps = {
   },
   onScanDevices: vi.fn(),
   onConnectDevice: vi.fn(),
-  onDisconnectDevice: vi.fn()
+  onDisconnectDevice: vi.fn(),
+  onReconfigureSession: vi.fn()
 };
 
 describe("SessionPage", () => {
@@ -70,6 +71,41 @@ describe("SessionPage", () => {
     expect(screen.getByRole("button", { name: /停止会话/i })).toBeEnabled();
   });
 
+  it("运行中可以把当前配置应用到会话", () => {
+    const onReconfigure = vi.fn();
+
+    const { rerender } = render(
+      <SessionPage
+        status="idle"
+        {...baseProps}
+        onReconfigureSession={onReconfigure}
+        onStartSession={vi.fn()}
+        onStopSession={vi.fn()}
+      />
+    );
+
+    expect(screen.getByRole("button", { name: /应用到当前会话/i })).toBeDisabled();
+
+    rerender(
+      <SessionPage
+        status="running"
+        {...baseProps}
+        onReconfigureSession={onReconfigure}
+        onStartSession={vi.fn()}
+        onStopSession={vi.fn()}
+      />
+    );
+
+    fireEvent.change(screen.getByLabelText(/帧率/i), { target: { value: "120" } });
+    fireEvent.click(screen.getByRole("button", { name: /应用到当前会话/i }));
+
+    expect(onReconfigure).toHaveBeenCalledWith({
+      fps: 120,
+      resolution: "2460x1080",
+      bitrateKbps: 80000
+    });
+  });
+
   it("可以显示扫描到的手机并发起连接", () => {
     const onStart = vi.fn();
     const onStop = vi.fn();
@@ -1,8 +1,8 @@
 import { FormEvent, useMemo, useState } from "react";
 
-import { LanDevice, SessionPayload } from "../lib/api";
+import { LanDevice, ReconfigurePayload, SessionPayload } from "../lib/api";
 
-export type UiSessionStatus = "idle" | "starting" | "running" | "error";
+export type UiSessionStatus = "idle" | "starting" | "running" | "paused" | "error";
 
 type Props = {
   status: UiSessionStatus;
@@ -18,6 +18,7 @@ type Props = {
   onConnectDevice: (device: LanDevice) => Promise<void> | void;
   onDisconnectDevice: () => Promise<void> | void;
   onStartSession: (payload: SessionPayload) => Promise<void> | void;
+  onReconfigureSession: (payload: ReconfigurePayload) => Promise<void> | void;
   onStopSession: () => Promise<void> | void;
 };
 
@@ -28,6 +29,7 @@ const STATUS_LABEL: Record<UiSessionStatus, string> = {
   idle: "空闲",
   starting: "启动中",
   running: "运行中",
+  paused: "已暂停",
   error: "错误"
 };
 
@@ -41,6 +43,7 @@ export function SessionPage({
   onConnectDevice,
   onDisconnectDevice,
   onStartSession,
+  onReconfigureSession,
   onStopSession
 }: Props) {
   const [fps, setFps] = useState<(typeof FPS_VALUES)[number]>(144);
@@ -62,6 +65,8 @@ export function SessionPage({
     });
   };
 
+  const sessionActive = status === "running" || status === "paused";
+
   return (
     <main className="host-shell">
       <section className="host-panel">
@@ -189,13 +194,23 @@ export function SessionPage({
             <button type="submit" disabled={status === "starting"}>
               {status === "starting" ? "启动中..." : "启动锁定会话"}
             </button>
+            <button
+              type="button"
+              className="ghost"
+              onClick={() => {
+                void onReconfigureSession({ fps, resolution, bitrateKbps });
+              }}
+              disabled={!sessionActive}
+            >
+              应用到当前会话
+            </button>
             <button
               type="button"
               className="ghost"
               onClick={() => {
                 void onStopSession();
               }}
-              disabled={status !== "running"}
+              disabled={!sessionActive}
             >
               停止会话
             </button>